@@ -1373,6 +1373,54 @@ EOF
   assert_not_match "content-encoding"
 }
 
+@test "asset configuration via .ic-assets.json5 - unknown nested fields are reported by the build" {
+  dfx_new_frontend
+
+  dfx_start
+
+  echo '[
+    {
+      "match": "index.html",
+      "cache": {
+        "max-age": 10
+      }
+    },
+    {
+      "match": "*.js",
+      "cache": {
+        "maxAge": 10,
+        "age": 20
+      }
+    }
+  ]' > src/e2e_project_frontend/assets/.ic-assets.json5
+
+  dfx canister create --all
+  assert_command dfx build e2e_project_frontend
+  assert_contains "Invalid field in asset config file"
+  assert_contains ".ic-assets.json5: unknown field \`max-age\` in the \`cache\` of rule 1, expected \`max_age\`"
+  assert_contains ".ic-assets.json5: unknown field \`maxAge\` in the \`cache\` of rule 2, expected \`max_age\`"
+  assert_contains ".ic-assets.json5: unknown field \`age\` in the \`cache\` of rule 2, expected \`max_age\`"
+}
+
+@test "asset configuration via .ic-assets.json5 - unknown fields of rules fail the build" {
+  dfx_new_frontend
+
+  dfx_start
+
+  echo '[
+    {
+      "match": "index.html",
+      "header": {
+        "x-extra-header": "x-extra-value"
+      }
+    }
+  ]' > src/e2e_project_frontend/assets/.ic-assets.json5
+
+  dfx canister create --all
+  assert_command_fail dfx build e2e_project_frontend
+  assert_contains "unknown field \`header\`"
+}
+
 @test "aliasing rules: <filename> to <filename>.html or <filename>/index.html" {
   echo "test alias file" >'src/e2e_project_frontend/assets/test_alias_file.html'
   mkdir 'src/e2e_project_frontend/assets/index_test'
@@ -38,6 +38,40 @@ teardown() {
   assert_match "$ID"
 }
 
+@test "checks and caches custom canister downloads with a hash" {
+  install_asset wasm/identity
+  mkdir -p www/wasm
+  mv main.wasm www/wasm/
+  mv main.did www/wasm
+  start_webserver --directory www
+  dfx_start
+
+  dfx_new
+
+  jq '.canisters={}' dfx.json | sponge dfx.json
+
+  jq '.canisters.e2e_project.candid="http://localhost:'"$E2E_WEB_SERVER_PORT"'/wasm/main.did"' dfx.json | sponge dfx.json
+  jq '.canisters.e2e_project.wasm="http://localhost:'"$E2E_WEB_SERVER_PORT"'/wasm/main.wasm"' dfx.json | sponge dfx.json
+  jq '.canisters.e2e_project.type="custom"' dfx.json | sponge dfx.json
+  jq '.canisters.e2e_project.wasm_hash="0000000000000000000000000000000000000000000000000000000000000000"' dfx.json | sponge dfx.json
+  dfx canister create --all
+
+  assert_command_fail dfx build
+  assert_contains "but 0000000000000000000000000000000000000000000000000000000000000000 was expected"
+
+  WASM_HASH="$(sha256sum www/wasm/main.wasm | cut -d " " -f 1)"
+  CANDID_HASH="$(sha256sum www/wasm/main.did | cut -d " " -f 1)"
+  jq '.canisters.e2e_project.wasm_hash="'"$WASM_HASH"'"' dfx.json | sponge dfx.json
+  jq '.canisters.e2e_project.candid_hash="'"$CANDID_HASH"'"' dfx.json | sponge dfx.json
+  assert_command dfx build
+
+  # a warm cache does not download the files again
+  rm -rf www/wasm
+  rm -rf .dfx/local/canisters/e2e_project
+  assert_command dfx build
+  assert_file_exists .dfx/local/canisters/e2e_project/download-main.wasm
+}
+
 @test "report an error if a canister defines both a wasm url and a build step" {
   install_asset wasm/identity
   mkdir -p www/wasm
@@ -206,7 +240,21 @@ teardown() {
   dfx_start
   dfx canister create --all
   assert_command dfx build
-  assert_match "warning \[M0145\], this pattern of type"
+  assert_match "warning \[M0145\] at .*main.mo:[0-9]+:[0-9]+: this pattern of type"
+}
+
+@test "build applies Motoko warning levels" {
+  install_asset warning
+  dfx_start
+  dfx canister create --all
+
+  jq '.canisters.e2e_project_backend.warning_levels={M0145: "allow"}' dfx.json | sponge dfx.json
+  assert_command dfx build
+  assert_not_contains "M0145"
+
+  jq '.canisters.e2e_project_backend.warning_levels={} | .canisters.e2e_project_backend.warnings_as_errors=true' dfx.json | sponge dfx.json
+  assert_command_fail dfx build
+  assert_contains "M0145"
 }
 
 @test "build fails on unknown imports" {
@@ -335,3 +383,120 @@ teardown() {
 
   assert_command timeout 30s dfx build --check
 }
+
+@test "build includes canister definitions from other files" {
+  jq '.canisters.e2e_project_backend | .main="main.mo" | {canisters: {e2e_project_backend: .}}' dfx.json > src/e2e_project_backend/dfx.canister.json
+  jq 'del(.canisters.e2e_project_backend) | .include=["src/*/dfx.canister.json"]' dfx.json | sponge dfx.json
+
+  assert_command dfx build --check
+  assert_command ls .dfx/local/canisters/e2e_project_backend/e2e_project_backend.wasm
+
+  jq '.canisters.e2e_project_backend={}' dfx.json | sponge dfx.json
+  assert_command_fail dfx build --check
+  assert_contains "Canister 'e2e_project_backend' is defined in both dfx.json and"
+  assert_contains "src/e2e_project_backend/dfx.canister.json"
+}
+
+@test "build selects canisters by tag" {
+  jq '.canisters.e2e_project_backend.tags=["api"] | .canisters.other=(.canisters.e2e_project_backend | .tags=["batch"])' dfx.json | sponge dfx.json
+
+  assert_command dfx build --check --tag api
+  assert_command ls .dfx/local/canisters/e2e_project_backend/e2e_project_backend.wasm
+  assert_command_fail ls .dfx/local/canisters/other/other.wasm
+
+  assert_command dfx build --check --exclude api
+  assert_command ls .dfx/local/canisters/other/other.wasm
+
+  assert_command_fail dfx build --check --tag missing
+  assert_contains "No canister in the configuration file has the tag 'missing'."
+
+  assert_command_fail dfx build --check --tag api e2e_project_backend
+  assert_contains "--tag and --exclude cannot be used together with a canister name."
+
+  jq '.canisters.other.dependencies=["e2e_project_backend"]' dfx.json | sponge dfx.json
+  assert_command_fail dfx build --check --tag batch --exclude api
+  assert_contains "Canister 'other' depends on canister 'e2e_project_backend', which is left out with --exclude."
+}
+
+@test "build canisters in parallel" {
+  install_asset wasm/identity
+  jq '.canisters={}' dfx.json | sponge dfx.json
+  for name in a b c; do
+    jq '.canisters.'"$name"'={type: "custom", wasm: "main.wasm", candid: "main.did", build: "echo built '"$name"'"}' dfx.json | sponge dfx.json
+  done
+  jq '.canisters.c.dependencies=["a", "b"]' dfx.json | sponge dfx.json
+
+  assert_command dfx build --check --jobs 2
+  assert_contains "[a] built a"
+  assert_contains "[b] built b"
+  assert_contains "[c] built c"
+
+  jq '.canisters.a.build="false"' dfx.json | sponge dfx.json
+  assert_command_fail dfx build --check --jobs 2
+  assert_contains "Did not build c because of the failure above."
+}
+
+@test "build reuses canisters whose build inputs did not change" {
+  install_asset wasm/identity
+  jq '.canisters={}' dfx.json | sponge dfx.json
+  echo "input" > input.txt
+  jq '.canisters.custom={type: "custom", wasm: "main.wasm", candid: "main.did", build: "echo building custom", build_inputs: ["input.txt"]}' dfx.json | sponge dfx.json
+  dfx_start
+  dfx canister create --all
+
+  assert_command dfx build
+  assert_contains "building custom"
+
+  rm .dfx/local/canisters/custom/index.js
+  assert_command dfx build
+  assert_contains "Canister 'custom' is up to date. Reusing its previous build."
+  assert_not_contains "building custom"
+  # the post-build steps still run
+  assert_file_exists .dfx/local/canisters/custom/index.js
+
+  assert_command dfx build --force-rebuild
+  assert_contains "building custom"
+
+  echo "changed" > input.txt
+  assert_command dfx build
+  assert_contains "building custom"
+}
+
+@test "build instruments canisters for profiling" {
+  cp "${BATS_TEST_DIRNAME}/../assets/counter/counter.mo" src/e2e_project_backend/main.mo
+  dfx_start
+  dfx canister create --all
+
+  assert_command dfx deploy e2e_project_backend --instrument
+  assert_command dfx canister call e2e_project_backend inc
+  assert_command dfx canister profile e2e_project_backend
+  assert_contains "Wrote the profile of"
+  assert_file_exists e2e_project_backend.folded
+  assert_command grep inc e2e_project_backend.folded
+}
+
+@test "build reports Wasm sizes and enforces max_wasm_size" {
+  dfx_start
+  dfx canister create --all
+
+  assert_command dfx build e2e_project_backend --size-report
+  assert_contains "Size report of canister 'e2e_project_backend':"
+  assert_contains "Sections:"
+  assert_contains "icp:public candid:service"
+  assert_contains "Largest functions:"
+
+  jq '.canisters.e2e_project_backend.max_wasm_size="1KB"' dfx.json | sponge dfx.json
+  assert_command_fail dfx build e2e_project_backend
+  assert_contains "exceeds its max_wasm_size of 1000 bytes"
+}
+
+@test "build runs the build commands of included canisters in the directory of their file" {
+  mkdir -p services/custom
+  echo -n -e '\x00asm\x01\x00\x00\x00' > services/custom/empty.wasm
+  echo 'service : {}' > services/custom/main.did
+  echo '{ "canisters": { "custom": { "type": "custom", "candid": "main.did", "wasm": "main.wasm", "build": "cp empty.wasm main.wasm" } } }' > services/custom/dfx.canister.json
+  jq '.include=["services/*/dfx.canister.json"]' dfx.json | sponge dfx.json
+
+  assert_command dfx build custom --check
+  assert_command ls services/custom/main.wasm
+}
@@ -35,3 +35,28 @@ teardown() {
   assert_command dfx build
   assert_match "Audit found vulnerabilities"
 }
+
+@test "build writes .env for the selected network when reusing a rust build" {
+  cd ..
+  dfx_new_rust hello
+  jq '.output_env_file=".env"' dfx.json | sponge dfx.json
+  dfx_start
+  setup_actuallylocal_project_network
+  dfx canister create --all
+  dfx canister create --all --network actuallylocal
+
+  assert_command dfx build
+  assert_contains "DFX_NETWORK='local'" "$(< .env)"
+  assert_command dfx build --network actuallylocal
+  assert_contains "DFX_NETWORK='actuallylocal'" "$(< .env)"
+
+  assert_command dfx build
+  assert_contains "Canister 'hello_backend' is up to date. Reusing its previous build."
+  assert_contains "DFX_NETWORK='local'" "$(< .env)"
+  assert_not_contains "DFX_NETWORK='actuallylocal'" "$(< .env)"
+
+  rm .env
+  assert_command dfx build --network actuallylocal
+  assert_contains "Canister 'hello_backend' is up to date. Reusing its previous build."
+  assert_contains "DFX_NETWORK='actuallylocal'" "$(< .env)"
+}
@@ -0,0 +1,66 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+
+  dfx_new
+  cp "${BATS_TEST_DIRNAME}/../assets/counter/counter.mo" src/e2e_project_backend/main.mo
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "clone creates a canister with the code and settings of another canister" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  dfx canister update-settings e2e_project_backend --freezing-threshold 1234567
+  dfx canister call e2e_project_backend write '(5)'
+
+  assert_command dfx canister clone e2e_project_backend --as backend_copy
+  assert_contains "Cloned canister e2e_project_backend to backend_copy with canister id"
+
+  assert_command jq -r .backend_copy.local .dfx/local/canister_ids.json
+  assert_eq "$(dfx canister id backend_copy)"
+  assert_neq "$(dfx canister id backend_copy)" "$(dfx canister id e2e_project_backend)"
+
+  assert_command dfx canister status backend_copy
+  assert_contains "Freezing threshold: 1_234_567"
+  assert_eq "$(dfx canister info backend_copy | grep 'Module hash')" "$(dfx canister info e2e_project_backend | grep 'Module hash')"
+
+  assert_command dfx canister call backend_copy inc_read
+  assert_eq "(1 : nat)"
+
+  assert_command_fail dfx canister clone e2e_project_backend --as backend_copy
+  assert_contains "A canister named backend_copy already exists"
+}
+
+@test "clone finds the module by hash or takes it from --wasm" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  cp .dfx/local/canisters/e2e_project_backend/e2e_project_backend.wasm module.wasm
+  rm .dfx/local/canisters/e2e_project_backend/e2e_project_backend.wasm
+
+  assert_command_fail dfx canister clone e2e_project_backend --as backend_copy
+  assert_contains "Cannot find a Wasm module with hash"
+
+  assert_command dfx canister clone e2e_project_backend --as backend_copy --wasm module.wasm
+  assert_command dfx canister call backend_copy read
+  assert_eq "(0 : nat)"
+}
+
+@test "clone does not copy the state of the source canister" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  dfx canister call e2e_project_backend write '(7)'
+
+  assert_command dfx canister clone e2e_project_backend --as backend_copy
+  assert_command dfx canister call backend_copy read
+  assert_eq "(0 : nat)"
+  assert_command dfx canister call e2e_project_backend read
+  assert_eq "(7 : nat)"
+}
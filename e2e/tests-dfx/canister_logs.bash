@@ -65,3 +65,101 @@ dfx_canister_logs_tail_n_1() {
   assert_not_contains "Alice"
   assert_contains "Bob"
 }
+
+@test "canister logs can be filtered" {
+  install_asset logs
+  dfx_start
+  dfx canister create --all
+  dfx build
+  dfx canister install e2e_project
+  dfx canister call e2e_project hello Alice
+  dfx canister call e2e_project hello Bob
+  sleep 2
+
+  assert_command dfx canister logs e2e_project --tail 1
+  assert_not_contains "Alice"
+  assert_contains "Bob"
+
+  assert_command dfx canister logs e2e_project --since 1h
+  assert_contains "Alice"
+  assert_contains "Bob"
+
+  assert_command dfx canister logs e2e_project --since 2000-01-01T00:00:00Z
+  assert_contains "Alice"
+
+  assert_command dfx canister logs e2e_project --since 2100-01-01T00:00:00Z
+  assert_not_contains "Alice"
+
+  assert_command_fail dfx canister logs e2e_project --since yesterday
+  assert_contains "Cannot parse --since"
+}
+
+@test "canister logs of all canisters are prefixed with the canister name" {
+  install_asset logs
+  dfx_start
+  dfx canister create --all
+  dfx build
+  dfx canister install e2e_project
+  dfx canister call e2e_project hello Alice
+  sleep 2
+
+  assert_command dfx canister logs --all
+  assert_contains "[e2e_project] ["
+  assert_contains "Hello, Alice!"
+}
+
+@test "canister logs of all canisters skip the canisters that cannot be read" {
+  install_asset logs
+  jq '.canisters.other=.canisters.e2e_project' dfx.json | sponge dfx.json
+  dfx identity new --storage-mode plaintext alice
+  dfx_start
+  dfx canister create --all
+  dfx build
+  dfx canister install e2e_project
+  dfx canister call e2e_project hello Alice
+  dfx canister update-settings other --set-controller alice --yes
+  sleep 2
+
+  assert_command dfx canister logs --all
+  assert_contains "Failed to get the logs of canister 'other'"
+  assert_contains "Hello, Alice!"
+
+  assert_command_fail dfx canister logs other
+}
+
+@test "canister logs print json" {
+  install_asset logs
+  dfx_start
+  dfx canister create --all
+  dfx build
+  dfx canister install e2e_project
+  dfx canister call e2e_project hello Alice
+  dfx canister call e2e_project hello Bob
+  sleep 2
+
+  assert_command dfx canister logs e2e_project --json
+  assert_eq "$(echo "$stdout" | jq -r '.content | rtrimstr("\n")')" "Hello, Alice!
+Hello, Bob!"
+  assert_eq "$(echo "$stdout" | jq -r .canister | uniq)" "e2e_project"
+}
+
+@test "canister logs follow new records" {
+  install_asset logs
+  dfx_start
+  dfx canister create --all
+  dfx build
+  dfx canister install e2e_project
+  dfx canister call e2e_project hello Alice
+  sleep 2
+
+  dfx canister logs e2e_project --follow --interval 1 > follow.log &
+  follow_pid=$!
+  sleep 3
+  dfx canister call e2e_project hello Bob
+  sleep 5
+  kill "$follow_pid"
+
+  assert_command cat follow.log
+  assert_contains "Hello, Bob!"
+  assert_eq "$(grep -c "Hello, Alice!" follow.log)" "1"
+}
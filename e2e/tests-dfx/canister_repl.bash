@@ -0,0 +1,71 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+
+  dfx_new
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "repl calls methods and stores results in variables" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  cat > session.txt <<'SESSION'
+:methods
+greet ("alice")
+let greeting = greet ("bob")
+greet ($greeting)
+:vars
+SESSION
+  assert_command dfx canister repl e2e_project_backend < session.txt
+  assert_contains 'greet : (text) -> (text) query'
+  assert_contains '("Hello, alice!")'
+  assert_contains '("Hello, Hello, bob!!")'
+  assert_contains '$greeting = "Hello, bob!"'
+}
+
+@test "repl reports errors, continues, and fails at the end of a piped script" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  cat > session.txt <<'SESSION'
+missing ()
+greet ($unknown)
+greet (1)
+greet ("alice")
+SESSION
+  assert_command_fail dfx canister repl e2e_project_backend < session.txt
+  assert_contains "Unknown method 'missing'."
+  assert_contains "Unknown variable '\$unknown'."
+  assert_contains '("Hello, alice!")'
+  assert_contains "3 command(s) of the script failed."
+}
+
+@test "repl switches identities" {
+  install_asset whoami
+  dfx_start
+  dfx deploy whoami
+  dfx identity new --storage-mode plaintext alice
+  ALICE_PRINCIPAL=$(dfx identity get-principal --identity alice)
+
+  cat > session.txt <<'SESSION'
+:identity
+:identity alice
+:identity
+whoami
+:quit
+whoami
+SESSION
+  assert_command dfx canister repl whoami < session.txt
+  assert_contains "default"
+  assert_contains "alice"
+  assert_contains "$ALICE_PRINCIPAL"
+}
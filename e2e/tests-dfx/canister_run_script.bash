@@ -0,0 +1,93 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+
+  dfx_new
+  cp "${BATS_TEST_DIRNAME}/../assets/counter/counter.mo" src/e2e_project_backend/main.mo
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "run-script runs the steps, checks the results and captures values" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  dfx identity new --storage-mode plaintext alice
+
+  cat > script.yaml <<SCRIPT
+steps:
+  - canister: e2e_project_backend
+    method: write
+    args: "(41 : nat)"
+  - canister: e2e_project_backend
+    method: inc
+  - canister: e2e_project_backend
+    method: read
+    expect: "(42 : nat)"
+    capture: count
+  - canister: e2e_project_backend
+    method: write
+    identity: alice
+    json_args: ["\${count}"]
+  - canister: e2e_project_backend
+    method: inc_read
+    expect_json: "43"
+SCRIPT
+  assert_command dfx canister run-script script.yaml
+  assert_contains "Step 5/5: e2e_project_backend.inc_read"
+  assert_contains "All 5 steps passed."
+
+  assert_command dfx canister call e2e_project_backend read
+  assert_eq "(43 : nat)"
+}
+
+@test "run-script reads JSON scripts" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  echo '{ "steps": [ { "canister": "e2e_project_backend", "method": "write", "json_args": ["7"] }, { "canister": "e2e_project_backend", "method": "read", "expect": "(7)" } ] }' > script.json
+  assert_command dfx canister run-script script.json
+  assert_contains "All 2 steps passed."
+}
+
+@test "run-script fails on an unexpected result" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  cat > script.yaml <<SCRIPT
+steps:
+  - canister: e2e_project_backend
+    method: inc
+  - canister: e2e_project_backend
+    method: read
+    expect: "(2 : nat)"
+  - canister: e2e_project_backend
+    method: inc
+SCRIPT
+  assert_command_fail dfx canister run-script script.yaml
+  assert_contains "Step 2 (e2e_project_backend.read) failed."
+  assert_contains "The result is (1 : nat), but (2 : nat) was expected."
+
+  assert_command dfx canister call e2e_project_backend read
+  assert_eq "(1 : nat)"
+}
+
+@test "run-script checks expected errors" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  cat > script.yaml <<SCRIPT
+steps:
+  - canister: e2e_project_backend
+    method: missing
+    expect_error: "has no update method 'missing'"
+SCRIPT
+  assert_command dfx canister run-script script.yaml
+  assert_contains "All 1 steps passed."
+}
@@ -0,0 +1,103 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+
+  dfx_new
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "settings diff reports settings that differ from dfx.json" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  assert_command dfx canister settings diff e2e_project_backend
+  assert_eq "e2e_project_backend: matches dfx.json"
+
+  jq '.canisters.e2e_project_backend.initialization_values.freezing_threshold=1234' dfx.json | sponge dfx.json
+  assert_command dfx canister settings diff --all
+  assert_contains "e2e_project_backend:"
+  assert_contains "freezing_threshold: 2592000 -> 1234"
+
+  assert_command dfx canister settings diff e2e_project_backend --json
+  assert_command jq -r '.[0].drift[0].setting, .[0].drift[0].desired' <<<"$stdout"
+  assert_eq "freezing_threshold
+1234"
+
+  # diff does not update the settings
+  assert_command dfx canister status e2e_project_backend
+  assert_contains "Freezing threshold: 2_592_000"
+}
+
+@test "deploy updates settings that differ from dfx.json with consent" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  jq '.canisters.e2e_project_backend.initialization_values.freezing_threshold=1234' dfx.json | sponge dfx.json
+  jq '.canisters.e2e_project_backend.initialization_values.log_visibility="public"' dfx.json | sponge dfx.json
+
+  assert_command dfx deploy e2e_project_backend <<<"no"
+  assert_contains "e2e_project_backend freezing_threshold: 2592000 -> 1234"
+  assert_contains "e2e_project_backend log_visibility: controllers -> public"
+  assert_contains "Kept the current settings."
+  assert_command dfx canister status e2e_project_backend
+  assert_contains "Freezing threshold: 2_592_000"
+
+  assert_command dfx deploy e2e_project_backend <<<"yes"
+  assert_contains "Updating the settings of canister e2e_project_backend..."
+  assert_command dfx canister status e2e_project_backend
+  assert_contains "Freezing threshold: 1_234"
+  assert_contains "Log visibility: public"
+
+  jq '.canisters.e2e_project_backend.initialization_values.freezing_threshold=4321' dfx.json | sponge dfx.json
+  assert_command dfx deploy e2e_project_backend --yes
+  assert_command dfx canister settings diff e2e_project_backend
+  assert_eq "e2e_project_backend: matches dfx.json"
+}
+
+@test "deploy sets the controllers listed in dfx.json" {
+  dfx identity new alice --storage-mode plaintext
+  ALICE_PRINCIPAL=$(dfx identity get-principal --identity alice)
+  DEFAULT_PRINCIPAL=$(dfx identity get-principal)
+  jq '.canisters.e2e_project_backend.controllers=["default","alice"]' dfx.json | sponge dfx.json
+  dfx_start
+
+  assert_command dfx deploy e2e_project_backend --yes
+  assert_contains "e2e_project_backend controllers: add $ALICE_PRINCIPAL"
+  assert_command dfx canister info e2e_project_backend
+  assert_contains "$ALICE_PRINCIPAL"
+  assert_contains "$DEFAULT_PRINCIPAL"
+
+  assert_command dfx canister settings diff e2e_project_backend
+  assert_eq "e2e_project_backend: matches dfx.json"
+
+  jq '.canisters.e2e_project_backend.controllers=["default"]' dfx.json | sponge dfx.json
+  assert_command dfx canister settings diff e2e_project_backend
+  assert_contains "controllers: remove $ALICE_PRINCIPAL"
+  assert_command dfx deploy e2e_project_backend --yes
+  assert_command dfx canister info e2e_project_backend
+  assert_not_contains "$ALICE_PRINCIPAL"
+}
+
+@test "deploy refuses controllers that lock out the deploying identity" {
+  dfx identity new alice --storage-mode plaintext
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  jq '.canisters.e2e_project_backend.controllers=["alice"]' dfx.json | sponge dfx.json
+  assert_command_fail dfx deploy e2e_project_backend --yes
+  assert_contains "Updating them would lock it out of the canister."
+  assert_command dfx canister info e2e_project_backend
+  assert_contains "$(dfx identity get-principal)"
+
+  jq '.canisters.e2e_project_backend.networks.local.controllers=["default","alice"]' dfx.json | sponge dfx.json
+  assert_command dfx deploy e2e_project_backend --yes
+  assert_contains "controllers: add $(dfx identity get-principal --identity alice)"
+}
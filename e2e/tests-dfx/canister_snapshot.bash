@@ -0,0 +1,71 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+
+  dfx_new
+  cp "${BATS_TEST_DIRNAME}/../assets/counter/counter.mo" src/e2e_project_backend/main.mo
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "canister snapshots can be created, listed, loaded and deleted" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  dfx canister call e2e_project_backend write '(5)'
+
+  assert_command_fail dfx canister snapshot create e2e_project_backend
+  dfx canister stop e2e_project_backend
+  assert_command dfx canister snapshot create e2e_project_backend
+  assert_contains "Created a new snapshot of canister e2e_project_backend."
+  snapshot=$(echo "$stderr" | sed -n 's/.*Snapshot ID: \([0-9a-f]*\).*/\1/p')
+  dfx canister start e2e_project_backend
+
+  assert_command dfx canister snapshot list e2e_project_backend
+  assert_contains "$snapshot: "
+
+  dfx canister call e2e_project_backend write '(7)'
+  dfx canister stop e2e_project_backend
+  assert_command dfx canister snapshot load e2e_project_backend "$snapshot"
+  assert_contains "Loaded snapshot $snapshot into canister e2e_project_backend."
+  dfx canister start e2e_project_backend
+  assert_command dfx canister call e2e_project_backend read
+  assert_eq "(5 : nat)"
+
+  assert_command dfx canister snapshot delete e2e_project_backend "$snapshot"
+  assert_command dfx canister snapshot list e2e_project_backend
+  assert_contains "No snapshots found for canister e2e_project_backend."
+}
+
+@test "canister snapshots can be replaced" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  dfx canister stop e2e_project_backend
+
+  dfx canister snapshot create e2e_project_backend
+  first=$(dfx canister snapshot list e2e_project_backend | cut -d: -f1)
+  assert_command dfx canister snapshot create e2e_project_backend --replace "$first"
+  assert_command dfx canister snapshot list e2e_project_backend
+  assert_not_contains "$first"
+
+  assert_command_fail dfx canister snapshot load e2e_project_backend "not-hex"
+  assert_contains "Snapshot id must"
+}
+
+@test "canister snapshots can be managed through the wallet" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  WALLET_ID=$(dfx identity get-wallet)
+  dfx canister update-settings e2e_project_backend --add-controller "$WALLET_ID"
+  dfx canister stop e2e_project_backend
+
+  assert_command dfx canister snapshot create e2e_project_backend --wallet "$WALLET_ID"
+  assert_command dfx canister snapshot list e2e_project_backend --wallet "$WALLET_ID"
+  assert_contains "bytes, taken at"
+}
@@ -60,6 +60,11 @@ current_time_nanoseconds() {
   assert_command dfx cycles balance --identity alice
   assert_eq "0.000 TC (trillion cycles)."
 
+  assert_command dfx cycles balance --identity alice --subaccount "$ALICE_SUBACCT1" --json
+  assert_eq "$(echo "$stdout" | jq -r .cycles)" "0"
+  assert_eq "$(echo "$stdout" | jq -r .owner)" "$ALICE"
+  assert_eq "$(echo "$stdout" | jq -r .subaccount)" "$ALICE_SUBACCT1"
+
   assert_command dfx cycles balance --identity bob --precise
   assert_eq "0 cycles."
 
@@ -0,0 +1,97 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+load ../utils/cycles-ledger
+
+setup() {
+  standard_setup
+
+  dfx_new
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "top-up-all reports that no canister has a cycles policy" {
+  assert_command dfx cycles top-up-all --dry-run
+  assert_contains "No canister in dfx.json has a cycles policy."
+}
+
+@test "top-up-all --dry-run prints the top-ups without sending cycles" {
+  jq '.canisters.e2e_project_backend.cycles={"minimum_balance":1000000000000000,"maximum_per_run":5000000000000}' dfx.json | sponge dfx.json
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  assert_command dfx cycles top-up-all --dry-run
+  assert_contains "e2e_project_backend: balance"
+  assert_contains "would be topped up with 5.000 TC"
+  assert_contains "Would top up 1 of 1 canister(s) with 5.000 TC in total."
+
+  assert_command dfx cycles top-up-all --dry-run --json
+  assert_command jq -r '.canisters[0].result, .canisters[0].top_up' <<<"$stdout"
+  assert_eq "would-top-up
+5000000000000"
+}
+
+@test "top-up-all skips canisters above the minimum balance and canisters not yet created" {
+  jq '.canisters.e2e_project_backend.cycles={"minimum_balance":1000}' dfx.json | sponge dfx.json
+  jq '.canisters.e2e_project_frontend.cycles={"minimum_balance":1000}' dfx.json | sponge dfx.json
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  assert_command dfx cycles top-up-all --dry-run
+  assert_contains "e2e_project_backend: balance"
+  assert_contains "above the minimum balance"
+  assert_contains "e2e_project_frontend: not created on this network"
+  assert_contains "Would top up 0 of 2 canister(s)"
+}
+
+@test "top-up-all reads the balances of wallet-controlled canisters through the wallet" {
+  dfx_start
+  WALLET=$(dfx identity get-wallet)
+  dfx canister create e2e_project_backend --controller "$WALLET"
+  jq '.canisters.e2e_project_backend.cycles={"minimum_balance":1000000000000000,"maximum_per_run":500000000000,"funding_source":"wallet"}' dfx.json | sponge dfx.json
+
+  assert_command dfx cycles top-up-all
+  assert_contains "e2e_project_backend: balance"
+  assert_contains "topped up with 0.500 TC"
+
+  jq '.canisters.e2e_project_backend.cycles.funding_source="cycles-ledger"' dfx.json | sponge dfx.json
+  assert_command_fail dfx cycles top-up-all --dry-run
+  assert_contains "e2e_project_backend: failed:"
+  assert_command dfx cycles top-up-all --dry-run --wallet "$WALLET"
+  assert_contains "would be topped up with 0.500 TC"
+}
+
+@test "top-up-all tops up canisters from the cycles ledger" {
+  install_shared_asset subnet_type/shared_network_settings/system
+  jq -s '.[0] * .[1]' "${BATS_TEST_DIRNAME}/../assets/cycles-ledger/dfx.json" dfx.json | sponge dfx.json
+  install_cycles_ledger_canisters
+  dfx identity new --storage-mode plaintext cycle-giver
+  dfx identity new --storage-mode plaintext bob
+  BOB=$(dfx identity get-principal --identity bob)
+
+  dfx_start_for_nns_install
+  dfx extension install nns --version 0.3.1 || true
+  dfx nns install --ledger-accounts "$(dfx ledger account-id --identity cycle-giver)"
+  assert_command dfx deploy cycles-ledger --specified-id "um5iw-rqaaa-aaaaq-qaaba-cai" --argument '(variant { Init = record { max_blocks_per_request = 100; index_id = null; } })'
+  assert_command dfx deploy depositor --argument "(record {ledger_id = principal \"$(dfx canister id cycles-ledger)\"})" --with-cycles 10000000000000 --specified-id "ul4oc-4iaaa-aaaaq-qaabq-cai"
+  assert_command dfx canister call depositor deposit "(record {to = record{owner = principal \"$BOB\";};cycles = 2_400_000_000_000;})" --identity cycle-giver
+
+  assert_command dfx deploy e2e_project_backend --identity bob
+  jq '.canisters.e2e_project_backend.cycles={"minimum_balance":1000000000000000,"maximum_per_run":500000000000}' dfx.json | sponge dfx.json
+  balance=$(dfx canister status e2e_project_backend --identity bob --json | jq -r .cycles)
+
+  assert_command dfx cycles top-up-all --identity bob
+  assert_contains "e2e_project_backend: balance"
+  assert_contains "topped up with 0.500 TC"
+  assert_contains "Topped up 1 of 1 canister(s) with 0.500 TC in total."
+
+  assert_command dfx cycles balance --precise --identity bob
+  assert_eq "1899900000000 cycles."
+  assert_command jq -r .cycles <<<"$(dfx canister status e2e_project_backend --identity bob --json)"
+  assert_eq "$((balance + 500000000000))"
+}
@@ -0,0 +1,88 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+
+  dfx_new_assets
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "deploy --plan does not create canisters" {
+  dfx_start
+
+  assert_command dfx deploy --plan
+  assert_contains "Deploy plan for network local:"
+  assert_contains "e2e_project_backend"
+  assert_contains "code: create the canister, then install"
+
+  assert_command dfx deploy --plan --json
+  assert_command jq -r '.canisters[] | select(.canister == "e2e_project_backend") | .code' <<<"$stdout"
+  assert_eq "create-and-install"
+
+  assert_command_fail dfx canister id e2e_project_backend
+}
+
+@test "deploy --plan reports upgrades, settings and assets" {
+  dfx_start
+  dfx deploy
+
+  assert_command dfx deploy --plan e2e_project_backend
+  assert_contains "code: unchanged, module 0x"
+  assert_not_contains "settings:"
+
+  sed -i.bak 's/Hello, /Hi, /' src/e2e_project_backend/main.mo
+  jq '.canisters.e2e_project_backend.initialization_values.freezing_threshold=1234' dfx.json | sponge dfx.json
+  assert_command dfx deploy --plan e2e_project_backend
+  assert_contains "code: upgrade module 0x"
+  assert_contains "candid interface: compatible"
+  assert_contains "stable types: compatible"
+  assert_contains "settings: freezing_threshold 2592000 -> 1234"
+
+  # the plan does not change anything
+  assert_command dfx canister call e2e_project_backend greet '("Names")'
+  assert_eq '("Hello, Names!")'
+  assert_command dfx canister status e2e_project_backend
+  assert_contains "Freezing threshold: 2_592_000"
+
+  echo "new file" >src/e2e_project_frontend/assets/new-asset.txt
+  assert_command dfx deploy --plan e2e_project_frontend --json
+  assert_command jq -r '.canisters[] | select(.canister == "e2e_project_frontend") | .assets.created[]' <<<"$stdout"
+  assert_eq "/new-asset.txt"
+  assert_command_fail dfx canister call e2e_project_frontend get '(record{key="/new-asset.txt";accept_encodings=vec{"identity"}})'
+}
+
+@test "deploy --plan does not build canisters whose dependencies do not exist" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  jq '.canisters.e2e_project_backend.dependencies=["new_dependency"] | .canisters.new_dependency={"type": "motoko", "main": "src/e2e_project_backend/main.mo"}' dfx.json | sponge dfx.json
+
+  assert_command dfx deploy --plan e2e_project_backend
+  assert_contains "new_dependency"
+  assert_contains "code: create the canister, then install"
+  assert_contains "code: build and install after creating new_dependency, installed module 0x"
+  assert_command_fail dfx canister id new_dependency
+
+  assert_command dfx deploy --plan e2e_project_backend --mode reinstall --json
+  assert_command jq -r '.canisters[] | select(.canister == "new_dependency") | .code' <<<"$stdout"
+  assert_eq "create"
+}
+
+@test "deploy --plan compares controllers without update calls" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  dfx identity new --storage-mode plaintext alice
+  ALICE_PRINCIPAL=$(dfx identity get-principal --identity alice)
+  jq '.canisters.e2e_project_backend.controllers=["default", "alice"]' dfx.json | sponge dfx.json
+
+  assert_command dfx deploy --plan e2e_project_backend
+  assert_contains "controllers: add $ALICE_PRINCIPAL"
+  assert_command dfx canister info e2e_project_backend
+  assert_not_contains "$ALICE_PRINCIPAL"
+}
@@ -100,6 +100,33 @@ teardown() {
   assert_file_not_exists "src/declarations/hello_backend/index.d.ts"
 }
 
+@test "dfx generate creates Rust bindings for each target" {
+  dfx_new hello
+  jq '.canisters.hello_backend.declarations.bindings=["rs"]' dfx.json | sponge dfx.json
+
+  dfx_start
+  dfx canister create --all
+  dfx build
+
+  dfx generate hello_backend
+  assert_file_exists "src/declarations/hello_backend/hello_backend.rs"
+  assert_file_not_exists "src/declarations/hello_backend/hello_backend.did.js"
+  assert_command cat "src/declarations/hello_backend/hello_backend.rs"
+  assert_contains "ic_agent"
+  assert_contains "pub async fn greet"
+
+  jq '.canisters.hello_backend.declarations.rust_target="canister"' dfx.json | sponge dfx.json
+  dfx generate hello_backend
+  assert_command cat "src/declarations/hello_backend/hello_backend.rs"
+  assert_contains "ic_cdk"
+  assert_not_contains "ic_agent"
+
+  jq '.canisters.hello_backend.declarations.rust_target="types"' dfx.json | sponge dfx.json
+  dfx generate hello_backend
+  assert_command cat "src/declarations/hello_backend/hello_backend.rs"
+  assert_not_contains "pub async fn greet"
+}
+
 @test "dfx generate does not create any files" {
   dfx_new hello
   jq '.canisters.hello_backend.declarations.bindings=[]' dfx.json | sponge dfx.json
@@ -101,3 +101,18 @@ test_project_import_specific_canister() {
     assert_command dfx beta project import "http://localhost:$E2E_WEB_SERVER_PORT/project-directory/dfx.json" --all
 }
 
+@test "project import follows included files" {
+    mkdir -p www/project/services/included
+    cp "${assets}/project-import/project-directory/normal-canister-directory/some-subdirectory/the-candid-filename.did" www/project/services/included/included.did
+    echo '{ "include": ["services/*/dfx.canister.json"] }' > www/project/dfx.json
+    echo '{ "canisters": { "included": { "type": "custom", "candid": "included.did", "wasm": "included.wasm" } } }' > www/project/services/included/dfx.canister.json
+
+    assert_command dfx beta project import www/project/dfx.json --all
+    assert_command jq -r '.canisters.included.candid' dfx.json
+    assert_eq "candid/included.did"
+    assert_files_eq www/project/services/included/included.did candid/included.did
+
+    start_webserver --directory "www"
+    assert_command_fail dfx beta project import "http://localhost:$E2E_WEB_SERVER_PORT/project/dfx.json" --all
+    assert_contains "glob patterns are only supported for local files"
+}
@@ -66,3 +66,21 @@ teardown() {
   assert_command dfx info candid-ui-url  
   assert_eq "http://127.0.0.1:$(dfx info webserver-port)/?canisterId=$(dfx canister id __Candid_UI)"
 }
+
+@test "displays canister configuration resolved for the network" {
+  jq '.canisters.e2e_project_backend.init_arg="(\"base\")"' dfx.json | sponge dfx.json
+  jq '.canisters.e2e_project_backend.networks.ic.init_arg="(\"mainnet\")"' dfx.json | sponge dfx.json
+  jq '.canisters.e2e_project_backend.networks.ic.initialization_values.compute_allocation=5' dfx.json | sponge dfx.json
+
+  assert_command dfx info canister-config e2e_project_backend
+  assert_eq '("base")' "$(echo "$stdout" | jq -r .init_arg)"
+  assert_eq "null" "$(echo "$stdout" | jq -r .initialization_values.compute_allocation)"
+  assert_eq "null" "$(echo "$stdout" | jq -r .networks)"
+
+  assert_command dfx info canister-config e2e_project_backend --ic
+  assert_eq '("mainnet")' "$(echo "$stdout" | jq -r .init_arg)"
+  assert_eq "5" "$(echo "$stdout" | jq -r .initialization_values.compute_allocation)"
+
+  assert_command_fail dfx info canister-config nonexistent
+  assert_contains "Cannot find canister 'nonexistent'."
+}
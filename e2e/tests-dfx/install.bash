@@ -90,6 +90,11 @@ teardown() {
   assert_command_fail dfx canister install --all --wasm "${archive:?}/wallet/0.10.0/wallet.wasm"
 }
 
+@test "install --tag conflicts with --argument" {
+  assert_command_fail dfx canister install --tag backend --argument '()'
+  assert_contains "cannot be used with"
+}
+
 @test "install runs post-install tasks" {
   install_asset post_install
   dfx_start
@@ -141,6 +146,22 @@ teardown() {
   assert_match "hello $id"
 }
 
+@test "post-install tasks of included canisters run in the directory of their file" {
+  mkdir -p services/custom
+  echo -n -e '\x00asm\x01\x00\x00\x00' > services/custom/main.wasm
+  echo 'service : {}' > services/custom/main.did
+  printf '#!/usr/bin/env bash\necho "post-install in $(basename "$(pwd)")"\n' > services/custom/post-install.sh
+  chmod +x services/custom/post-install.sh
+  echo '{ "canisters": { "custom": { "type": "custom", "candid": "main.did", "wasm": "main.wasm", "post_install": "./post-install.sh" } } }' > services/custom/dfx.canister.json
+  jq '.include=["services/*/dfx.canister.json"]' dfx.json | sponge dfx.json
+  dfx_start
+
+  assert_command dfx canister create custom
+  assert_command dfx build custom
+  assert_command dfx canister install custom
+  assert_match 'post-install in custom'
+}
+
 @test "can install gzip wasm" {
   jq '.canisters.e2e_project_backend.gzip=true' dfx.json | sponge dfx.json
   dfx_start
@@ -274,6 +295,41 @@ teardown() {
   assert_match "Hello, icp!"
 }
 
+@test "install interpolates environment variables in init_arg" {
+  install_asset deploy_deps
+  dfx_start
+  jq '.canisters.dependency.init_arg="(\"${GREETEE:-nobody}\")"' dfx.json | sponge dfx.json
+
+  dfx canister create dependency
+  dfx build dependency
+  assert_command dfx canister install dependency
+  assert_command dfx canister call dependency greet
+  assert_match "Hello, nobody!"
+
+  echo "GREETEE='dotenv'" >> .env
+  assert_command dfx canister install dependency --mode reinstall --yes
+  assert_command dfx canister call dependency greet
+  assert_match "Hello, dotenv!"
+
+  assert_command dfx canister install dependency --mode reinstall --yes --argument '("icp")'
+  assert_contains "has init_arg/init_arg_file in dfx.json: (\"dotenv\"),"
+
+  # values of variables that hold secrets are redacted
+  jq '.canisters.dependency.init_arg="(\"${GREETEE_TOKEN}\")"' dfx.json | sponge dfx.json
+  GREETEE_TOKEN=secret-value assert_command dfx canister install dependency --mode reinstall --yes --argument '("icp")'
+  assert_contains "has init_arg/init_arg_file in dfx.json: (\"***\"),"
+  assert_not_contains "secret-value"
+
+  jq '.canisters.dependency.init_arg="(\"${UNSET_GREETEE}\")"' dfx.json | sponge dfx.json
+  assert_command_fail dfx canister install dependency --mode reinstall --yes
+  assert_contains "Environment variable 'UNSET_GREETEE' referenced by canisters.dependency.init_arg in dfx.json is not set"
+
+  # the .env file is only read when dfx.json references a variable that is not set
+  jq 'del(.canisters.dependency.init_arg)' dfx.json | sponge dfx.json
+  echo "not a variable" >> .env
+  assert_command dfx canister install dependency --mode reinstall --yes --argument '("icp")'
+}
+
 @test "install succeeds if init_arg_file is defined in dfx.json" {
   install_asset deploy_deps
   dfx_start
@@ -0,0 +1,60 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+
+  dfx_new
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "identity commands print json" {
+  dfx identity new --storage-mode plaintext alice
+
+  assert_command dfx identity list --json
+  assert_eq "$(echo "$stdout" | jq -c '[.[] | select(.selected)] | map(.name)')" '["default"]'
+  assert_eq "$(echo "$stdout" | jq -r '.[] | select(.name == "alice") | .selected')" "false"
+
+  assert_command dfx identity whoami --json
+  assert_eq "$(echo "$stdout" | jq -r .name)" "default"
+
+  assert_command dfx identity get-principal --json
+  assert_eq "$(echo "$stdout" | jq -r .principal)" "$(dfx identity get-principal)"
+}
+
+@test "canister commands print json" {
+  dfx_start
+  dfx deploy e2e_project_backend
+  canister_id="$(dfx canister id e2e_project_backend)"
+
+  assert_command dfx canister id e2e_project_backend --json
+  assert_eq "$(echo "$stdout" | jq -r .canister_id)" "$canister_id"
+
+  assert_command dfx canister info e2e_project_backend --json
+  assert_eq "$(echo "$stdout" | jq -r '.controllers | length')" "1"
+  assert_match "0x[0-9a-f]{64}" "$(echo "$stdout" | jq -r .module_hash)"
+
+  assert_command dfx canister status e2e_project_backend --json
+  assert_eq "$(echo "$stdout" | jq -r .status)" "running"
+  assert_eq "$(echo "$stdout" | jq -r .canister_id)" "$canister_id"
+  assert_match "^[0-9]+$" "$(echo "$stdout" | jq -r .cycles)"
+  assert_eq "$(echo "$stdout" | jq -r .log_visibility)" "controllers"
+
+  dfx canister create --all
+  assert_command dfx canister status --all --json
+  assert_eq "$(echo "$stdout" | jq -r 'map(.canister) | sort | join(" ")')" "$(jq -r '.canisters | keys | join(" ")' dfx.json)"
+}
+
+@test "commands without json output reject --json" {
+  assert_command_fail dfx identity new --json bob
+  assert_contains "This command does not support --json."
+
+  assert_command_fail dfx deploy --json
+  assert_contains "This command does not support --json."
+}
@@ -62,3 +62,42 @@ teardown() {
   cargo update
   assert_command dfx deploy
 }
+
+@test "rust canister can be built with a custom profile and features" {
+  dfx_new_rust
+  cat >> Cargo.toml <<'TOML'
+
+[profile.release-canister]
+inherits = "release"
+opt-level = "z"
+TOML
+  cat >> src/e2e_project_backend/Cargo.toml <<'TOML'
+
+[features]
+loud = []
+TOML
+  jq '.canisters.e2e_project_backend.profile="release-canister" | .canisters.e2e_project_backend.features=["loud"]' dfx.json | sponge dfx.json
+
+  assert_command dfx build --check -vv
+  assert_contains "cargo build --target wasm32-unknown-unknown --profile release-canister -p e2e_project_backend --locked --features loud"
+  assert_command ls target/wasm32-unknown-unknown/release-canister/e2e_project_backend.wasm
+}
+
+@test "rust canister without candid field extracts its interface from the wasm" {
+  assert_command candid-extractor --version
+  dfx_new_rust
+  echo 'ic_cdk::export_candid!();' >> src/e2e_project_backend/src/lib.rs
+  jq 'del(.canisters.e2e_project_backend.candid)' dfx.json | sponge dfx.json
+
+  dfx_start
+  assert_command dfx deploy
+  assert_command dfx canister call e2e_project_backend greet dfinity
+  assert_match '("Hello, dfinity!")'
+
+  # the extracted interface is used for the candid:service metadata
+  assert_command dfx canister metadata e2e_project_backend candid:service
+  assert_match 'greet : \(text\) -> \(text\) query'
+
+  assert_command dfx generate e2e_project_backend
+  assert_file_exists src/declarations/e2e_project_backend/e2e_project_backend.did
+}
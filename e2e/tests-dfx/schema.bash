@@ -36,3 +36,17 @@ teardown() {
   assert_command jq type out.json
   assert_eq '"object"'
 }
+
+@test "dfx schema can display for assets-config" {
+  assert_command dfx schema --for assets-config --outfile out.json
+  # make sure out.json contains exactly one json object
+  assert_command jq type out.json
+  assert_eq '"object"'
+}
+
+@test "dfx schema can display for included-canisters" {
+  assert_command dfx schema --for included-canisters --outfile out.json
+  # make sure out.json contains exactly one json object
+  assert_command jq type out.json
+  assert_eq '"object"'
+}
@@ -0,0 +1,42 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+
+  dfx_new
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "verify succeeds for a canister deployed from the local build" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  assert_command dfx canister verify e2e_project_backend
+  assert_contains "Canister 'e2e_project_backend' matches the local build."
+}
+
+@test "verify reports the post-processing step that diverged" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  jq '.canisters.e2e_project_backend.gzip=true' dfx.json | sponge dfx.json
+  assert_command_fail dfx canister verify e2e_project_backend
+  assert_contains "The deployed module matches this step of the local build: the module with metadata, before it is gzipped."
+  assert_contains "The deployed module of canister 'e2e_project_backend' does not match the local build."
+}
+
+@test "verify reports a module built from different code" {
+  dfx_start
+  dfx deploy e2e_project_backend
+
+  sed -i.bak 's/Hello, /Hi, /' src/e2e_project_backend/main.mo
+  assert_command_fail dfx canister verify e2e_project_backend
+  assert_contains "The code of the deployed module differs from the local build."
+}
@@ -55,6 +55,11 @@ if [ "$E2E_TEST" = "tests-dfx/deps.bash" ]; then
      cargo binstall -y ic-wasm
 fi
 
+if [ "$E2E_TEST" = "tests-dfx/rust.bash" ]; then
+     cargo install cargo-binstall@1.6.9
+     cargo binstall -y candid-extractor
+fi
+
 if [ "$E2E_TEST" = "tests-icx-asset/icx-asset.bash" ]; then
     cargo build -p icx-asset
     ICX_ASSET="$(pwd)/target/debug/icx-asset"
@@ -1,9 +1,13 @@
 use crate::error::AssetLoadConfigError;
-use crate::error::AssetLoadConfigError::{LoadRuleFailed, MalformedAssetConfigFile};
+use crate::error::AssetLoadConfigError::{
+    InvalidAssetConfigField, LoadRuleFailed, MalformedAssetConfigFile,
+};
 use crate::error::GetAssetConfigError;
 use crate::error::GetAssetConfigError::{AssetConfigNotFound, InvalidPath};
 use derivative::Derivative;
 use globset::GlobMatcher;
+use schemars::schema::RootSchema;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::{
@@ -32,8 +36,11 @@ pub struct AssetConfig {
 
 pub(crate) type HeadersConfig = BTreeMap<String, String>;
 
-#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+/// # Cache Configuration
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq, JsonSchema)]
 pub(crate) struct CacheConfig {
+    /// # Max Age
+    /// Value of the `max-age` directive of the `Cache-Control` header, in seconds.
     pub(crate) max_age: Option<u64>,
 }
 
@@ -97,6 +104,7 @@ type ConfigMap = HashMap<PathBuf, ConfigNode>;
 #[derive(Debug)]
 pub struct AssetSourceDirectoryConfiguration {
     config_map: ConfigMap,
+    ignored_fields: Vec<String>,
 }
 
 /// A directory or subdirectory with assets.
@@ -110,13 +118,34 @@ struct AssetConfigTreeNode {
 impl AssetSourceDirectoryConfiguration {
     /// Constructs config tree for assets directory.
     pub fn load(root_dir: &Path) -> Result<Self, AssetLoadConfigError> {
+        Self::load_impl(root_dir, false)
+    }
+
+    /// Constructs config tree for assets directory, rejecting any field that is not part of the
+    /// `.ic-assets.json` schema, including fields nested in objects such as `cache`.
+    pub fn load_strict(root_dir: &Path) -> Result<Self, AssetLoadConfigError> {
+        Self::load_impl(root_dir, true)
+    }
+
+    fn load_impl(root_dir: &Path, strict: bool) -> Result<Self, AssetLoadConfigError> {
         if !root_dir.has_root() {
             return Err(AssetLoadConfigError::InvalidRootDir(root_dir.to_path_buf()));
         }
         let mut config_map = HashMap::new();
-        AssetConfigTreeNode::load(None, root_dir, &mut config_map)?;
+        let mut ignored_fields = vec![];
+        AssetConfigTreeNode::load(None, root_dir, &mut config_map, &mut ignored_fields, strict)?;
+
+        Ok(Self {
+            config_map,
+            ignored_fields,
+        })
+    }
 
-        Ok(Self { config_map })
+    /// Returns the fields nested in the rules of the `.ic-assets.json` files that are not part
+    /// of the schema, and that loading the files ignores, such as a misspelled field in `cache`.
+    /// Each one is described with its file and rule.
+    pub fn get_ignored_fields(&self) -> &[String] {
+        &self.ignored_fields
     }
 
     /// Fetches the configuration for the asset.
@@ -175,6 +204,8 @@ impl AssetConfigTreeNode {
         parent: Option<ConfigNode>,
         dir: &Path,
         configs: &mut ConfigMap,
+        ignored_fields: &mut Vec<String>,
+        strict: bool,
     ) -> Result<(), AssetLoadConfigError> {
         let config_path = match (
             dir.join(ASSETS_CONFIG_FILENAME_JSON).exists(),
@@ -193,8 +224,28 @@ impl AssetConfigTreeNode {
         if let Some(config_path) = config_path {
             let content = dfx_core::fs::read_to_string(&config_path)?;
 
+            if strict {
+                if let Some(json5::Error::Message {
+                    msg,
+                    location: Some(location),
+                }) = rule_utils::validate_fields(&content)
+                {
+                    return Err(InvalidAssetConfigField(
+                        config_path,
+                        location.line,
+                        location.column,
+                        msg,
+                    ));
+                }
+            }
+
             let interim_rules: Vec<rule_utils::InterimAssetConfigRule> = json5::from_str(&content)
                 .map_err(|e| MalformedAssetConfigFile(config_path.to_path_buf(), e))?;
+            ignored_fields.extend(
+                rule_utils::ignored_fields(&content)
+                    .into_iter()
+                    .map(|field| format!("{}: {}", config_path.display(), field)),
+            );
             for interim_rule in interim_rules {
                 let rule = AssetConfigRule::from_interim(interim_rule, dir)
                     .map_err(|e| LoadRuleFailed(config_path.to_path_buf(), e))?;
@@ -216,7 +267,13 @@ impl AssetConfigTreeNode {
             .filter_map(|x| x.ok())
             .filter(|x| x.file_type().map_or_else(|_e| false, |ft| ft.is_dir()))
         {
-            Self::load(Some(parent_ref.clone()), &f.path(), configs)?;
+            Self::load(
+                Some(parent_ref.clone()),
+                &f.path(),
+                configs,
+                ignored_fields,
+                strict,
+            )?;
         }
         Ok(())
     }
@@ -269,6 +326,13 @@ impl AssetConfig {
     }
 }
 
+/// Returns the JSON schema of the `.ic-assets.json` and `.ic-assets.json5` configuration files.
+pub fn asset_config_schema() -> RootSchema {
+    let mut schema = schemars::schema_for!(Vec<rule_utils::InterimAssetConfigRule>);
+    schema.schema.metadata().title = Some(ASSETS_CONFIG_FILENAME_JSON.to_string());
+    schema
+}
+
 /// This module contains various utilities needed for serialization/deserialization
 /// and pretty-printing of the `AssetConfigRule` data structure.
 mod rule_utils {
@@ -277,6 +341,8 @@ mod rule_utils {
     use crate::error::LoadRuleError;
     use globset::{Glob, GlobMatcher};
     use itertools::Itertools;
+    use schemars::JsonSchema;
+    use serde::de::IgnoredAny;
     use serde::{Deserialize, Serializer};
     use serde_json::Value;
     use std::collections::BTreeMap;
@@ -346,19 +412,109 @@ mod rule_utils {
         }
     }
 
-    #[derive(Deserialize)]
+    /// # Asset Configuration Rule
+    /// Configuration applied to all assets matching the `match` glob pattern.
+    /// When several rules match an asset, later rules take precedence over earlier ones.
+    #[derive(Deserialize, JsonSchema)]
     #[serde(deny_unknown_fields)]
+    #[schemars(rename = "AssetConfigRule")]
     pub(super) struct InterimAssetConfigRule {
+        /// # Match
+        /// Glob pattern, relative to the directory containing the configuration file,
+        /// that selects the assets this rule applies to.
         r#match: String,
+
+        /// # Cache
+        /// HTTP caching settings for the matched assets.
         cache: Option<CacheConfig>,
+
+        /// # Headers
+        /// HTTP response headers for the matched assets.
+        /// Set to `null` to remove all headers set by previous rules.
         #[serde(default, deserialize_with = "headers_deserialize")]
+        #[schemars(with = "Option<HeadersConfig>")]
         headers: Maybe<HeadersConfig>,
+
+        /// # Ignore
+        /// If true, the matched assets are not uploaded.
         ignore: Option<bool>,
+
+        /// # Enable Aliasing
+        /// Whether the matched assets can be requested without their `.html` extension
+        /// or `/index.html` suffix.
         enable_aliasing: Option<bool>,
+
+        /// # Allow Raw Access
+        /// Whether the matched assets can be served from the `raw.icp0.io` domain.
         allow_raw_access: Option<bool>,
+
+        /// # Encodings
+        /// Content encodings to upload for the matched assets.
         encodings: Option<Vec<ContentEncoder>>,
     }
 
+    /// Mirrors the keys of [`InterimAssetConfigRule`] without interpreting their values,
+    /// so that unknown keys are reported with their location, even inside nested objects.
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct StrictAssetConfigRule {
+        r#match: Option<IgnoredAny>,
+        cache: Option<StrictCacheConfig>,
+        headers: Option<IgnoredAny>,
+        ignore: Option<IgnoredAny>,
+        enable_aliasing: Option<IgnoredAny>,
+        allow_raw_access: Option<IgnoredAny>,
+        encodings: Option<IgnoredAny>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct StrictCacheConfig {
+        max_age: Option<IgnoredAny>,
+    }
+
+    /// Reads only the `cache` of a rule, keeping the fields that [`CacheConfig`] ignores.
+    #[derive(Deserialize)]
+    struct LenientAssetConfigRule {
+        cache: Option<LenientCacheConfig>,
+    }
+
+    #[derive(Deserialize)]
+    struct LenientCacheConfig {
+        #[serde(flatten)]
+        fields: BTreeMap<String, IgnoredAny>,
+    }
+
+    /// Describes every field nested in the rules of a config file that is not part of the schema,
+    /// but that the regular parser ignores instead of rejecting.
+    pub(super) fn ignored_fields(content: &str) -> Vec<String> {
+        let Ok(rules) = json5::from_str::<Vec<LenientAssetConfigRule>>(content) else {
+            return vec![];
+        };
+        let mut ignored = vec![];
+        for (index, rule) in rules.iter().enumerate() {
+            for field in rule.cache.iter().flat_map(|cache| cache.fields.keys()) {
+                if field != "max_age" {
+                    ignored.push(format!(
+                        "unknown field `{}` in the `cache` of rule {}, expected `max_age`",
+                        field,
+                        index + 1
+                    ));
+                }
+            }
+        }
+        ignored
+    }
+
+    /// Returns an error pointing at the first unknown or misplaced field in the config file, if any.
+    /// Syntax errors are left for the regular parser to report.
+    pub(super) fn validate_fields(content: &str) -> Option<json5::Error> {
+        json5::from_str::<Value>(content).ok()?;
+        json5::from_str::<Vec<StrictAssetConfigRule>>(content).err()
+    }
+
     impl AssetConfigRule {
         pub(super) fn from_interim(
             InterimAssetConfigRule {
@@ -912,6 +1068,119 @@ mod with_tempdir {
         );
     }
 
+    #[test]
+    fn strict_mode_reports_unknown_field_location() {
+        let cfg = Some(HashMap::from([(
+            "".to_string(),
+            r#"[
+  {
+    "match": "*",
+    "header": {"x-frame-options": "DENY"}
+  }
+]"#
+            .to_string(),
+        )]));
+        let assets_temp_dir = create_temporary_assets_directory(cfg, 0);
+        let assets_dir = assets_temp_dir.path().canonicalize().unwrap();
+        let assets_config = AssetSourceDirectoryConfiguration::load_strict(&assets_dir);
+        assert_eq!(
+            assets_config.err().unwrap().to_string(),
+            format!(
+                "Invalid field in asset config file {}:4:5: unknown field `header`, expected one of `match`, `cache`, `headers`, `ignore`, `enable_aliasing`, `allow_raw_access`, `encodings`",
+                assets_dir.join(ASSETS_CONFIG_FILENAME_JSON).display()
+            )
+        );
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_nested_field() {
+        let cfg = Some(HashMap::from([(
+            "nested".to_string(),
+            r#"[{"match": "*", "cache": {"max-age": 333}}]"#.to_string(),
+        )]));
+        let assets_temp_dir = create_temporary_assets_directory(cfg, 0);
+        let assets_dir = assets_temp_dir.path().canonicalize().unwrap();
+
+        assert!(AssetSourceDirectoryConfiguration::load(&assets_dir).is_ok());
+        let assets_config = AssetSourceDirectoryConfiguration::load_strict(&assets_dir);
+        assert_eq!(
+            assets_config.err().unwrap().to_string(),
+            format!(
+                "Invalid field in asset config file {}:1:27: unknown field `max-age`, expected `max_age`",
+                assets_dir
+                    .join("nested")
+                    .join(ASSETS_CONFIG_FILENAME_JSON)
+                    .display()
+            )
+        );
+    }
+
+    #[test]
+    fn loading_reports_every_ignored_nested_field() {
+        let cfg = Some(HashMap::from([
+            (
+                "".to_string(),
+                r#"[
+                    {"match": "*", "cache": {"max_age": 10, "max-age": 20, "maxAge": 30}},
+                    {"match": "*.html", "headers": {"x-header": "value"}}
+                ]"#
+                .to_string(),
+            ),
+            (
+                "nested".to_string(),
+                r#"[{"match": "*", "cache": {"age": 333}}]"#.to_string(),
+            ),
+        ]));
+        let assets_temp_dir = create_temporary_assets_directory(cfg, 0);
+        let assets_dir = assets_temp_dir.path().canonicalize().unwrap();
+        let assets_config = AssetSourceDirectoryConfiguration::load(&assets_dir).unwrap();
+        let mut ignored_fields = assets_config.get_ignored_fields().to_vec();
+        ignored_fields.sort();
+        assert_eq!(
+            ignored_fields,
+            vec![
+                format!(
+                    "{}: unknown field `max-age` in the `cache` of rule 1, expected `max_age`",
+                    assets_dir.join(ASSETS_CONFIG_FILENAME_JSON).display()
+                ),
+                format!(
+                    "{}: unknown field `maxAge` in the `cache` of rule 1, expected `max_age`",
+                    assets_dir.join(ASSETS_CONFIG_FILENAME_JSON).display()
+                ),
+                format!(
+                    "{}: unknown field `age` in the `cache` of rule 1, expected `max_age`",
+                    assets_dir
+                        .join("nested")
+                        .join(ASSETS_CONFIG_FILENAME_JSON)
+                        .display()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_mode_accepts_valid_config() {
+        let cfg = Some(HashMap::from([(
+            "".to_string(),
+            r#"[{"match": "*", "cache": {"max_age": 333}, "headers": null, "encodings": ["gzip"]}]"#
+                .to_string(),
+        )]));
+        let assets_temp_dir = create_temporary_assets_directory(cfg, 7);
+        let assets_dir = assets_temp_dir.path().canonicalize().unwrap();
+        let mut assets_config =
+            AssetSourceDirectoryConfiguration::load_strict(&assets_dir).unwrap();
+        assert_eq!(
+            assets_config
+                .get_asset_config(assets_dir.join("index.html").as_path())
+                .unwrap(),
+            AssetConfig {
+                cache: Some(CacheConfig { max_age: Some(333) }),
+                encodings: Some(vec![ContentEncoder::Gzip]),
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn invalid_asset_path() {
         let cfg = Some(HashMap::new());
@@ -1,6 +1,7 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Copy, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ContentEncoder {
     Gzip,
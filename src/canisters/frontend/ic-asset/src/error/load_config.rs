@@ -14,6 +14,10 @@ pub enum AssetLoadConfigError {
     #[error("root_dir '{0}' is expected to be a canonical path")]
     InvalidRootDir(PathBuf),
 
+    /// An asset configuration file contained a field that is not part of the schema.
+    #[error("Invalid field in asset config file {}:{}:{}: {}", .0.display(), .1, .2, .3)]
+    InvalidAssetConfigField(PathBuf, usize, usize, String),
+
     /// Failed to load a rule from the asset configuration file.
     #[error("Failed to load rule in {0}: {1}")]
     LoadRuleFailed(PathBuf, LoadRuleError),
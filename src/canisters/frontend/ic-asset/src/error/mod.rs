@@ -12,6 +12,7 @@ mod get_asset_properties;
 mod hash_content;
 mod load_config;
 mod load_rule;
+mod plan_sync;
 mod prepare_sync_for_proposal;
 mod sync;
 mod upload;
@@ -29,6 +30,7 @@ pub use get_asset_properties::GetAssetPropertiesError;
 pub use hash_content::HashContentError;
 pub use load_config::AssetLoadConfigError;
 pub use load_rule::LoadRuleError;
+pub use plan_sync::PlanSyncError;
 pub use prepare_sync_for_proposal::PrepareSyncForProposalError;
 pub use sync::SyncError;
 pub use upload::UploadError;
@@ -36,10 +36,13 @@ mod batch_upload;
 mod canister_api;
 pub mod error;
 mod evidence;
+mod plan;
 mod sync;
 mod upload;
 
+pub use asset::config::{asset_config_schema, AssetSourceDirectoryConfiguration};
 pub use evidence::compute_evidence;
+pub use plan::{plan_sync, SyncPlan};
 pub use sync::prepare_sync_for_proposal;
 pub use sync::sync;
 pub use upload::upload;
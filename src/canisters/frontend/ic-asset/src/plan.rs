@@ -0,0 +1,165 @@
+use crate::batch_upload::operations::assemble_batch_operations;
+use crate::batch_upload::operations::AssetDeletionReason;
+use crate::batch_upload::plumbing::make_project_assets;
+use crate::canister_api::methods::asset_properties::get_assets_properties;
+use crate::canister_api::methods::list::list_assets;
+use crate::canister_api::types::batch_upload::v1::BatchOperationKind;
+use crate::error::PlanSyncError;
+use crate::sync::gather_asset_descriptors;
+use ic_utils::Canister;
+use slog::{info, Logger};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// The changes that [`sync`](crate::sync) would make to the assets of an asset canister.
+/// Each field lists asset keys, in order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SyncPlan {
+    /// Assets that would be created.
+    pub created: Vec<String>,
+    /// Existing assets whose content would change, for at least one encoding.
+    pub updated: Vec<String>,
+    /// Assets that would be deleted.
+    pub deleted: Vec<String>,
+    /// Existing assets whose properties (such as headers or max age) would change.
+    pub properties_updated: Vec<String>,
+}
+
+impl SyncPlan {
+    /// Whether the assets in the canister already match the assets in the directories.
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty()
+            && self.updated.is_empty()
+            && self.deleted.is_empty()
+            && self.properties_updated.is_empty()
+    }
+}
+
+/// Computes the changes that [`sync`](crate::sync) would make to the assets of an asset canister,
+/// without uploading content or changing the canister.
+pub async fn plan_sync(
+    canister: &Canister<'_>,
+    dirs: &[&Path],
+    no_delete: bool,
+    logger: &Logger,
+) -> Result<SyncPlan, PlanSyncError> {
+    let asset_descriptors = gather_asset_descriptors(dirs, logger)?;
+
+    let canister_assets = list_assets(canister)
+        .await
+        .map_err(PlanSyncError::ListAssets)?;
+    info!(
+        logger,
+        "Fetching properties for all assets in the canister."
+    );
+    let canister_asset_properties = get_assets_properties(canister, &canister_assets).await?;
+
+    let project_assets =
+        make_project_assets(None, asset_descriptors, &canister_assets, logger).await?;
+
+    let operations = assemble_batch_operations(
+        &project_assets,
+        canister_assets,
+        match no_delete {
+            true => AssetDeletionReason::Incompatible,
+            false => AssetDeletionReason::Obsolete,
+        },
+        canister_asset_properties,
+    );
+    Ok(plan_from_operations(&operations))
+}
+
+fn plan_from_operations(operations: &[BatchOperationKind]) -> SyncPlan {
+    let mut created = BTreeSet::new();
+    let mut updated = BTreeSet::new();
+    let mut deleted = BTreeSet::new();
+    let mut properties_updated = BTreeSet::new();
+    for operation in operations {
+        match operation {
+            BatchOperationKind::CreateAsset(args) => {
+                created.insert(args.key.clone());
+            }
+            BatchOperationKind::SetAssetContent(args) => {
+                updated.insert(args.key.clone());
+            }
+            BatchOperationKind::UnsetAssetContent(args) => {
+                updated.insert(args.key.clone());
+            }
+            BatchOperationKind::DeleteAsset(args) => {
+                deleted.insert(args.key.clone());
+            }
+            BatchOperationKind::SetAssetProperties(args) => {
+                properties_updated.insert(args.key.clone());
+            }
+            BatchOperationKind::Clear(_) => {}
+        }
+    }
+    // A new asset also gets its content set, and an asset whose content type changes
+    // is deleted and created again.
+    SyncPlan {
+        updated: updated
+            .into_iter()
+            .filter(|key| !created.contains(key))
+            .collect(),
+        created: created.into_iter().collect(),
+        deleted: deleted.into_iter().collect(),
+        properties_updated: properties_updated.into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod test_plan_from_operations {
+    use super::*;
+    use crate::canister_api::types::batch_upload::common::{
+        CreateAssetArguments, DeleteAssetArguments, SetAssetContentArguments,
+        UnsetAssetContentArguments,
+    };
+
+    fn create(key: &str) -> BatchOperationKind {
+        BatchOperationKind::CreateAsset(CreateAssetArguments {
+            key: key.to_string(),
+            content_type: "text/plain".to_string(),
+            max_age: None,
+            headers: None,
+            enable_aliasing: None,
+            allow_raw_access: None,
+        })
+    }
+
+    fn set_content(key: &str, content_encoding: &str) -> BatchOperationKind {
+        BatchOperationKind::SetAssetContent(SetAssetContentArguments {
+            key: key.to_string(),
+            content_encoding: content_encoding.to_string(),
+            chunk_ids: vec![],
+            sha256: None,
+        })
+    }
+
+    #[test]
+    fn new_assets_are_only_created() {
+        let plan = plan_from_operations(&[
+            create("/new.txt"),
+            set_content("/new.txt", "identity"),
+            set_content("/new.txt", "gzip"),
+            set_content("/changed.txt", "identity"),
+            BatchOperationKind::UnsetAssetContent(UnsetAssetContentArguments {
+                key: "/changed.txt".to_string(),
+                content_encoding: "br".to_string(),
+            }),
+            BatchOperationKind::DeleteAsset(DeleteAssetArguments {
+                key: "/old.txt".to_string(),
+            }),
+        ]);
+        assert_eq!(
+            plan,
+            SyncPlan {
+                created: vec!["/new.txt".to_string()],
+                updated: vec!["/changed.txt".to_string()],
+                deleted: vec!["/old.txt".to_string()],
+                properties_updated: vec![],
+            }
+        );
+        assert!(!plan.is_empty());
+        assert!(plan_from_operations(&[]).is_empty());
+    }
+}
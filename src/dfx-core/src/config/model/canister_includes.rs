@@ -0,0 +1,310 @@
+//! Canister definitions included into dfx.json from other files.
+use crate::config::model::dfinity::CONFIG_FILE_NAME;
+use crate::config::model::env_interpolation::interpolate_env_vars;
+use crate::error::config::IncludeCanistersError;
+use globset::GlobBuilder;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use url::Url;
+use walkdir::WalkDir;
+
+const GLOB_CHARS: [char; 4] = ['*', '?', '[', '{'];
+
+/// The fields of an included file, as far as merging it into dfx.json is concerned.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IncludedFile {
+    canisters: Map<String, Value>,
+}
+
+/// Merges the canisters of all files matched by the `include` field of dfx.json into its `canisters` field.
+/// Relative paths in an included file are rewritten to be relative to the project root.
+/// Also returns the directory of the file that defines each included canister,
+/// in which its build and post-install commands run.
+pub fn apply_canister_includes(
+    mut json: Value,
+    project_root: &Path,
+) -> Result<(Value, BTreeMap<String, PathBuf>), IncludeCanistersError> {
+    let Some(patterns) = json.get("include") else {
+        return Ok((json, BTreeMap::new()));
+    };
+    let patterns = patterns
+        .as_array()
+        .and_then(|patterns| {
+            patterns
+                .iter()
+                .map(|p| p.as_str())
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or(IncludeCanistersError::IncludeFieldIsNotAnArrayOfStrings())?
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    let mut included = vec![];
+    for pattern in &patterns {
+        for path in expand_include_pattern(project_root, pattern)? {
+            if !included.contains(&path) {
+                included.push(path);
+            }
+        }
+    }
+
+    let mut defined_in: Vec<(String, PathBuf)> = json
+        .get("canisters")
+        .and_then(|c| c.as_object())
+        .map(|canisters| {
+            canisters
+                .keys()
+                .map(|name| (name.clone(), PathBuf::from(CONFIG_FILE_NAME)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut canisters = Map::new();
+    let mut canister_dirs = BTreeMap::new();
+    for path in included {
+        let content = crate::fs::read(&path).map_err(IncludeCanistersError::ReadFile)?;
+        let file_json: Value = serde_json::from_slice(&content)
+            .map_err(|e| IncludeCanistersError::DeserializeFailed(path.clone(), e))?;
+        let file_json = interpolate_env_vars(file_json, project_root)
+            .map_err(|e| IncludeCanistersError::InterpolateEnvVarsFailed(path.clone(), e))?;
+        let file: IncludedFile = serde_json::from_value(file_json)
+            .map_err(|e| IncludeCanistersError::DeserializeFailed(path.clone(), e))?;
+
+        let dir = path.parent().unwrap_or(project_root);
+        let base = dir.strip_prefix(project_root).unwrap_or(dir);
+        for (name, mut canister) in file.canisters {
+            if let Some((_, first)) = defined_in.iter().find(|(n, _)| *n == name) {
+                return Err(IncludeCanistersError::DuplicateCanister {
+                    name,
+                    first: first.clone(),
+                    second: path.clone(),
+                });
+            }
+            rebase_canister_paths(&mut canister, base);
+            defined_in.push((name.clone(), path.clone()));
+            canister_dirs.insert(name.clone(), dir.to_path_buf());
+            canisters.insert(name, canister);
+        }
+    }
+
+    if !canisters.is_empty() {
+        let object = json.as_object_mut().unwrap(); // "include" was found in it
+        let all = object
+            .entry("canisters")
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .ok_or(IncludeCanistersError::CanistersFieldIsNotAnObject())?;
+        all.extend(canisters);
+    }
+    Ok((json, canister_dirs))
+}
+
+/// Whether a path pattern contains glob characters, as opposed to naming a single file.
+pub fn is_glob_pattern(pattern: &str) -> bool {
+    pattern.contains(GLOB_CHARS)
+}
+
+/// Returns the files matched by an include pattern, relative to `root`, in sorted order.
+/// A pattern without glob characters must name an existing file.
+pub fn expand_include_pattern(
+    root: &Path,
+    pattern: &str,
+) -> Result<Vec<PathBuf>, IncludeCanistersError> {
+    let pattern_path = Path::new(pattern);
+    let literal: PathBuf = pattern_path
+        .components()
+        .take_while(|c| !is_glob_pattern(&c.as_os_str().to_string_lossy()))
+        .collect();
+    let remainder: PathBuf = pattern_path
+        .components()
+        .skip(literal.components().count())
+        .collect();
+
+    let literal = root.join(literal);
+    if remainder.as_os_str().is_empty() {
+        return if literal.is_file() {
+            Ok(vec![literal])
+        } else {
+            Err(IncludeCanistersError::IncludedFileNotFound(literal))
+        };
+    }
+    if !literal.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let remainder = remainder.to_string_lossy().replace('\\', "/");
+    let matcher = GlobBuilder::new(&remainder)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| IncludeCanistersError::InvalidPattern(pattern.to_string(), e))?
+        .compile_matcher();
+    let mut walk = WalkDir::new(&literal).min_depth(1).sort_by_file_name();
+    if !remainder.contains("**") {
+        walk = walk.max_depth(Path::new(&remainder).components().count());
+    }
+
+    let mut paths = vec![];
+    for entry in walk {
+        let entry = entry.map_err(IncludeCanistersError::WalkDirFailed)?;
+        let relative = entry.path().strip_prefix(&literal).unwrap();
+        if entry.file_type().is_file() && matcher.is_match(relative) {
+            paths.push(entry.into_path());
+        }
+    }
+    Ok(paths)
+}
+
+fn rebase_canister_paths(canister: &mut Value, base: &Path) {
+    for field in ["main", "init_arg_file"] {
+        rebase_path_field(canister, field, base);
+    }
+    for field in ["candid", "wasm"] {
+        rebase_path_or_url_field(canister, field, base);
+    }
+    if let Some(Value::Array(sources)) = canister.get_mut("source") {
+        for source in sources {
+            rebase_path(source, base);
+        }
+    }
+    if let Some(declarations) = canister.get_mut("declarations") {
+        rebase_path_field(declarations, "output", base);
+    }
+    if let Some(remote) = canister.get_mut("remote") {
+        rebase_path_field(remote, "candid", base);
+    }
+    if let Some(Value::Array(metadata)) = canister.get_mut("metadata") {
+        for section in metadata {
+            rebase_path_field(section, "path", base);
+        }
+    }
+    if let Some(Value::Object(networks)) = canister.get_mut("networks") {
+        for overrides in networks.values_mut() {
+            for field in ["main", "init_arg_file"] {
+                rebase_path_field(overrides, field, base);
+            }
+            for field in ["candid", "wasm"] {
+                rebase_path_or_url_field(overrides, field, base);
+            }
+        }
+    }
+}
+
+fn rebase_path_field(object: &mut Value, field: &str, base: &Path) {
+    if let Some(value) = object.get_mut(field) {
+        rebase_path(value, base);
+    }
+}
+
+fn rebase_path_or_url_field(object: &mut Value, field: &str, base: &Path) {
+    if let Some(value) = object.get_mut(field) {
+        if !matches!(value.as_str(), Some(s) if Url::parse(s).is_ok()) {
+            rebase_path(value, base);
+        }
+    }
+}
+
+fn rebase_path(value: &mut Value, base: &Path) {
+    if let Value::String(s) = value {
+        let path = Path::new(s.as_str());
+        if path.is_relative() && !path.as_os_str().is_empty() {
+            let rebased: PathBuf = base
+                .join(path)
+                .components()
+                .filter(|c| *c != Component::CurDir)
+                .collect();
+            *s = rebased.to_string_lossy().into_owned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn includes_canisters_with_rebased_paths_and_their_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "services/a/dfx.canister.json",
+            r#"{ "canisters": { "a": { "main": "src/main.mo", "declarations": { "output": "./decl" }, "post_install": "./post-install.sh" } } }"#,
+        );
+        write(
+            root,
+            "services/b/dfx.canister.json",
+            r#"{ "canisters": { "b": { "type": "custom", "wasm": "https://example.com/b.wasm", "candid": "b.did", "source": ["dist"], "build": ["make", "echo 'built b'"], "networks": { "ic": { "build": "make ic" } } } } }"#,
+        );
+        write(
+            root,
+            "services/c/other.json",
+            r#"{ "canisters": { "c": {} } }"#,
+        );
+
+        let json = json!({
+            "include": ["services/*/dfx.canister.json"],
+            "canisters": { "root": { "main": "main.mo" } }
+        });
+        let (json, canister_dirs) = apply_canister_includes(json, root).unwrap();
+        assert_eq!(
+            json["canisters"],
+            json!({
+                "root": { "main": "main.mo" },
+                "a": {
+                    "main": "services/a/src/main.mo",
+                    "declarations": { "output": "services/a/decl" },
+                    "post_install": "./post-install.sh"
+                },
+                "b": {
+                    "type": "custom",
+                    "wasm": "https://example.com/b.wasm",
+                    "candid": "services/b/b.did",
+                    "source": ["services/b/dist"],
+                    "build": ["make", "echo 'built b'"],
+                    "networks": { "ic": { "build": "make ic" } }
+                }
+            })
+        );
+        assert_eq!(
+            canister_dirs,
+            BTreeMap::from([
+                ("a".to_string(), root.join("services/a")),
+                ("b".to_string(), root.join("services/b")),
+            ])
+        );
+    }
+
+    #[test]
+    fn duplicate_canister_names_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "a.json", r#"{ "canisters": { "app": {} } }"#);
+
+        let json = json!({ "include": ["a.json"], "canisters": { "app": {} } });
+        let err = apply_canister_includes(json, root).unwrap_err();
+        assert!(matches!(
+            err,
+            IncludeCanistersError::DuplicateCanister { ref name, .. } if name == "app"
+        ));
+    }
+
+    #[test]
+    fn missing_literal_include_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = json!({ "include": ["missing.json"] });
+        assert!(matches!(
+            apply_canister_includes(json, dir.path()),
+            Err(IncludeCanistersError::IncludedFileNotFound(_))
+        ));
+    }
+}
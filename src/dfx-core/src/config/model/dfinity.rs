@@ -3,11 +3,15 @@
 use crate::config::directories::get_user_dfx_config_dir;
 use crate::config::model::bitcoin_adapter::BitcoinAdapterLogLevel;
 use crate::config::model::canister_http_adapter::HttpAdapterLogLevel;
+use crate::config::model::canister_includes::apply_canister_includes;
+use crate::config::model::env_interpolation::interpolate_env_vars;
 use crate::config::model::extension_canister_type::apply_extension_canister_types;
 use crate::error::config::{GetOutputEnvFileError, GetTempPathError};
 use crate::error::dfx_config::AddDependenciesError::CanisterCircularDependency;
 use crate::error::dfx_config::GetCanisterNamesWithDependenciesError::AddDependenciesFailed;
+use crate::error::dfx_config::GetCanisterNamesWithTagsError::NoCanisterWithTag;
 use crate::error::dfx_config::GetComputeAllocationError::GetComputeAllocationFailed;
+use crate::error::dfx_config::GetControllersError::GetControllersFailed;
 use crate::error::dfx_config::GetFreezingThresholdError::GetFreezingThresholdFailed;
 use crate::error::dfx_config::GetLogVisibilityError::GetLogVisibilityFailed;
 use crate::error::dfx_config::GetMemoryAllocationError::GetMemoryAllocationFailed;
@@ -18,9 +22,10 @@ use crate::error::dfx_config::GetSpecifiedIdError::GetSpecifiedIdFailed;
 use crate::error::dfx_config::GetWasmMemoryLimitError::GetWasmMemoryLimitFailed;
 use crate::error::dfx_config::{
     AddDependenciesError, GetCanisterConfigError, GetCanisterNamesWithDependenciesError,
-    GetComputeAllocationError, GetFreezingThresholdError, GetLogVisibilityError,
-    GetMemoryAllocationError, GetPullCanistersError, GetRemoteCanisterIdError,
-    GetReservedCyclesLimitError, GetSpecifiedIdError, GetWasmMemoryLimitError,
+    GetCanisterNamesWithTagsError, GetComputeAllocationError, GetControllersError,
+    GetFreezingThresholdError, GetLogVisibilityError, GetMemoryAllocationError,
+    GetPullCanistersError, GetRemoteCanisterIdError, GetReservedCyclesLimitError,
+    GetSpecifiedIdError, GetWasmMemoryLimitError,
 };
 use crate::error::load_dfx_config::LoadDfxConfigError;
 use crate::error::load_dfx_config::LoadDfxConfigError::{
@@ -118,6 +123,19 @@ impl std::fmt::Display for WasmOptLevel {
     }
 }
 
+/// # Motoko Warning Level
+/// How the Motoko compiler reports a warning.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MotokoWarningLevel {
+    /// Do not report the warning (`moc -A`).
+    Allow,
+    /// Report the warning (`moc -W`).
+    Warn,
+    /// Report the warning as an error, which fails the build (`moc -E`).
+    Error,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum MetadataVisibility {
@@ -254,11 +272,35 @@ pub struct ConfigCanistersCanister {
     #[serde(default)]
     pub initialization_values: InitializationValues,
 
+    /// # Cycles Top-Up Policy
+    /// Defines when `dfx cycles top-up-all` tops up this canister, by how much, and from which funds.
+    #[serde(default)]
+    pub cycles: Option<CyclesPolicy>,
+
+    /// # Controllers
+    /// Identity names or principals of the controllers of this canister.
+    /// `dfx deploy` adds and removes controllers to match this list.
+    /// The identity that deploys the canister must be in the list.
+    #[serde(default)]
+    pub controllers: Option<Vec<String>>,
+
     /// # Dependencies
     /// Defines on which canisters this canister depends on.
     #[serde(default)]
     pub dependencies: Vec<String>,
 
+    /// # Tags
+    /// Labels used to select groups of canisters, for example with `dfx deploy --tag <tag>`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// # Build Inputs
+    /// Files that the build of this canister reads, as paths or glob patterns relative to the project root.
+    /// dfx skips building the canister if neither these files nor its build settings changed since the last build.
+    /// Canisters of type `custom` and `assets` are only skipped if this is set.
+    #[serde(default)]
+    pub build_inputs: Vec<String>,
+
     /// # Force Frontend URL
     /// Mostly unused.
     /// If this value is not null, a frontend URL is displayed after deployment even if the canister type is not 'asset'.
@@ -311,6 +353,19 @@ pub struct ConfigCanistersCanister {
     /// Disabled by default.
     pub gzip: Option<bool>,
 
+    /// # Instrument Canister Wasm
+    /// Whether to instrument the Wasm with `ic-wasm` so that it records the instructions spent in each function.
+    /// The profile can then be fetched with `dfx canister profile`.
+    /// Disabled by default.
+    pub instrument: Option<bool>,
+
+    /// # Maximum Wasm Size
+    /// The size budget of the final Wasm module, after it is optimized, its metadata is added and it is gzipped.
+    /// The build fails if the module is larger.
+    /// Can be specified as an integer, or as an SI unit string (e.g. "2MiB")
+    #[schemars(with = "Option<ByteSchema>")]
+    pub max_wasm_size: Option<Byte>,
+
     /// # Specified Canister ID
     /// Attempts to create the canister with this Canister ID.
     /// This option only works with non-mainnet replica.
@@ -327,6 +382,104 @@ pub struct ConfigCanistersCanister {
     /// The Candid initialization argument file for installing the canister.
     /// If the `--argument` or `--argument-file` argument is also provided, this `init_arg_file` field will be ignored.
     pub init_arg_file: Option<String>,
+
+    /// # Network-Specific Overrides
+    /// Mapping between network names and fields that replace this canister's configuration on that network.
+    /// Fields that are not set keep the value from the base canister configuration.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub networks: BTreeMap<String, ConfigCanistersCanisterNetworkOverride>,
+}
+
+/// # Network-Specific Canister Configuration
+/// Fields that replace a canister's configuration on a specific network.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigCanistersCanisterNetworkOverride {
+    /// # Canister-Specific Build Argument
+    /// Replaces the additional argument passed to the Motoko compiler on this network.
+    pub args: Option<String>,
+
+    /// # Resource Allocation Settings
+    /// Replaces individual resource allocation settings on this network.
+    /// Settings that are not set here keep their base value.
+    pub initialization_values: Option<InitializationValues>,
+
+    /// # Cycles Top-Up Policy
+    /// Replaces the cycles top-up policy on this network.
+    pub cycles: Option<CyclesPolicy>,
+
+    /// # Controllers
+    /// Replaces the controllers of the canister on this network.
+    pub controllers: Option<Vec<String>>,
+
+    /// # Post-Install Commands
+    /// Replaces the commands run after canister installation on this network.
+    pub post_install: Option<SerdeVec<String>>,
+
+    /// # Path to Canister Entry Point
+    /// Replaces the entry point on this network.
+    pub main: Option<PathBuf>,
+
+    /// # Shrink Canister Wasm
+    /// Replaces the `shrink` setting on this network.
+    pub shrink: Option<bool>,
+
+    /// # Optimize Canister Wasm
+    /// Replaces the `optimize` setting on this network.
+    pub optimize: Option<WasmOptLevel>,
+
+    /// # Gzip Canister Wasm
+    /// Replaces the `gzip` setting on this network.
+    pub gzip: Option<bool>,
+
+    /// # Instrument Canister Wasm
+    /// Replaces the `instrument` setting on this network.
+    pub instrument: Option<bool>,
+
+    /// # Specified Canister ID
+    /// Replaces the canister ID to create the canister with on this network.
+    #[schemars(with = "Option<String>")]
+    pub specified_id: Option<Principal>,
+
+    /// # Init Arg
+    /// Replaces the Candid initialization argument on this network.
+    pub init_arg: Option<String>,
+
+    /// # Init Arg File
+    /// Replaces the Candid initialization argument file on this network.
+    pub init_arg_file: Option<String>,
+
+    /// # Build Commands
+    /// Replaces the build commands of 'assets' and 'custom' canisters on this network.
+    pub build: Option<SerdeVec<String>>,
+
+    /// # Wasm Path
+    /// Replaces the Wasm path of a 'custom' canister on this network.
+    pub wasm: Option<String>,
+
+    /// # Wasm Hash
+    /// Replaces the expected hash of the downloaded Wasm of a 'custom' canister on this network.
+    pub wasm_hash: Option<String>,
+
+    /// # Candid Hash
+    /// Replaces the expected hash of the downloaded candid file of a 'custom' canister on this network.
+    pub candid_hash: Option<String>,
+
+    /// # Cargo Features
+    /// Replaces the cargo features of a 'rust' canister on this network.
+    pub features: Option<Vec<String>>,
+
+    /// # No Default Features
+    /// Replaces the `no_default_features` setting of a 'rust' canister on this network.
+    pub no_default_features: Option<bool>,
+
+    /// # Cargo Profile
+    /// Replaces the cargo profile of a 'rust' canister on this network.
+    pub profile: Option<String>,
+
+    /// # Candid File
+    /// Replaces the candid file of 'rust' and 'custom' canisters on this network.
+    pub candid: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, JsonSchema)]
@@ -340,7 +493,33 @@ pub enum CanisterTypeProperties {
 
         /// # Candid File
         /// Path of this canister's candid interface declaration.
-        candid: PathBuf,
+        /// If omitted, the interface is extracted from the built Wasm with `candid-extractor`,
+        /// which requires the canister to call `ic_cdk::export_candid!()`.
+        candid: Option<PathBuf>,
+
+        /// # Cargo Features
+        /// Features of the package to activate, passed to `cargo build --features`.
+        #[schemars(default)]
+        features: Vec<String>,
+
+        /// # No Default Features
+        /// Do not activate the default features of the package.
+        #[schemars(default)]
+        no_default_features: bool,
+
+        /// # Cargo Profile
+        /// Cargo profile to build with, for example a custom `release-canister` profile. Defaults to `release`.
+        profile: Option<String>,
+
+        /// # Compilation Target
+        /// Target triple to build for. Defaults to `wasm32-unknown-unknown`.
+        /// Wasm built for a WASI target such as `wasm32-wasip1` is converted with `wasi2ic`.
+        target: Option<String>,
+
+        /// # Extra Cargo Arguments
+        /// Additional arguments passed to `cargo build`.
+        #[schemars(default)]
+        cargo_args: Vec<String>,
     },
     /// # Asset-Specific Properties
     Assets {
@@ -376,9 +555,45 @@ pub enum CanisterTypeProperties {
         /// No build commands are allowed if the `wasm` field is a URL.
         #[schemars(default)]
         build: SerdeVec<String>,
+
+        /// # Wasm Hash
+        /// The SHA-256 hash of the Wasm module, in hex, if the `wasm` field is a URL.
+        /// The build fails if the downloaded module has a different hash.
+        /// Downloads with a hash are cached, so that later builds do not download them again.
+        wasm_hash: Option<String>,
+
+        /// # Candid Hash
+        /// The SHA-256 hash of the candid file, in hex, if the `candid` field is a URL.
+        /// The build fails if the downloaded file has a different hash.
+        /// Downloads with a hash are cached, so that later builds do not download them again.
+        candid_hash: Option<String>,
     },
     /// # Motoko-Specific Properties
-    Motoko,
+    Motoko {
+        /// # Incremental Garbage Collection
+        /// Use the incremental garbage collector (`moc --incremental-gc`).
+        #[schemars(default)]
+        incremental_gc: bool,
+
+        /// # Stable Regions
+        /// Enable the stable regions API (`moc --stable-regions`).
+        #[schemars(default)]
+        stable_regions: bool,
+
+        /// # Maximum Stable Memory Pages
+        /// Maximum number of 64KiB pages of stable memory the canister can use (`moc --max-stable-pages`).
+        max_stable_pages: Option<u64>,
+
+        /// # Warnings As Errors
+        /// Report all compiler warnings as errors, which fails the build (`moc -Werror`).
+        #[schemars(default)]
+        warnings_as_errors: bool,
+
+        /// # Warning Levels
+        /// How to report individual compiler warnings, by warning code, for example `{ "M0194": "error" }`.
+        #[schemars(default)]
+        warning_levels: BTreeMap<String, MotokoWarningLevel>,
+    },
     /// # Pull-Specific Properties
     Pull {
         /// # Canister ID
@@ -400,6 +615,61 @@ impl CanisterTypeProperties {
     }
 }
 
+/// # Cycles Top-Up Policy
+/// `dfx cycles top-up-all` tops up the canister when its balance is below `minimum_balance`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CyclesPolicy {
+    /// # Minimum Balance
+    /// The canister is topped up when its cycles balance is below this number of cycles.
+    #[schemars(with = "u128")]
+    pub minimum_balance: u128,
+
+    /// # Target Balance
+    /// The number of cycles the canister is topped up to.
+    /// Defaults to the minimum balance.
+    #[schemars(with = "Option<u128>")]
+    pub target_balance: Option<u128>,
+
+    /// # Maximum Per Run
+    /// The largest number of cycles sent to the canister in one run of `dfx cycles top-up-all`.
+    /// Unlimited by default.
+    #[schemars(with = "Option<u128>")]
+    pub maximum_per_run: Option<u128>,
+
+    /// # Funding Source
+    /// Where the cycles come from: "cycles-ledger" (the default), "wallet" or "icp".
+    #[serde(default)]
+    pub funding_source: CyclesFundingSource,
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CyclesFundingSource {
+    /// The cycles ledger account of the selected identity.
+    #[default]
+    CyclesLedger,
+    /// The cycles wallet of the selected identity.
+    Wallet,
+    /// ICP of the selected identity, converted to cycles by the cycles minting canister.
+    Icp,
+}
+
+impl CyclesPolicy {
+    /// The number of cycles to send to a canister with this balance, if it is below the minimum.
+    pub fn top_up_amount(&self, balance: u128) -> Option<u128> {
+        if balance >= self.minimum_balance {
+            return None;
+        }
+        let target = self
+            .target_balance
+            .unwrap_or(self.minimum_balance)
+            .max(self.minimum_balance);
+        let amount = target - balance;
+        Some(self.maximum_per_run.map_or(amount, |max| amount.min(max)))
+    }
+}
+
 #[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum CanisterLogVisibility {
@@ -485,10 +755,16 @@ pub struct CanisterDeclarationsConfig {
 
     /// # Languages to generate
     /// A list of languages to generate type declarations.
-    /// Supported options are 'js', 'ts', 'did', 'mo'.
+    /// Supported options are 'js', 'ts', 'did', 'mo', 'rs'.
     /// Default is ['js', 'ts', 'did'].
     pub bindings: Option<Vec<String>>,
 
+    /// # Rust Bindings Target
+    /// The kind of Rust bindings that the 'rs' option generates.
+    /// Default is 'agent'.
+    #[serde(default)]
+    pub rust_target: RustBindingsTarget,
+
     /// # Canister ID ENV Override
     /// A string that will replace process.env.CANISTER_ID_{canister_name_uppercase}
     /// in the 'src/dfx/assets/language_bindings/canister.js' template.
@@ -501,6 +777,20 @@ pub struct CanisterDeclarationsConfig {
     pub node_compatibility: bool,
 }
 
+/// # Rust Bindings Target
+/// Who calls the canister through the generated Rust bindings.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RustBindingsTarget {
+    /// A client that calls the canister through `ic-agent`, for services and integration tests.
+    #[default]
+    Agent,
+    /// Another canister, which calls the canister through `ic-cdk`.
+    Canister,
+    /// Only the types of the canister interface, without a client.
+    Types,
+}
+
 /// # Bitcoin Adapter Configuration
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ConfigDefaultsBitcoin {
@@ -803,6 +1093,13 @@ pub struct ConfigInterface {
     /// Mapping between canisters and their settings.
     pub canisters: Option<BTreeMap<String, ConfigCanistersCanister>>,
 
+    /// # Included Files
+    /// Glob patterns, relative to dfx.json, of files that define additional canisters.
+    /// Each file has a `canisters` field like dfx.json.
+    /// Relative paths in an included file are resolved against that file's directory,
+    /// and its build and post-install commands run in that directory.
+    pub include: Option<Vec<String>>,
+
     /// Defaults for dfx start.
     pub defaults: Option<ConfigDefaults>,
 
@@ -814,6 +1111,15 @@ pub struct ConfigInterface {
     pub output_env_file: Option<PathBuf>,
 }
 
+/// # Included Canister Definitions
+/// A file with canister definitions, included by the `include` field of dfx.json.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct IncludedCanistersConfig {
+    /// Mapping between canisters and their settings.
+    pub canisters: BTreeMap<String, ConfigCanistersCanister>,
+}
+
 pub type TopLevelConfigNetworks = BTreeMap<String, ConfigNetwork>;
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
@@ -821,7 +1127,124 @@ pub struct NetworksConfigInterface {
     pub networks: TopLevelConfigNetworks,
 }
 
-impl ConfigCanistersCanister {}
+impl ConfigCanistersCanister {
+    /// Returns this canister's configuration with the overrides for the given network applied.
+    pub fn for_network(&self, network: &str) -> ConfigCanistersCanister {
+        let mut resolved = ConfigCanistersCanister {
+            networks: BTreeMap::new(),
+            ..self.clone()
+        };
+        let Some(overrides) = self.networks.get(network).cloned() else {
+            return resolved;
+        };
+
+        if overrides.args.is_some() {
+            resolved.args = overrides.args;
+        }
+        if let Some(values) = overrides.initialization_values {
+            resolved.initialization_values = values.or(resolved.initialization_values);
+        }
+        if overrides.cycles.is_some() {
+            resolved.cycles = overrides.cycles;
+        }
+        if overrides.controllers.is_some() {
+            resolved.controllers = overrides.controllers;
+        }
+        if let Some(post_install) = overrides.post_install {
+            resolved.post_install = post_install;
+        }
+        if overrides.main.is_some() {
+            resolved.main = overrides.main;
+        }
+        if overrides.shrink.is_some() {
+            resolved.shrink = overrides.shrink;
+        }
+        if overrides.optimize.is_some() {
+            resolved.optimize = overrides.optimize;
+        }
+        if overrides.gzip.is_some() {
+            resolved.gzip = overrides.gzip;
+        }
+        if overrides.instrument.is_some() {
+            resolved.instrument = overrides.instrument;
+        }
+        if overrides.specified_id.is_some() {
+            resolved.specified_id = overrides.specified_id;
+        }
+        if overrides.init_arg.is_some() {
+            resolved.init_arg = overrides.init_arg;
+        }
+        if overrides.init_arg_file.is_some() {
+            resolved.init_arg_file = overrides.init_arg_file;
+        }
+        match &mut resolved.type_specific {
+            CanisterTypeProperties::Rust {
+                candid,
+                features,
+                no_default_features,
+                profile,
+                ..
+            } => {
+                if let Some(c) = overrides.candid {
+                    *candid = Some(PathBuf::from(c));
+                }
+                if let Some(f) = overrides.features {
+                    *features = f;
+                }
+                if let Some(n) = overrides.no_default_features {
+                    *no_default_features = n;
+                }
+                if let Some(p) = overrides.profile {
+                    *profile = Some(p);
+                }
+            }
+            CanisterTypeProperties::Assets { build, .. } => {
+                if let Some(b) = overrides.build {
+                    *build = b;
+                }
+            }
+            CanisterTypeProperties::Custom {
+                wasm,
+                candid,
+                build,
+                wasm_hash,
+                candid_hash,
+            } => {
+                if let Some(w) = overrides.wasm {
+                    *wasm = w;
+                }
+                if let Some(c) = overrides.candid {
+                    *candid = c;
+                }
+                if overrides.wasm_hash.is_some() {
+                    *wasm_hash = overrides.wasm_hash;
+                }
+                if overrides.candid_hash.is_some() {
+                    *candid_hash = overrides.candid_hash;
+                }
+                if let Some(b) = overrides.build {
+                    *build = b;
+                }
+            }
+            CanisterTypeProperties::Motoko { .. } | CanisterTypeProperties::Pull { .. } => {}
+        }
+        resolved
+    }
+}
+
+impl InitializationValues {
+    /// Fills every setting that is not set with the value from `base`.
+    fn or(self, base: InitializationValues) -> InitializationValues {
+        InitializationValues {
+            compute_allocation: self.compute_allocation.or(base.compute_allocation),
+            memory_allocation: self.memory_allocation.or(base.memory_allocation),
+            freezing_threshold: self.freezing_threshold.or(base.freezing_threshold),
+            reserved_cycles_limit: self.reserved_cycles_limit.or(base.reserved_cycles_limit),
+            wasm_memory_limit: self.wasm_memory_limit.or(base.wasm_memory_limit),
+            log_visibility: self.log_visibility.or(base.log_visibility),
+        }
+    }
+}
 
 pub fn to_socket_addr(s: &str) -> Result<SocketAddr, SocketAddrConversionError> {
     match s.to_socket_addrs() {
@@ -880,6 +1303,19 @@ impl ConfigInterface {
     pub fn get_version(&self) -> u32 {
         self.version.unwrap_or(1)
     }
+
+    /// Returns this configuration with the network-specific overrides of every canister applied.
+    pub fn for_network(&self, network: &str) -> ConfigInterface {
+        ConfigInterface {
+            canisters: self.canisters.as_ref().map(|canisters| {
+                canisters
+                    .iter()
+                    .map(|(name, canister)| (name.clone(), canister.for_network(network)))
+                    .collect()
+            }),
+            ..self.clone()
+        }
+    }
     pub fn get_dfx(&self) -> Option<String> {
         self.dfx.to_owned()
     }
@@ -905,6 +1341,41 @@ impl ConfigInterface {
             })
     }
 
+    /// Return the names of the canisters that have at least one of the given tags,
+    /// or of all canisters if no tags are given.
+    /// Canisters that have one of the excluded tags are left out.
+    pub fn get_canister_names_with_tags(
+        &self,
+        tags: &[String],
+        excluded_tags: &[String],
+    ) -> Result<Vec<String>, GetCanisterNamesWithTagsError> {
+        let canisters = self
+            .canisters
+            .as_ref()
+            .ok_or(GetCanisterNamesWithTagsError::CanistersFieldDoesNotExist())?;
+        if let Some(unknown) = tags
+            .iter()
+            .chain(excluded_tags)
+            .find(|tag| !canisters.values().any(|c| c.tags.contains(tag)))
+        {
+            return Err(NoCanisterWithTag(unknown.clone()));
+        }
+
+        Ok(canisters
+            .iter()
+            .filter(|(_, c)| tags.is_empty() || c.tags.iter().any(|t| tags.contains(t)))
+            .filter(|(_, c)| !c.tags.iter().any(|t| excluded_tags.contains(t)))
+            .map(|(name, _)| name.clone())
+            .collect())
+    }
+
+    /// Return whether the canister has at least one of the given tags.
+    pub fn canister_has_any_tag(&self, canister_name: &str, tags: &[String]) -> bool {
+        self.get_canister_config(canister_name)
+            .map(|c| c.tags.iter().any(|t| tags.contains(t)))
+            .unwrap_or(false)
+    }
+
     pub fn get_remote_canister_id(
         &self,
         canister: &str,
@@ -1003,6 +1474,18 @@ impl ConfigInterface {
             .map(|visibility| visibility.into()))
     }
 
+    /// The identity names or principals of the controllers of a canister, if dfx.json lists them.
+    pub fn get_controllers(
+        &self,
+        canister_name: &str,
+    ) -> Result<Option<Vec<String>>, GetControllersError> {
+        Ok(self
+            .get_canister_config(canister_name)
+            .map_err(|e| GetControllersFailed(canister_name.to_string(), e))?
+            .controllers
+            .clone())
+    }
+
     fn get_canister_config(
         &self,
         canister_name: &str,
@@ -1078,6 +1561,7 @@ fn add_dependencies(
 pub struct Config {
     path: PathBuf,
     json: Value,
+    canister_dirs: BTreeMap<String, PathBuf>,
     // public interface to the config:
     pub config: ConfigInterface,
 }
@@ -1133,11 +1617,20 @@ impl Config {
     ) -> Result<Config, LoadDfxConfigError> {
         let json: Value = serde_json::from_slice(content)
             .map_err(|e| LoadDfxConfigError::DeserializeValueFailed(Box::new(path.clone()), e))?;
-        let effective_json = apply_extension_canister_types(json.clone(), extension_manager)?;
+        let project_root = path.parent().unwrap_or_else(|| Path::new("."));
+        let effective_json = interpolate_env_vars(json.clone(), project_root)?;
+        let (effective_json, canister_dirs) =
+            apply_canister_includes(effective_json, project_root)?;
+        let effective_json = apply_extension_canister_types(effective_json, extension_manager)?;
 
         let config = serde_json::from_value(effective_json)
             .map_err(|e| LoadDfxConfigError::DeserializeValueFailed(Box::new(path.clone()), e))?;
-        Ok(Config { path, json, config })
+        Ok(Config {
+            path,
+            json,
+            canister_dirs,
+            config,
+        })
     }
 
     /// Create a configuration from a string.
@@ -1172,6 +1665,17 @@ impl Config {
         &self.config
     }
 
+    /// Returns a copy of this configuration in which canister definitions are resolved for the given network.
+    /// The raw JSON is left untouched, so saving the result does not persist the overrides.
+    pub fn for_network(&self, network: &str) -> Config {
+        Config {
+            path: self.path.clone(),
+            json: self.json.clone(),
+            canister_dirs: self.canister_dirs.clone(),
+            config: self.config.for_network(network),
+        }
+    }
+
     pub fn get_project_root(&self) -> &Path {
         // a configuration path contains a file name specifically. As
         // such we should be returning at least root as parent. If
@@ -1181,6 +1685,15 @@ impl Config {
         )
     }
 
+    /// The directory in which the build and post-install commands of a canister run:
+    /// the directory of the included file that defines it, or else the project root.
+    pub fn get_canister_dir(&self, canister_name: &str) -> &Path {
+        self.canister_dirs
+            .get(canister_name)
+            .map(PathBuf::as_path)
+            .unwrap_or_else(|| self.get_project_root())
+    }
+
     // returns the path to the output env file if any, guaranteed to be
     // a child relative to the project root
     pub fn get_output_env_file(
@@ -1245,6 +1758,18 @@ impl<'de> Visitor<'de> for PropertiesVisitor {
         let mut r#type = None;
         let mut id = None;
         let mut workspace = None;
+        let mut features = None;
+        let mut no_default_features = None;
+        let mut profile = None;
+        let mut target = None;
+        let mut cargo_args = None;
+        let mut incremental_gc = None;
+        let mut stable_regions = None;
+        let mut max_stable_pages = None;
+        let mut warnings_as_errors = None;
+        let mut warning_levels = None;
+        let mut wasm_hash = None;
+        let mut candid_hash = None;
         while let Some(key) = map.next_key::<String>()? {
             match &*key {
                 "package" => package = Some(map.next_value()?),
@@ -1255,14 +1780,37 @@ impl<'de> Visitor<'de> for PropertiesVisitor {
                 "type" => r#type = Some(map.next_value::<String>()?),
                 "id" => id = Some(map.next_value()?),
                 "workspace" => workspace = Some(map.next_value()?),
+                "features" => features = Some(map.next_value()?),
+                "no_default_features" => no_default_features = Some(map.next_value()?),
+                "profile" => profile = Some(map.next_value()?),
+                "target" => target = Some(map.next_value()?),
+                "cargo_args" => cargo_args = Some(map.next_value()?),
+                "incremental_gc" => incremental_gc = Some(map.next_value()?),
+                "stable_regions" => stable_regions = Some(map.next_value()?),
+                "max_stable_pages" => max_stable_pages = Some(map.next_value()?),
+                "warnings_as_errors" => warnings_as_errors = Some(map.next_value()?),
+                "warning_levels" => warning_levels = Some(map.next_value()?),
+                "wasm_hash" => wasm_hash = Some(map.next_value()?),
+                "candid_hash" => candid_hash = Some(map.next_value()?),
                 _ => continue,
             }
         }
         let props = match r#type.as_deref() {
-            Some("motoko") | None => CanisterTypeProperties::Motoko,
+            Some("motoko") | None => CanisterTypeProperties::Motoko {
+                incremental_gc: incremental_gc.unwrap_or_default(),
+                stable_regions: stable_regions.unwrap_or_default(),
+                max_stable_pages,
+                warnings_as_errors: warnings_as_errors.unwrap_or_default(),
+                warning_levels: warning_levels.unwrap_or_default(),
+            },
             Some("rust") => CanisterTypeProperties::Rust {
-                candid: PathBuf::from(candid.ok_or_else(|| missing_field("candid"))?),
+                candid: candid.map(PathBuf::from),
                 package: package.ok_or_else(|| missing_field("package"))?,
+                features: features.unwrap_or_default(),
+                no_default_features: no_default_features.unwrap_or_default(),
+                profile,
+                target,
+                cargo_args: cargo_args.unwrap_or_default(),
             },
             Some("assets") => CanisterTypeProperties::Assets {
                 source: source.ok_or_else(|| missing_field("source"))?,
@@ -1273,6 +1821,8 @@ impl<'de> Visitor<'de> for PropertiesVisitor {
                 build: build.unwrap_or_default(),
                 candid: candid.ok_or_else(|| missing_field("candid"))?,
                 wasm: wasm.ok_or_else(|| missing_field("wasm"))?,
+                wasm_hash,
+                candid_hash,
             },
             Some("pull") => CanisterTypeProperties::Pull {
                 id: id.ok_or_else(|| missing_field("id"))?,
@@ -1531,4 +2081,246 @@ mod tests {
         assert_eq!(None, compute_allocation);
         assert_eq!(None, memory_allocation);
     }
+
+    #[test]
+    fn network_overrides_are_merged_over_base() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "app": {
+                  "type": "custom",
+                  "wasm": "app.wasm",
+                  "candid": "app.did",
+                  "build": "make",
+                  "init_arg": "(\"base\")",
+                  "initialization_values": {
+                    "compute_allocation": 10,
+                    "memory_allocation": "1GB"
+                  },
+                  "networks": {
+                    "ic": {
+                      "build": ["make release"],
+                      "init_arg": "(\"ic\")",
+                      "initialization_values": {
+                        "compute_allocation": 50
+                      }
+                    }
+                  }
+                }
+              }
+        }"#,
+        )
+        .unwrap();
+
+        let local = config.for_network("local");
+        let local = local.get_config();
+        assert_eq!(Some(10), local.get_compute_allocation("app").unwrap());
+        assert_eq!(
+            local.canisters.as_ref().unwrap()["app"].init_arg.as_deref(),
+            Some("(\"base\")")
+        );
+
+        let ic = config.for_network("ic");
+        let ic = ic.get_config();
+        assert_eq!(Some(50), ic.get_compute_allocation("app").unwrap());
+        assert_eq!(
+            Some("1GB".parse::<Byte>().unwrap()),
+            ic.get_memory_allocation("app").unwrap()
+        );
+        let app = &ic.canisters.as_ref().unwrap()["app"];
+        assert_eq!(app.init_arg.as_deref(), Some("(\"ic\")"));
+        assert!(app.networks.is_empty());
+        match &app.type_specific {
+            CanisterTypeProperties::Custom { build, wasm, .. } => {
+                assert_eq!(build.clone().into_vec(), vec!["make release".to_string()]);
+                assert_eq!(wasm, "app.wasm");
+            }
+            _ => panic!("not a custom canister"),
+        }
+    }
+
+    #[test]
+    fn rust_build_settings_can_be_overridden_per_network() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "app": {
+                  "type": "rust",
+                  "package": "app",
+                  "candid": "app.did",
+                  "features": ["local-dev"],
+                  "target": "wasm32-wasip1",
+                  "cargo_args": ["--config", "net.offline=true"],
+                  "networks": {
+                    "ic": {
+                      "features": ["production"],
+                      "no_default_features": true,
+                      "profile": "release-canister"
+                    }
+                  }
+                }
+              }
+        }"#,
+        )
+        .unwrap();
+
+        let ic = config.for_network("ic");
+        match &ic.get_config().canisters.as_ref().unwrap()["app"].type_specific {
+            CanisterTypeProperties::Rust {
+                features,
+                no_default_features,
+                profile,
+                target,
+                cargo_args,
+                ..
+            } => {
+                assert_eq!(features, &vec!["production".to_string()]);
+                assert!(*no_default_features);
+                assert_eq!(profile.as_deref(), Some("release-canister"));
+                assert_eq!(target.as_deref(), Some("wasm32-wasip1"));
+                assert_eq!(cargo_args.len(), 2);
+            }
+            _ => panic!("not a rust canister"),
+        }
+    }
+
+    #[test]
+    fn cycles_policy_can_be_overridden_per_network() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "app": {
+                  "type": "motoko",
+                  "main": "main.mo",
+                  "cycles": {
+                    "minimum_balance": 1000000000000,
+                    "target_balance": 3000000000000
+                  },
+                  "networks": {
+                    "ic": {
+                      "cycles": {
+                        "minimum_balance": 5000000000000,
+                        "maximum_per_run": 2000000000000,
+                        "funding_source": "icp"
+                      }
+                    }
+                  }
+                }
+              }
+        }"#,
+        )
+        .unwrap();
+
+        let local = config.for_network("local");
+        let policy = local.get_config().canisters.as_ref().unwrap()["app"]
+            .cycles
+            .clone()
+            .unwrap();
+        assert_eq!(policy.funding_source, CyclesFundingSource::CyclesLedger);
+        assert_eq!(policy.top_up_amount(1_000_000_000_000), None);
+        assert_eq!(
+            policy.top_up_amount(400_000_000_000),
+            Some(2_600_000_000_000)
+        );
+
+        let ic = config.for_network("ic");
+        let policy = ic.get_config().canisters.as_ref().unwrap()["app"]
+            .cycles
+            .clone()
+            .unwrap();
+        assert_eq!(policy.funding_source, CyclesFundingSource::Icp);
+        assert_eq!(
+            policy.top_up_amount(4_000_000_000_000),
+            Some(1_000_000_000_000)
+        );
+        assert_eq!(policy.top_up_amount(0), Some(2_000_000_000_000));
+    }
+
+    #[test]
+    fn controllers_can_be_overridden_per_network() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "app": {
+                  "type": "motoko",
+                  "main": "main.mo",
+                  "controllers": ["default"],
+                  "networks": {
+                    "ic": {
+                      "controllers": ["deployer", "aaaaa-aa"]
+                    }
+                  }
+                }
+              }
+        }"#,
+        )
+        .unwrap();
+
+        let local = config.for_network("local");
+        assert_eq!(
+            local.get_config().get_controllers("app").unwrap(),
+            Some(vec!["default".to_string()])
+        );
+        let ic = config.for_network("ic");
+        assert_eq!(
+            ic.get_config().get_controllers("app").unwrap(),
+            Some(vec!["deployer".to_string(), "aaaaa-aa".to_string()])
+        );
+    }
+
+    #[test]
+    fn network_overrides_reject_unknown_fields() {
+        let json = r#"{
+              "canisters": {
+                "app": {
+                  "networks": {
+                    "ic": {
+                      "remote": {}
+                    }
+                  }
+                }
+              }
+        }"#;
+        assert!(Config::from_slice(PathBuf::from("-"), json.as_bytes(), None).is_err());
+    }
+
+    #[test]
+    fn select_canisters_by_tag() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "backend": { "tags": ["payments"] },
+                "frontend": { "tags": ["frontend", "payments"] },
+                "admin": { "tags": ["frontend"] },
+                "other": {}
+              }
+        }"#,
+        )
+        .unwrap();
+        let config_interface = config.get_config();
+        let tags = |t: &[&str]| t.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            config_interface
+                .get_canister_names_with_tags(&tags(&["payments"]), &[])
+                .unwrap(),
+            tags(&["backend", "frontend"])
+        );
+        assert_eq!(
+            config_interface
+                .get_canister_names_with_tags(&[], &tags(&["frontend"]))
+                .unwrap(),
+            tags(&["backend", "other"])
+        );
+        assert_eq!(
+            config_interface
+                .get_canister_names_with_tags(&tags(&["payments"]), &tags(&["frontend"]))
+                .unwrap(),
+            tags(&["backend"])
+        );
+        assert!(matches!(
+            config_interface.get_canister_names_with_tags(&tags(&["typo"]), &[]),
+            Err(NoCanisterWithTag(tag)) if tag == "typo"
+        ));
+    }
 }
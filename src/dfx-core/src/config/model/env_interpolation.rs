@@ -0,0 +1,313 @@
+//! Interpolation of `${VAR}` and `${VAR:-default}` references in dfx.json string values.
+use crate::error::config::InterpolateEnvVarsError;
+use lazy_static::lazy_static;
+use serde_json::Value;
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::RwLock;
+
+pub const ENV_FILE_NAME: &str = ".env";
+
+/// Fields of a canister (or of its network overrides) that hold shell commands.
+/// Variables in them are expanded by the shell when the command runs,
+/// which also makes the environment variables that dfx sets for build steps available.
+const SHELL_COMMAND_FIELDS: [&str; 2] = ["build", "post_install"];
+
+/// Variables whose names end with one of these suffixes hold secrets, and their values are redacted from log output.
+const SECRET_NAME_SUFFIXES: [&str; 4] = ["_SECRET", "_TOKEN", "_PASSWORD", "_KEY"];
+
+/// Values shorter than this are not redacted, because replacing them would mangle unrelated output.
+const MIN_REDACTED_LEN: usize = 4;
+
+lazy_static! {
+    static ref REDACTED_VALUES: RwLock<BTreeSet<String>> = RwLock::new(BTreeSet::new());
+}
+
+/// Replaces `${VAR}` and `${VAR:-default}` references in all string values of `json`.
+/// Variables are looked up in the process environment first, then in the `.env` file in `project_root`.
+/// The `.env` file is only read if a referenced variable is not set in the process environment.
+/// `$${` produces a literal `${`.
+pub fn interpolate_env_vars(
+    json: Value,
+    project_root: &Path,
+) -> Result<Value, InterpolateEnvVarsError> {
+    let env_file = OnceCell::new();
+    let lookup = |name: &str| -> Result<Option<String>, InterpolateEnvVarsError> {
+        if let Ok(value) = std::env::var(name) {
+            return Ok(Some(value));
+        }
+        if env_file.get().is_none() {
+            let _ = env_file.set(read_env_file(&project_root.join(ENV_FILE_NAME))?);
+        }
+        Ok(env_file.get().and_then(|vars| vars.get(name).cloned()))
+    };
+    interpolate_value(json, &[], "", &lookup)
+}
+
+/// Replaces every value substituted from a variable that holds a secret with `***`.
+/// Secrets are variables whose names end with `_SECRET`, `_TOKEN`, `_PASSWORD` or `_KEY`.
+pub fn redact_secrets(s: &str) -> String {
+    let values = REDACTED_VALUES.read().unwrap();
+    // replace longer values first, so that a value containing another one is fully redacted
+    let mut values: Vec<&String> = values.iter().collect();
+    values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+    values
+        .into_iter()
+        .fold(s.to_string(), |s, value| s.replace(value.as_str(), "***"))
+}
+
+type Lookup<'a> = dyn Fn(&str) -> Result<Option<String>, InterpolateEnvVarsError> + 'a;
+
+/// `keys` are the object keys leading to `value` (`[]` for array elements),
+/// and `path` is their printable form for error messages.
+fn interpolate_value(
+    value: Value,
+    keys: &[&str],
+    path: &str,
+    lookup: &Lookup,
+) -> Result<Value, InterpolateEnvVarsError> {
+    Ok(match value {
+        Value::String(s) => Value::String(interpolate_str(&s, path, lookup)?),
+        Value::Array(values) => {
+            let keys = [keys, &["[]"]].concat();
+            Value::Array(
+                values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, v)| interpolate_value(v, &keys, &format!("{path}[{i}]"), lookup))
+                    .collect::<Result<_, _>>()?,
+            )
+        }
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| {
+                    if is_shell_command_field(keys, &k) {
+                        return Ok((k, v));
+                    }
+                    let field_path = if path.is_empty() {
+                        k.clone()
+                    } else {
+                        format!("{path}.{k}")
+                    };
+                    let keys = [keys, &[k.as_str()]].concat();
+                    interpolate_value(v, &keys, &field_path, lookup).map(|v| (k, v))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        other => other,
+    })
+}
+
+/// Whether `key` of the object at `parents` is the `build` or `post_install` field of a canister
+/// (`canisters.<name>`) or of one of its network overrides (`canisters.<name>.networks.<network>`).
+fn is_shell_command_field(parents: &[&str], key: &str) -> bool {
+    SHELL_COMMAND_FIELDS.contains(&key)
+        && matches!(parents, ["canisters", _] | ["canisters", _, "networks", _])
+}
+
+fn interpolate_str(
+    s: &str,
+    path: &str,
+    lookup: &Lookup,
+) -> Result<String, InterpolateEnvVarsError> {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = after;
+            continue;
+        }
+        let Some(after) = rest.strip_prefix("${") else {
+            result.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+        let end = after
+            .find('}')
+            .ok_or_else(|| InterpolateEnvVarsError::UnterminatedReference(path.to_string()))?;
+        let reference = &after[..end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        if !is_valid_variable_name(name) {
+            return Err(InterpolateEnvVarsError::InvalidVariableName {
+                name: name.to_string(),
+                path: path.to_string(),
+            });
+        }
+        match (lookup(name)?, default) {
+            (Some(value), None) => {
+                register_secret(name, &value);
+                result.push_str(&value);
+            }
+            (Some(value), Some(_)) if !value.is_empty() => {
+                register_secret(name, &value);
+                result.push_str(&value);
+            }
+            (_, Some(default)) => result.push_str(default),
+            (None, None) => {
+                return Err(InterpolateEnvVarsError::MissingVariable {
+                    name: name.to_string(),
+                    path: path.to_string(),
+                })
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn is_valid_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn register_secret(name: &str, value: &str) {
+    let is_secret = SECRET_NAME_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix));
+    if is_secret && value.len() >= MIN_REDACTED_LEN {
+        REDACTED_VALUES.write().unwrap().insert(value.to_string());
+    }
+}
+
+/// Reads `KEY=value` lines from a .env file, if it exists.
+/// Blank lines, `#` comments and an `export ` prefix are allowed; values may be quoted.
+fn read_env_file(path: &Path) -> Result<BTreeMap<String, String>, InterpolateEnvVarsError> {
+    let mut vars = BTreeMap::new();
+    if !path.exists() {
+        return Ok(vars);
+    }
+    let content = crate::fs::read_to_string(path).map_err(InterpolateEnvVarsError::ReadEnvFile)?;
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (name, value) = line
+            .split_once('=')
+            .filter(|(name, _)| is_valid_variable_name(name.trim()))
+            .ok_or_else(|| InterpolateEnvVarsError::InvalidEnvFileLine {
+                path: path.to_path_buf(),
+                line: index + 1,
+            })?;
+        let value = value.trim();
+        let value = [('\'', '\''), ('"', '"')]
+            .iter()
+            .find_map(|(open, close)| {
+                value
+                    .strip_prefix(*open)
+                    .and_then(|v| v.strip_suffix(*close))
+            })
+            .unwrap_or(value);
+        vars.insert(name.trim().to_string(), value.to_string());
+    }
+    Ok(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lookup(name: &str) -> Result<Option<String>, InterpolateEnvVarsError> {
+        Ok(match name {
+            "NAME" => Some("alice".to_string()),
+            "API_TOKEN" => Some("t0ps3cr3t".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn interpolates_variables_and_defaults() {
+        let json = json!({
+            "canisters": {
+                "app": {
+                    "init_arg": "(\"${NAME}\", \"${UNSET:-fallback}\", \"${EMPTY:-empty}\")",
+                    "build": "echo ${NOT_INTERPOLATED}",
+                    "args": "$$HOME $${NAME} $NAME",
+                    "networks": { "ic": { "post_install": "echo ${NOT_INTERPOLATED}" } }
+                }
+            },
+            "defaults": { "build": { "args": "${NAME}" } }
+        });
+        let result = interpolate_value(json, &[], "", &lookup).unwrap();
+        assert_eq!(
+            result,
+            json!({
+                "canisters": {
+                    "app": {
+                        "init_arg": "(\"alice\", \"fallback\", \"empty\")",
+                        "build": "echo ${NOT_INTERPOLATED}",
+                        "args": "$$HOME ${NAME} $NAME",
+                        "networks": { "ic": { "post_install": "echo ${NOT_INTERPOLATED}" } }
+                    }
+                },
+                "defaults": { "build": { "args": "alice" } }
+            })
+        );
+    }
+
+    #[test]
+    fn redacts_only_secret_variables() {
+        let json =
+            json!({ "canisters": { "app": { "init_arg": "(\"${NAME}\", \"${API_TOKEN}\")" } } });
+        interpolate_value(json, &[], "", &lookup).unwrap();
+        assert_eq!(redact_secrets("alice uses t0ps3cr3t"), "alice uses ***");
+    }
+
+    #[test]
+    fn reports_missing_variables_with_their_location() {
+        let json = json!({ "canisters": { "app": { "metadata": [{ "content": "${UNSET}" }] } } });
+        let err = interpolate_value(json, &[], "", &lookup).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Environment variable 'UNSET' referenced by canisters.app.metadata[0].content in dfx.json is not set"
+        );
+    }
+
+    #[test]
+    fn reads_env_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ENV_FILE_NAME);
+        std::fs::write(
+            &path,
+            "# comment\n\nA=1\nexport B='two'\nC=\"three\"\nCANISTER_ID_APP='bkyz2-fmaaa-aaaaa-qaaaq-cai'\n",
+        )
+        .unwrap();
+        let vars = read_env_file(&path).unwrap();
+        assert_eq!(vars["A"], "1");
+        assert_eq!(vars["B"], "two");
+        assert_eq!(vars["C"], "three");
+        assert_eq!(vars["CANISTER_ID_APP"], "bkyz2-fmaaa-aaaaa-qaaaq-cai");
+
+        std::fs::write(&path, "not a variable\n").unwrap();
+        assert!(read_env_file(&path).is_err());
+    }
+
+    #[test]
+    fn reads_env_file_only_when_needed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(ENV_FILE_NAME), "not a variable\n").unwrap();
+
+        let json = json!({ "canisters": { "app": { "init_arg": "(\"${PATH}\")" } } });
+        assert!(interpolate_env_vars(json, dir.path()).is_ok());
+
+        let json =
+            json!({ "canisters": { "app": { "init_arg": "(\"${DFX_SURELY_UNSET_VAR:-x}\")" } } });
+        assert!(matches!(
+            interpolate_env_vars(json, dir.path()),
+            Err(InterpolateEnvVarsError::InvalidEnvFileLine { line: 1, .. })
+        ));
+    }
+}
@@ -1,7 +1,9 @@
 pub mod bitcoin_adapter;
 pub mod canister_http_adapter;
 pub mod canister_id_store;
+pub mod canister_includes;
 pub mod dfinity;
+pub mod env_interpolation;
 pub mod extension_canister_type;
 pub mod local_server_descriptor;
 pub mod network_descriptor;
@@ -54,6 +54,58 @@ pub struct ApplyExtensionCanisterTypeErrorWithContext {
     pub source: ApplyExtensionCanisterTypeError,
 }
 
+#[derive(Error, Debug)]
+pub enum InterpolateEnvVarsError {
+    #[error("Environment variable '{name}' referenced by {path} in dfx.json is not set")]
+    MissingVariable { name: String, path: String },
+
+    #[error("Invalid environment variable name '{name}' referenced by {path} in dfx.json")]
+    InvalidVariableName { name: String, path: String },
+
+    #[error("Unterminated environment variable reference in {0} in dfx.json: missing '}}'")]
+    UnterminatedReference(String),
+
+    #[error("Failed to read environment file")]
+    ReadEnvFile(#[source] FsError),
+
+    #[error("Invalid line {line} in {}: expected NAME=value", path.display())]
+    InvalidEnvFileLine { path: PathBuf, line: usize },
+}
+
+#[derive(Error, Debug)]
+pub enum IncludeCanistersError {
+    #[error("the include field in dfx.json must be an array of strings")]
+    IncludeFieldIsNotAnArrayOfStrings(),
+
+    #[error("the canisters field in dfx.json must be an object")]
+    CanistersFieldIsNotAnObject(),
+
+    #[error("Invalid include pattern '{0}'")]
+    InvalidPattern(String, #[source] globset::Error),
+
+    #[error("Included file {} does not exist", .0.display())]
+    IncludedFileNotFound(PathBuf),
+
+    #[error("Failed to search for included files")]
+    WalkDirFailed(#[source] walkdir::Error),
+
+    #[error("Failed to read included file")]
+    ReadFile(#[source] FsError),
+
+    #[error("Failed to deserialize included file {}", .0.display())]
+    DeserializeFailed(PathBuf, #[source] serde_json::Error),
+
+    #[error("Failed to interpolate environment variables in included file {}", .0.display())]
+    InterpolateEnvVarsFailed(PathBuf, #[source] InterpolateEnvVarsError),
+
+    #[error("Canister '{name}' is defined in both {} and {}", first.display(), second.display())]
+    DuplicateCanister {
+        name: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+}
+
 #[derive(Error, Debug)]
 pub enum ApplyExtensionCanisterTypesError {
     #[error("the canisters field in dfx.json must be an object")]
@@ -28,6 +28,15 @@ pub enum GetCanisterNamesWithDependenciesError {
     AddDependenciesFailed(String, #[source] AddDependenciesError),
 }
 
+#[derive(Error, Debug)]
+pub enum GetCanisterNamesWithTagsError {
+    #[error("No canisters in the configuration file.")]
+    CanistersFieldDoesNotExist(),
+
+    #[error("No canister in the configuration file has the tag '{0}'.")]
+    NoCanisterWithTag(String),
+}
+
 #[derive(Error, Debug)]
 pub enum GetComputeAllocationError {
     #[error("Failed to get compute allocation for canister '{0}'")]
@@ -64,6 +73,12 @@ pub enum GetLogVisibilityError {
     GetLogVisibilityFailed(String, #[source] GetCanisterConfigError),
 }
 
+#[derive(Error, Debug)]
+pub enum GetControllersError {
+    #[error("Failed to get controllers for canister '{0}'")]
+    GetControllersFailed(String, #[source] GetCanisterConfigError),
+}
+
 #[derive(Error, Debug)]
 pub enum GetPullCanistersError {
     #[error("Pull dependencies '{0}' and '{1}' have the same canister ID: {2}")]
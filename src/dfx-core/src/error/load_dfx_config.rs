@@ -1,4 +1,6 @@
-use crate::error::config::ApplyExtensionCanisterTypesError;
+use crate::error::config::{
+    ApplyExtensionCanisterTypesError, IncludeCanistersError, InterpolateEnvVarsError,
+};
 use crate::error::fs::FsError;
 use std::path::PathBuf;
 use thiserror::Error;
@@ -8,6 +10,12 @@ pub enum LoadDfxConfigError {
     #[error(transparent)]
     ApplyExtensionCanisterTypesError(#[from] ApplyExtensionCanisterTypesError),
 
+    #[error(transparent)]
+    InterpolateEnvVars(#[from] InterpolateEnvVarsError),
+
+    #[error(transparent)]
+    IncludeCanisters(#[from] IncludeCanistersError),
+
     #[error("Failed to deserialize json from {0}")]
     DeserializeValueFailed(Box<PathBuf>, #[source] serde_json::Error),
 
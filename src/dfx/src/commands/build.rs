@@ -6,7 +6,9 @@ use crate::lib::error::DfxResult;
 use crate::lib::models::canister::CanisterPool;
 use crate::lib::network::network_opt::NetworkOpt;
 use crate::lib::operations::canister::add_canisters_with_ids;
+use crate::util::clap::canister_tag_opt::CanisterTagOpt;
 use clap::Parser;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use tokio::runtime::Runtime;
 
@@ -21,10 +23,31 @@ pub struct CanisterBuildOpts {
     #[arg(long, conflicts_with("canister_name"))]
     all: bool,
 
+    #[command(flatten)]
+    canister_tags: CanisterTagOpt,
+
     /// Build canisters without creating them. This can be used to check that canisters build ok.
     #[arg(long)]
     check: bool,
 
+    /// The maximum number of canisters to build at the same time.
+    /// Canisters are only built once the canisters they depend on are built.
+    #[arg(long, short = 'j', default_value = "1")]
+    jobs: NonZeroUsize,
+
+    /// Build all canisters, even those whose build inputs did not change since their last build.
+    #[arg(long)]
+    force_rebuild: bool,
+
+    /// Instrument the Wasm of the canisters, so that `dfx canister profile` can fetch the instructions spent in each function.
+    #[arg(long)]
+    instrument: bool,
+
+    /// Print a breakdown of the size of the Wasm of each canister: by section, custom section,
+    /// largest functions and data segments, and its size before and after each post-processing step.
+    #[arg(long)]
+    size_report: bool,
+
     /// Output environment variables to a file in dotenv format (without overwriting any user-defined variables, if the file already exists).
     #[arg(long)]
     output_env_file: Option<PathBuf>,
@@ -48,12 +71,17 @@ pub fn exec(env: &dyn Environment, opts: CanisterBuildOpts) -> DfxResult {
 
     let build_mode_check = opts.check;
 
+    opts.canister_tags
+        .ensure_no_canister(opts.canister_name.as_deref())?;
+
     // Option can be None in which case --all was specified
-    let required_canisters = config
-        .get_config()
-        .get_canister_names_with_dependencies(opts.canister_name.as_deref())?;
+    let required_canisters = opts
+        .canister_tags
+        .select_with_dependencies(config.get_config(), opts.canister_name.as_deref())?;
     let canisters_to_load = add_canisters_with_ids(&required_canisters, &env, &config);
 
+    let canister_pool = CanisterPool::load(&env, build_mode_check, &canisters_to_load)?;
+
     let canisters_to_build = required_canisters
         .into_iter()
         .filter(|canister_name| {
@@ -64,8 +92,6 @@ pub fn exec(env: &dyn Environment, opts: CanisterBuildOpts) -> DfxResult {
         })
         .collect();
 
-    let canister_pool = CanisterPool::load(&env, build_mode_check, &canisters_to_load)?;
-
     // Create canisters on the replica and associate canister ids locally.
     if build_mode_check {
         slog::warn!(
@@ -89,7 +115,11 @@ pub fn exec(env: &dyn Environment, opts: CanisterBuildOpts) -> DfxResult {
         BuildConfig::from_config(&config, env.get_network_descriptor().is_playground())?
             .with_build_mode_check(build_mode_check)
             .with_canisters_to_build(canisters_to_build)
-            .with_env_file(env_file);
+            .with_env_file(env_file)
+            .with_jobs(opts.jobs.get())
+            .with_force_rebuild(opts.force_rebuild)
+            .with_instrument(opts.instrument)
+            .with_size_report(opts.size_report);
     runtime.block_on(canister_pool.build_or_fail(logger, &build_config))?;
 
     Ok(())
@@ -0,0 +1,218 @@
+use crate::lib::canister_info::CanisterInfo;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::ic_attributes::CanisterSettings as DfxCanisterSettings;
+use crate::lib::operations::canister::{
+    create_and_register_canister, get_canister_id_and_candid_path, get_canister_status,
+    update_settings,
+};
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::util::clap::parsers::cycle_amount_parser;
+use crate::util::clap::subnet_selection_opt::SubnetSelectionOpt;
+use crate::util::{blob_from_arguments, get_candid_init_type};
+use anyhow::{bail, Context};
+use candid::Principal;
+use clap::Parser;
+use dfx_core::canister::install_canister_wasm;
+use dfx_core::config::cache::get_cache_root;
+use dfx_core::identity::CallSender;
+use ic_utils::interfaces::management_canister::builders::{CanisterSettings, InstallMode};
+use ic_utils::interfaces::management_canister::DefiniteCanisterSettings;
+use sha2::{Digest, Sha256};
+use slog::{info, warn};
+use std::path::PathBuf;
+
+/// Creates a new canister with the code and settings of an existing canister.
+/// The new canister is recorded under a new name in canister_ids.json.
+/// Its state is not copied: it starts with the initial state of its code.
+#[derive(Parser)]
+pub struct CanisterCloneOpts {
+    /// Specifies the name or id of the canister to clone.
+    source: String,
+
+    /// Specifies the name of the new canister.
+    #[arg(long = "as", value_name = "NEW_NAME")]
+    new_name: String,
+
+    /// Specifies the Wasm module to install in the new canister.
+    /// By default, the module installed in the source canister is looked up by its hash
+    /// in the build output of the project and in the download cache.
+    #[arg(long)]
+    wasm: Option<PathBuf>,
+
+    /// Specifies the init argument of the new canister, in Candid.
+    #[arg(long)]
+    argument: Option<String>,
+
+    /// Specifies the initial cycle balance to deposit into the new canister.
+    #[arg(long, value_parser = cycle_amount_parser)]
+    with_cycles: Option<u128>,
+
+    /// Performs the call with the user Identity as the Sender of messages.
+    /// Bypasses the Wallet canister.
+    #[arg(long)]
+    no_wallet: bool,
+
+    #[command(flatten)]
+    subnet_selection: SubnetSelectionOpt,
+}
+
+pub async fn exec(
+    env: &dyn Environment,
+    opts: CanisterCloneOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    if env.get_network_descriptor().is_playground() {
+        bail!("Canisters borrowed from a playground cannot be cloned.");
+    }
+    fetch_root_key_if_needed(env).await?;
+    let log = env.get_logger();
+
+    let canister_id_store = env.get_canister_id_store()?;
+    if let Some(id) = canister_id_store.find(&opts.new_name) {
+        bail!(
+            "A canister named {} already exists with canister id {}.",
+            opts.new_name,
+            id
+        );
+    }
+    let (source_id, candid_path) = get_canister_id_and_candid_path(env, &opts.source)?;
+
+    let status = get_canister_status(env, source_id, call_sender).await?;
+    let module_hash = status
+        .module_hash
+        .as_deref()
+        .with_context(|| format!("Canister {} has no code installed.", opts.source))?;
+    let wasm_module = find_wasm_module(env, &opts.source, source_id, opts.wasm, module_hash)?;
+    let init_type = candid_path.and_then(|path| get_candid_init_type(&path));
+    let install_args = blob_from_arguments(
+        Some(env),
+        opts.argument.as_deref(),
+        None,
+        None,
+        &init_type,
+        true,
+        false,
+    )?;
+
+    let mut subnet_selection = opts
+        .subnet_selection
+        .into_subnet_selection_type(env)
+        .await?;
+    let clone_id = create_and_register_canister(
+        env,
+        &opts.new_name,
+        opts.with_cycles,
+        None,
+        call_sender,
+        opts.no_wallet,
+        None,
+        settings_without_controllers(&status.settings)?,
+        None,
+        &mut subnet_selection,
+    )
+    .await
+    .with_context(|| format!("Failed to create canister {}.", opts.new_name))?;
+
+    info!(log, "Installing code for canister {}...", opts.new_name);
+    install_canister_wasm(
+        env.get_agent(),
+        clone_id,
+        Some(&opts.new_name),
+        &install_args,
+        InstallMode::Install,
+        call_sender,
+        wasm_module,
+        true,
+    )
+    .await?;
+
+    // The controllers are set last, so that the caller controls the new canister until it is set up.
+    update_settings(
+        env,
+        clone_id,
+        DfxCanisterSettings {
+            controllers: Some(status.settings.controllers.clone()),
+            ..Default::default()
+        },
+        call_sender,
+    )
+    .await?;
+
+    info!(
+        log,
+        "Cloned canister {} to {} with canister id {}.", opts.source, opts.new_name, clone_id
+    );
+    Ok(())
+}
+
+/// Finds a Wasm module with the hash of the module installed in the source canister.
+fn find_wasm_module(
+    env: &dyn Environment,
+    source: &str,
+    source_id: Principal,
+    wasm_path: Option<PathBuf>,
+    module_hash: &[u8],
+) -> DfxResult<Vec<u8>> {
+    if let Some(path) = wasm_path {
+        let wasm_module = dfx_core::fs::read(&path)?;
+        if Sha256::digest(&wasm_module)[..] != module_hash[..] {
+            warn!(
+                env.get_logger(),
+                "The hash of {} does not match the module installed in canister {}.",
+                path.display(),
+                source
+            );
+        }
+        return Ok(wasm_module);
+    }
+
+    let mut candidates = vec![get_cache_root()?
+        .join("downloads")
+        .join(hex::encode(module_hash))];
+    let canister_name = env
+        .get_canister_id_store()?
+        .get_name(&source_id.to_text())
+        .cloned()
+        .unwrap_or_else(|| source.to_string());
+    if let Some(config) = env.get_config()? {
+        let in_project = config
+            .get_config()
+            .canisters
+            .as_ref()
+            .is_some_and(|canisters| canisters.contains_key(&canister_name));
+        if in_project {
+            let info = CanisterInfo::load(&config, &canister_name, Some(source_id))?;
+            candidates.insert(0, info.get_build_wasm_path());
+        }
+    }
+    for path in candidates {
+        if path.exists() {
+            let wasm_module = dfx_core::fs::read(&path)?;
+            if Sha256::digest(&wasm_module)[..] == module_hash[..] {
+                return Ok(wasm_module);
+            }
+        }
+    }
+    bail!(
+        "Cannot find a Wasm module with hash 0x{}, which is installed in canister {}. Build the canister, or use --wasm to provide the module.",
+        hex::encode(module_hash),
+        source
+    );
+}
+
+/// The settings of the source canister, except its controllers.
+fn settings_without_controllers(
+    settings: &DefiniteCanisterSettings,
+) -> DfxResult<DfxCanisterSettings> {
+    CanisterSettings {
+        controllers: None,
+        compute_allocation: Some(settings.compute_allocation.clone()),
+        memory_allocation: Some(settings.memory_allocation.clone()),
+        freezing_threshold: Some(settings.freezing_threshold.clone()),
+        reserved_cycles_limit: settings.reserved_cycles_limit.clone(),
+        wasm_memory_limit: settings.wasm_memory_limit.clone(),
+        log_visibility: Some(settings.log_visibility.clone()),
+    }
+    .try_into()
+}
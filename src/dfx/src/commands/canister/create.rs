@@ -7,6 +7,7 @@ use crate::lib::ic_attributes::{
 };
 use crate::lib::operations::canister::create_canister;
 use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::util::clap::canister_tag_opt::CanisterTagOpt;
 use crate::util::clap::parsers::{
     compute_allocation_parser, freezing_threshold_parser, log_visibility_parser,
     memory_allocation_parser, reserved_cycles_limit_parser, wasm_memory_limit_parser,
@@ -31,9 +32,12 @@ pub struct CanisterCreateOpts {
     canister_name: Option<String>,
 
     /// Creates all canisters configured in dfx.json.
-    #[arg(long, required_unless_present("canister_name"))]
+    #[arg(long, required_unless_present_any(["canister_name", "tag", "exclude"]))]
     all: bool,
 
+    #[command(flatten)]
+    canister_tags: CanisterTagOpt,
+
     /// Specifies the initial cycle balance to deposit into the newly created canister.
     /// The specified amount needs to take the canister create fee into account.
     /// This amount is deducted from the wallet's cycle balance.
@@ -161,6 +165,8 @@ pub async fn exec(
         .into_subnet_selection_type(env)
         .await?;
 
+    opts.canister_tags
+        .ensure_no_canister(opts.canister_name.as_deref())?;
     let pull_canisters_in_config = get_pull_canisters_in_config(env)?;
     if let Some(canister_name) = opts.canister_name.as_deref() {
         if pull_canisters_in_config.contains_key(canister_name) {
@@ -229,10 +235,10 @@ pub async fn exec(
         )
         .await?;
         Ok(())
-    } else if opts.all {
+    } else if opts.all || opts.canister_tags.is_set() {
         // Create all canisters.
-        if let Some(canisters) = &config_interface.canisters {
-            for canister_name in canisters.keys() {
+        if config_interface.canisters.is_some() {
+            for canister_name in &opts.canister_tags.select(config_interface)? {
                 if pull_canisters_in_config.contains_key(canister_name) {
                     continue;
                 }
@@ -1,10 +1,13 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::network::network_opt::NetworkOpt;
+use crate::lib::output::print_output;
 use candid::Principal;
 use clap::Parser;
 use dfx_core::config::model::canister_id_store::CanisterIdStore;
 use dfx_core::network::provider::{create_network_descriptor, LocalBindDetermination};
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
 
 /// Prints the identifier of a canister.
 #[derive(Parser)]
@@ -16,6 +19,19 @@ pub struct CanisterIdOpts {
     network: NetworkOpt,
 }
 
+/// The result of `dfx canister id`.
+#[derive(Serialize)]
+struct CanisterIdOutput {
+    canister: String,
+    canister_id: Principal,
+}
+
+impl Display for CanisterIdOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.canister_id)
+    }
+}
+
 pub async fn exec(env: &dyn Environment, opts: CanisterIdOpts) -> DfxResult {
     env.get_config_or_anyhow()?;
     let network_descriptor = create_network_descriptor(
@@ -31,6 +47,11 @@ pub async fn exec(env: &dyn Environment, opts: CanisterIdOpts) -> DfxResult {
     let canister_name = opts.canister.as_str();
     let canister_id =
         Principal::from_text(canister_name).or_else(|_| canister_id_store.get(canister_name))?;
-    println!("{}", Principal::to_text(&canister_id));
-    Ok(())
+    print_output(
+        env,
+        &CanisterIdOutput {
+            canister: opts.canister,
+            canister_id,
+        },
+    )
 }
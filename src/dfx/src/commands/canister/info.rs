@@ -1,5 +1,6 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
+use crate::lib::output::print_output;
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::lib::state_tree::canister_info::{
     read_state_tree_canister_controllers, read_state_tree_canister_module_hash,
@@ -8,6 +9,8 @@ use anyhow::anyhow;
 use candid::Principal;
 use clap::Parser;
 use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
 
 /// Get the hash of a canister’s Wasm module and its current controllers.
 #[derive(Parser)]
@@ -16,6 +19,24 @@ pub struct InfoOpts {
     canister: String,
 }
 
+/// The result of `dfx canister info`.
+#[derive(Serialize)]
+struct CanisterInfoOutput {
+    controllers: Vec<Principal>,
+    module_hash: Option<String>,
+}
+
+impl Display for CanisterInfoOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Controllers: {}\nModule hash: {}",
+            self.controllers.iter().join(" "),
+            self.module_hash.as_deref().unwrap_or("None")
+        )
+    }
+}
+
 pub async fn exec(env: &dyn Environment, opts: InfoOpts) -> DfxResult {
     let agent = env.get_agent();
 
@@ -27,24 +48,23 @@ pub async fn exec(env: &dyn Environment, opts: InfoOpts) -> DfxResult {
 
     fetch_root_key_if_needed(env).await?;
 
-    let controllers_sorted: Vec<_> = read_state_tree_canister_controllers(agent, canister_id)
+    let controllers = read_state_tree_canister_controllers(agent, canister_id)
         .await?
         .ok_or_else(|| anyhow!("Canister {canister_id} does not exist."))?
-        .iter()
-        .map(Principal::to_text)
-        .sorted()
+        .into_iter()
+        .sorted_by_key(Principal::to_text)
         .collect();
+    let module_hash = read_state_tree_canister_module_hash(agent, canister_id)
+        .await?
+        .map(|blob| format!("0x{}", hex::encode(blob)));
 
-    let module_hash_hex = match read_state_tree_canister_module_hash(agent, canister_id).await? {
-        None => "None".to_string(),
-        Some(blob) => format!("0x{}", hex::encode(blob)),
-    };
-
-    println!(
-        "Controllers: {}\nModule hash: {}",
-        controllers_sorted.join(" "),
-        module_hash_hex
-    );
+    print_output(
+        env,
+        &CanisterInfoOutput {
+            controllers,
+            module_hash,
+        },
+    )?;
 
     Ok(())
 }
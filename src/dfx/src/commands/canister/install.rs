@@ -6,12 +6,13 @@ use crate::lib::operations::canister::install_canister::install_canister;
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::util::blob_from_arguments;
 use crate::util::clap::argument_from_cli::ArgumentFromCliLongOpt;
+use crate::util::clap::canister_tag_opt::CanisterTagOpt;
 use dfx_core::canister::{install_canister_wasm, install_mode_to_prompt};
 use dfx_core::identity::CallSender;
 
 use anyhow::{anyhow, bail, Context};
 use candid::Principal;
-use clap::Parser;
+use clap::{ArgGroup, Parser};
 use ic_utils::interfaces::management_canister::builders::InstallMode;
 use slog::info;
 use std::path::PathBuf;
@@ -19,6 +20,12 @@ use std::str::FromStr;
 
 /// Installs compiled code in a canister.
 #[derive(Parser, Clone)]
+#[command(group(
+    ArgGroup::new("canister_tag_selection")
+        .args(["tag", "exclude"])
+        .multiple(true)
+        .conflicts_with_all(["argument", "argument_file"])
+))]
 pub struct CanisterInstallOpts {
     /// Specifies the canister to deploy. You must specify either canister name/id or the --all option.
     canister: Option<String>,
@@ -26,12 +33,15 @@ pub struct CanisterInstallOpts {
     /// Deploys all canisters configured in the project dfx.json files.
     #[arg(
         long,
-        required_unless_present("canister"),
+        required_unless_present_any(["canister", "tag", "exclude"]),
         conflicts_with("argument"),
         conflicts_with("argument_file")
     )]
     all: bool,
 
+    #[command(flatten)]
+    canister_tags: CanisterTagOpt,
+
     /// Specifies not to wait for the result of the call to be returned by polling the replica. Instead return a response ID.
     #[arg(long)]
     async_call: bool,
@@ -90,6 +100,8 @@ pub async fn exec(
     let mut canister_id_store = env.get_canister_id_store()?;
     let network = env.get_network_descriptor();
 
+    opts.canister_tags
+        .ensure_no_canister(opts.canister.as_deref())?;
     if mode == Some(InstallMode::Reinstall) && (opts.canister.is_none() || opts.all) {
         bail!("The --mode=reinstall is only valid when specifying a single canister, because reinstallation destroys all data in the canister.");
     }
@@ -193,14 +205,14 @@ pub async fn exec(
                 .map_err(Into::into)
             }
         }
-    } else if opts.all {
+    } else if opts.all || opts.canister_tags.is_set() {
         // Install all canisters.
         let config = env.get_config_or_anyhow()?;
         let config_interface = config.get_config();
         let env_file = config.get_output_env_file(opts.output_env_file)?;
         let pull_canisters_in_config = get_pull_canisters_in_config(env)?;
-        if let Some(canisters) = &config.get_config().canisters {
-            for canister in canisters.keys() {
+        if config_interface.canisters.is_some() {
+            for canister in &opts.canister_tags.select(config_interface)? {
                 if pull_canisters_in_config.contains_key(canister) {
                     continue;
                 }
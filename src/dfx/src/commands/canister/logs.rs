@@ -1,11 +1,17 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::operations::canister;
+use crate::lib::output::print_output_line_with;
 use crate::lib::root_key::fetch_root_key_if_needed;
+use anyhow::anyhow;
 use candid::Principal;
 use clap::Parser;
 use dfx_core::identity::CallSender;
-use ic_utils::interfaces::management_canister::FetchCanisterLogsResponse;
+use ic_utils::interfaces::management_canister::CanisterLogRecord;
+use serde::Serialize;
+use slog::warn;
+use std::collections::HashMap;
+use std::time::Duration;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
@@ -13,79 +19,248 @@ use time::OffsetDateTime;
 #[derive(Parser)]
 pub struct LogsOpts {
     /// Specifies the name or id of the canister to get its canister information.
-    canister: String,
+    /// You must specify either a canister name/id or the --all option.
+    canister: Option<String>,
+
+    /// Gets the logs of all of the canisters configured in the dfx.json file, interleaved and prefixed with the canister name.
+    #[arg(long, required_unless_present("canister"), conflicts_with("canister"))]
+    all: bool,
+
+    /// Keeps polling for new log records and prints them as they arrive, until interrupted.
+    #[arg(long)]
+    follow: bool,
+
+    /// Specifies the number of seconds between polls with --follow.
+    #[arg(long, default_value = "2", requires("follow"))]
+    interval: u64,
+
+    /// Only prints the records newer than a duration (e.g. `30s`, `5m`, `1h 30m`),
+    /// or than a time in RFC 3339 format (e.g. `2024-05-06T19:17:10Z`).
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Only prints the last N records.
+    #[arg(long, value_name = "N")]
+    tail: Option<usize>,
+}
+
+/// A canister log record, as printed by `dfx --json canister logs`, one JSON object per line.
+#[derive(Serialize)]
+struct LogRecordOutput<'a> {
+    canister: &'a str,
+    canister_id: Principal,
+    idx: u64,
+    timestamp: String,
+    /// The content, if it is UTF-8 text.
+    content: Option<String>,
+    content_hex: String,
+}
+
+/// A log record and the canister it comes from.
+struct LogEntry<'a> {
+    canister: &'a str,
+    canister_id: Principal,
+    record: CanisterLogRecord,
 }
 
 fn format_bytes(bytes: &[u8]) -> String {
     format!("(bytes) 0x{}", hex::encode(bytes))
 }
 
-fn format_canister_logs(logs: FetchCanisterLogsResponse) -> Vec<String> {
-    logs.canister_log_records
-        .into_iter()
-        .map(|r| {
-            let time = OffsetDateTime::from_unix_timestamp_nanos(r.timestamp_nanos as i128)
-                .expect("Invalid canister log record timestamp");
-
-            let message = if let Ok(s) = String::from_utf8(r.content.clone()) {
-                if format!("{s:?}").contains("\\u{") {
-                    format_bytes(&r.content)
-                } else {
-                    s
-                }
-            } else {
-                format_bytes(&r.content)
-            };
+fn format_timestamp(timestamp_nanos: u64) -> String {
+    OffsetDateTime::from_unix_timestamp_nanos(timestamp_nanos as i128)
+        .expect("Invalid canister log record timestamp")
+        .format(&Rfc3339)
+        .expect("Failed to format timestamp")
+}
+
+/// Formats a log record, prefixed with the name of its canister if given.
+fn format_log_record(r: &CanisterLogRecord, canister: Option<&str>) -> String {
+    let message = if let Ok(s) = String::from_utf8(r.content.clone()) {
+        if format!("{s:?}").contains("\\u{") {
+            format_bytes(&r.content)
+        } else {
+            s
+        }
+    } else {
+        format_bytes(&r.content)
+    };
 
-            format!(
-                "[{}. {}]: {}",
-                r.idx,
-                time.format(&Rfc3339).expect("Failed to format timestamp"),
-                message
-            )
-        })
+    let prefix = canister.map_or_else(String::new, |canister| format!("[{canister}] "));
+    format!(
+        "{}[{}. {}]: {}",
+        prefix,
+        r.idx,
+        format_timestamp(r.timestamp_nanos),
+        message
+    )
+}
+
+/// Selects the records that were not printed yet: the ones after `last_idx`, and not before `since`.
+fn new_records(
+    records: Vec<CanisterLogRecord>,
+    last_idx: Option<u64>,
+    since: Option<u64>,
+) -> Vec<CanisterLogRecord> {
+    records
+        .into_iter()
+        .filter(|r| last_idx.map_or(true, |last_idx| r.idx > last_idx))
+        .filter(|r| since.map_or(true, |since| r.timestamp_nanos >= since))
         .collect()
 }
 
+/// Parses `--since` into a timestamp in nanoseconds.
+fn parse_since(since: &str) -> DfxResult<u64> {
+    let time = match OffsetDateTime::parse(since, &Rfc3339) {
+        Ok(time) => time,
+        Err(_) => {
+            let duration = humantime::parse_duration(since).map_err(|_| {
+                anyhow!(
+                    "Cannot parse --since as a duration (e.g. `5m`, `1h 30m`) or a time in RFC 3339 format."
+                )
+            })?;
+            OffsetDateTime::now_utc() - duration
+        }
+    };
+    Ok(u64::try_from(time.unix_timestamp_nanos())?)
+}
+
 #[test]
 fn test_format_canister_logs() {
-    use ic_utils::interfaces::management_canister::CanisterLogRecord;
-
-    let logs = FetchCanisterLogsResponse {
-        canister_log_records: vec![
-            CanisterLogRecord {
-                idx: 42,
-                timestamp_nanos: 1_620_328_630_000_000_001,
-                content: b"Some text message".to_vec(),
-            },
-            CanisterLogRecord {
-                idx: 43,
-                timestamp_nanos: 1_620_328_630_000_000_002,
-                content: vec![192, 255, 238],
-            },
-        ],
-    };
+    let logs = vec![
+        CanisterLogRecord {
+            idx: 42,
+            timestamp_nanos: 1_620_328_630_000_000_001,
+            content: b"Some text message".to_vec(),
+        },
+        CanisterLogRecord {
+            idx: 43,
+            timestamp_nanos: 1_620_328_630_000_000_002,
+            content: vec![192, 255, 238],
+        },
+    ];
     assert_eq!(
-        format_canister_logs(logs),
+        logs.iter()
+            .map(|r| format_log_record(r, None))
+            .collect::<Vec<_>>(),
         vec![
             "[42. 2021-05-06T19:17:10.000000001Z]: Some text message".to_string(),
             "[43. 2021-05-06T19:17:10.000000002Z]: (bytes) 0xc0ffee".to_string(),
         ],
     );
+    assert_eq!(
+        format_log_record(&logs[0], Some("backend")),
+        "[backend] [42. 2021-05-06T19:17:10.000000001Z]: Some text message",
+    );
+}
+
+#[test]
+fn test_new_records() {
+    let record = |idx, timestamp_nanos| CanisterLogRecord {
+        idx,
+        timestamp_nanos,
+        content: vec![],
+    };
+    let records = || vec![record(1, 100), record(2, 200), record(3, 300)];
+    let idxs = |records: Vec<CanisterLogRecord>| records.iter().map(|r| r.idx).collect::<Vec<_>>();
+
+    assert_eq!(idxs(new_records(records(), None, None)), [1, 2, 3]);
+    assert_eq!(idxs(new_records(records(), Some(1), None)), [2, 3]);
+    assert_eq!(idxs(new_records(records(), None, Some(200))), [2, 3]);
+    assert_eq!(idxs(new_records(records(), Some(2), Some(100))), [3]);
 }
 
 pub async fn exec(env: &dyn Environment, opts: LogsOpts, call_sender: &CallSender) -> DfxResult {
-    let callee_canister = opts.canister.as_str();
     let canister_id_store = env.get_canister_id_store()?;
-
-    let canister_id = Principal::from_text(callee_canister)
-        .or_else(|_| canister_id_store.get(callee_canister))?;
+    let canisters = if let Some(callee_canister) = opts.canister.as_deref() {
+        let canister_id = Principal::from_text(callee_canister)
+            .or_else(|_| canister_id_store.get(callee_canister))?;
+        vec![(callee_canister.to_string(), canister_id)]
+    } else {
+        let config = env.get_config_or_anyhow()?;
+        let mut names = config
+            .get_config()
+            .get_canister_names_with_dependencies(None)?;
+        names.sort();
+        // canisters that were not created yet have no logs
+        names
+            .into_iter()
+            .filter_map(|name| canister_id_store.find(&name).map(|id| (name, id)))
+            .collect()
+    };
+    let since = opts.since.as_deref().map(parse_since).transpose()?;
 
     fetch_root_key_if_needed(env).await?;
 
-    let logs = canister::get_canister_logs(env, canister_id, call_sender).await?;
+    let mut last_idx: HashMap<Principal, u64> = HashMap::new();
+    let mut first_poll = true;
+    loop {
+        let mut entries = vec![];
+        let mut errors = vec![];
+        for (canister, canister_id) in &canisters {
+            let logs = match canister::get_canister_logs(env, *canister_id, call_sender).await {
+                Ok(logs) => logs,
+                // with --follow, the next poll tries again, and with --all, the other canisters are still printed
+                Err(err) if opts.follow || opts.all => {
+                    warn!(
+                        env.get_logger(),
+                        "Failed to get the logs of canister '{}': {:#}", canister, err
+                    );
+                    errors.push(err);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let records = logs.canister_log_records;
+            if let Some(idx) = records.iter().map(|r| r.idx).max() {
+                let previous = last_idx.insert(*canister_id, idx);
+                entries.extend(
+                    new_records(records, previous, since)
+                        .into_iter()
+                        .map(|record| LogEntry {
+                            canister,
+                            canister_id: *canister_id,
+                            record,
+                        }),
+                );
+            }
+        }
+        entries.sort_by_key(|e| e.record.timestamp_nanos);
+        if first_poll {
+            if let Some(tail) = opts.tail {
+                entries = entries.split_off(entries.len().saturating_sub(tail));
+            }
+            first_poll = false;
+        }
 
-    println!("{}", format_canister_logs(logs).join("\n"));
+        for entry in &entries {
+            print_log_entry(env, entry, opts.all)?;
+        }
 
-    Ok(())
+        if !opts.follow {
+            // fail if no canister could be read
+            return match errors.pop() {
+                Some(err) if errors.len() + 1 == canisters.len() => Err(err),
+                _ => Ok(()),
+            };
+        }
+        tokio::time::sleep(Duration::from_secs(opts.interval)).await;
+    }
+}
+
+fn print_log_entry(env: &dyn Environment, entry: &LogEntry, with_prefix: bool) -> DfxResult {
+    let r = &entry.record;
+    let output = LogRecordOutput {
+        canister: entry.canister,
+        canister_id: entry.canister_id,
+        idx: r.idx,
+        timestamp: format_timestamp(r.timestamp_nanos),
+        content: String::from_utf8(r.content.clone()).ok(),
+        content_hex: hex::encode(&r.content),
+    };
+    print_output_line_with(env, &output, |_| {
+        let prefix = with_prefix.then_some(entry.canister);
+        println!("{}", format_log_record(r, prefix));
+        Ok(())
+    })
 }
@@ -8,6 +8,7 @@ use dfx_core::identity::CallSender;
 use tokio::runtime::Runtime;
 
 mod call;
+mod clone;
 mod create;
 mod delete;
 mod deposit_cycles;
@@ -16,15 +17,21 @@ mod info;
 mod install;
 mod logs;
 mod metadata;
+mod profile;
+mod repl;
 mod request_status;
+mod run_script;
 mod send;
+mod settings;
 mod sign;
+mod snapshot;
 mod start;
 mod status;
 mod stop;
 mod uninstall_code;
 mod update_settings;
 mod url;
+mod verify;
 
 /// Manages canisters deployed on a network replica.
 #[derive(Parser)]
@@ -45,6 +52,7 @@ pub struct CanisterOpts {
 #[derive(Subcommand)]
 pub enum SubCommand {
     Call(call::CanisterCallOpts),
+    Clone(clone::CanisterCloneOpts),
     Create(create::CanisterCreateOpts),
     Delete(delete::CanisterDeleteOpts),
     DepositCycles(deposit_cycles::DepositCyclesOpts),
@@ -52,9 +60,14 @@ pub enum SubCommand {
     Info(info::InfoOpts),
     Install(install::CanisterInstallOpts),
     Metadata(metadata::CanisterMetadataOpts),
+    Profile(profile::CanisterProfileOpts),
+    Repl(repl::CanisterReplOpts),
     RequestStatus(request_status::RequestStatusOpts),
+    RunScript(run_script::RunScriptOpts),
     Send(send::CanisterSendOpts),
+    Settings(settings::SettingsOpts),
     Sign(sign::CanisterSignOpts),
+    Snapshot(snapshot::SnapshotOpts),
     Start(start::CanisterStartOpts),
     Status(status::CanisterStatusOpts),
     Stop(stop::CanisterStopOpts),
@@ -62,6 +75,20 @@ pub enum SubCommand {
     UpdateSettings(update_settings::UpdateSettingsOpts),
     Logs(logs::LogsOpts),
     Url(url::CanisterUrlOpts),
+    Verify(verify::CanisterVerifyOpts),
+}
+
+impl CanisterOpts {
+    pub fn supports_json(&self) -> bool {
+        matches!(
+            self.subcmd,
+            SubCommand::Id(_)
+                | SubCommand::Info(_)
+                | SubCommand::Logs(_)
+                | SubCommand::Settings(_)
+                | SubCommand::Status(_)
+        )
+    }
 }
 
 pub fn exec(env: &dyn Environment, opts: CanisterOpts) -> DfxResult {
@@ -79,6 +106,7 @@ pub fn exec(env: &dyn Environment, opts: CanisterOpts) -> DfxResult {
             .map_err(|e| anyhow!("Failed to determine call sender: {}", e))?;
         match opts.subcmd {
             SubCommand::Call(v) => call::exec(env, v, &call_sender).await,
+            SubCommand::Clone(v) => clone::exec(env, v, &call_sender).await,
             SubCommand::Create(v) => create::exec(env, v, &call_sender).await,
             SubCommand::Delete(v) => delete::exec(env, v, &call_sender).await,
             SubCommand::DepositCycles(v) => deposit_cycles::exec(env, v, &call_sender).await,
@@ -86,9 +114,14 @@ pub fn exec(env: &dyn Environment, opts: CanisterOpts) -> DfxResult {
             SubCommand::Install(v) => install::exec(env, v, &call_sender).await,
             SubCommand::Info(v) => info::exec(env, v).await,
             SubCommand::Metadata(v) => metadata::exec(env, v).await,
+            SubCommand::Profile(v) => profile::exec(env, v).await,
+            SubCommand::Repl(v) => repl::exec(env, v, &call_sender).await,
             SubCommand::RequestStatus(v) => request_status::exec(env, v).await,
+            SubCommand::RunScript(v) => run_script::exec(env, v, &call_sender).await,
             SubCommand::Send(v) => send::exec(env, v, &call_sender).await,
+            SubCommand::Settings(v) => settings::exec(env, v, &call_sender).await,
             SubCommand::Sign(v) => sign::exec(env, v, &call_sender).await,
+            SubCommand::Snapshot(v) => snapshot::exec(env, v, &call_sender).await,
             SubCommand::Start(v) => start::exec(env, v, &call_sender).await,
             SubCommand::Status(v) => status::exec(env, v, &call_sender).await,
             SubCommand::Stop(v) => stop::exec(env, v, &call_sender).await,
@@ -96,6 +129,7 @@ pub fn exec(env: &dyn Environment, opts: CanisterOpts) -> DfxResult {
             SubCommand::UpdateSettings(v) => update_settings::exec(env, v, &call_sender).await,
             SubCommand::Logs(v) => logs::exec(env, v, &call_sender).await,
             SubCommand::Url(v) => url::exec(env, v).await,
+            SubCommand::Verify(v) => verify::exec(env, v).await,
         }
     })
 }
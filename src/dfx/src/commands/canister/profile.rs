@@ -0,0 +1,149 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use anyhow::Context;
+use candid::{Decode, Principal};
+use clap::Parser;
+use ic_utils::Canister;
+use slog::info;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Fetches the execution profile of a canister that was built with `--instrument`,
+/// and writes it in the folded stack format that flamegraph tools read.
+#[derive(Parser)]
+pub struct CanisterProfileOpts {
+    /// Specifies the name or id of the canister to profile.
+    canister: String,
+
+    /// The file to write the profile to. Defaults to `<canister>.folded`.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+pub async fn exec(env: &dyn Environment, opts: CanisterProfileOpts) -> DfxResult {
+    let callee_canister = opts.canister.as_str();
+    let canister_id_store = env.get_canister_id_store()?;
+
+    let canister_id = Principal::from_text(callee_canister)
+        .or_else(|_| canister_id_store.get(callee_canister))?;
+
+    fetch_root_key_if_needed(env).await?;
+
+    let agent = env.get_agent();
+    // the instrumentation stores the function names in the `name` metadata
+    let names = agent
+        .read_state_canister_metadata(canister_id, "name")
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to read the function names of canister {}. Was it built with `dfx build --instrument`?",
+                canister_id
+            )
+        })?;
+    let names: BTreeMap<u16, String> = Decode!(&names, Vec<(u16, String)>)
+        .context("Failed to decode the function names.")?
+        .into_iter()
+        .collect();
+
+    let canister = Canister::builder()
+        .with_agent(agent)
+        .with_canister_id(canister_id)
+        .build()?;
+    let mut trace = vec![];
+    let mut index = 0;
+    loop {
+        let (page, next): (Vec<(i32, i64)>, Option<i32>) = canister
+            .query("__get_profiling")
+            .with_arg(index)
+            .build()
+            .call()
+            .await
+            .context("Failed to fetch the profiling trace.")?;
+        trace.extend(page);
+        match next {
+            Some(next) => index = next,
+            None => break,
+        }
+    }
+
+    let folded = fold_trace(&trace, &names);
+    let output = opts
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("{}.folded", callee_canister)));
+    let content: String = folded
+        .iter()
+        .map(|(stack, instructions)| format!("{stack} {instructions}\n"))
+        .collect();
+    dfx_core::fs::write(&output, content)?;
+    info!(
+        env.get_logger(),
+        "Wrote the profile of {} function calls to {}.",
+        trace.iter().filter(|(id, _)| *id >= 0).count(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Turns a trace of function entries (positive ids) and exits (negative ids), each with the instruction counter at
+/// that point, into the instructions spent in each function itself, by call stack.
+/// Call stacks are formatted as the function names from the outermost call, separated by `;`.
+fn fold_trace(trace: &[(i32, i64)], names: &BTreeMap<u16, String>) -> BTreeMap<String, i64> {
+    let name = |id: i32| {
+        u16::try_from(id)
+            .ok()
+            .and_then(|id| names.get(&id).cloned())
+            .unwrap_or_else(|| format!("func_{id}"))
+    };
+    let mut folded = BTreeMap::new();
+    // the called functions, with the instruction counter on entry and the instructions spent in their callees
+    let mut stack: Vec<(i32, i64, i64)> = vec![];
+    for &(id, counter) in trace {
+        if id >= 0 {
+            stack.push((id, counter, 0));
+            continue;
+        }
+        // an exit without a matching entry is left over from before the trace was started
+        let Some(position) = stack.iter().rposition(|(entered, _, _)| *entered == -id) else {
+            continue;
+        };
+        // functions that were entered after this one and never exited were interrupted by a trap
+        stack.truncate(position + 1);
+        let path = stack.iter().map(|(id, _, _)| name(*id)).collect::<Vec<_>>();
+        let (_, entry, callees) = stack.pop().unwrap();
+        let total = counter - entry;
+        *folded.entry(path.join(";")).or_insert(0) += total - callees;
+        if let Some(caller) = stack.last_mut() {
+            caller.2 += total;
+        }
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_trace_by_call_stack() {
+        let names = BTreeMap::from([(1, "update".to_string()), (2, "hash".to_string())]);
+        let trace = [
+            (1, 100),
+            (2, 110),
+            (-2, 150),
+            (3, 160),
+            (-3, 170),
+            (2, 180),
+            (-2, 200),
+            (-1, 220),
+        ];
+        assert_eq!(
+            fold_trace(&trace, &names),
+            BTreeMap::from([
+                ("update".to_string(), 50),
+                ("update;hash".to_string(), 60),
+                ("update;func_3".to_string(), 10),
+            ])
+        );
+    }
+}
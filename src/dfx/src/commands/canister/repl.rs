@@ -0,0 +1,522 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::operations::canister::get_canister_id_and_candid_path;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::util::{blob_from_arguments, fetch_remote_did_file, print_idl_blob};
+use anyhow::{bail, Context};
+use candid::types::value::IDLValue;
+use candid::types::{Function, Label, Type, TypeEnv, TypeInner};
+use candid::{IDLArgs, Principal};
+use candid_parser::utils::CandidSource;
+use clap::Parser;
+use dfx_core::config::directories::get_user_dfx_config_dir;
+use dfx_core::identity::CallSender;
+use dialoguer::{Completion, History, Input};
+use ic_agent::Agent;
+use regex::Regex;
+use slog::error;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::{BufRead, IsTerminal};
+use std::path::PathBuf;
+
+const MAX_HISTORY: usize = 1000;
+
+const HELP: &str = r#"Call a method with its arguments in Candid, for example:
+    greet ("world")
+Store the first value of the result in a variable, and use it in later arguments:
+    let name = get_name ()
+    greet ($name)
+Commands:
+    :methods           Lists the methods of the canister.
+    :vars              Lists the variables.
+    :identity [name]   Displays the identity that sends the calls, or switches to another identity.
+    :help              Displays this help.
+    :quit              Ends the session."#;
+
+/// Starts an interactive session for calling the methods of a canister.
+/// Completes method names and record fields with Tab, and keeps the history of the sessions.
+#[derive(Parser)]
+pub struct CanisterReplOpts {
+    /// Specifies the name or id of the canister to call.
+    canister: String,
+
+    /// Specifies the format for displaying the return results of the methods.
+    #[arg(long, value_parser = ["idl", "raw", "pp", "json"])]
+    output: Option<String>,
+
+    /// Provide the .did file of the canister. Overrides the Candid interface in the canister metadata,
+    /// and the one of the project.
+    #[arg(long)]
+    candid: Option<PathBuf>,
+}
+
+struct Repl<'a> {
+    env: &'a dyn Environment,
+    agent: Agent,
+    identity: String,
+    canister_id: Principal,
+    types: TypeEnv,
+    methods: BTreeMap<String, Function>,
+    variables: BTreeMap<String, IDLValue>,
+    output: Option<String>,
+    failed_commands: usize,
+}
+
+pub async fn exec(
+    env: &dyn Environment,
+    opts: CanisterReplOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    if call_sender != &CallSender::SelectedId {
+        bail!("The REPL sends its calls from identities, and cannot send them through a wallet.");
+    }
+    fetch_root_key_if_needed(env).await?;
+
+    let agent = env.get_agent();
+    let (canister_id, local_candid_path) = get_canister_id_and_candid_path(env, &opts.canister)?;
+    let did = if let Some(path) = opts.candid {
+        dfx_core::fs::read_to_string(&path)?
+    } else if let Some(did) = fetch_remote_did_file(agent, canister_id).await {
+        did
+    } else if let Some(path) = local_candid_path.filter(|path| path.exists()) {
+        dfx_core::fs::read_to_string(&path)?
+    } else {
+        bail!(
+            "Cannot find the Candid interface of canister {}. Use --candid to provide it.",
+            opts.canister
+        );
+    };
+    let (types, actor) = CandidSource::Text(&did)
+        .load()
+        .context("Failed to load the Candid interface.")?;
+    let actor = actor.context("The Candid interface has no service.")?;
+    let methods = types
+        .as_service(&actor)?
+        .iter()
+        .map(|(name, _)| Ok((name.clone(), types.get_method(&actor, name)?.clone())))
+        .collect::<DfxResult<_>>()?;
+
+    let mut repl = Repl {
+        env,
+        agent: agent.clone(),
+        identity: env.get_selected_identity().cloned().unwrap_or_default(),
+        canister_id,
+        types,
+        methods,
+        variables: BTreeMap::new(),
+        output: opts.output,
+        failed_commands: 0,
+    };
+    let prompt = format!("{} ({})", opts.canister, canister_id);
+
+    if std::io::stdin().is_terminal() {
+        eprintln!("Type :help for help.");
+        let mut history = ReplHistory::load();
+        loop {
+            let completion = repl.completion();
+            let line: String = Input::new()
+                .with_prompt(&prompt)
+                .allow_empty(true)
+                .history_with(&mut history)
+                .completion_with(&completion)
+                .interact_text()?;
+            if !repl.run_line(&line).await {
+                break;
+            }
+        }
+    } else {
+        // Reads the lines of a script piped to the command.
+        for line in std::io::stdin().lock().lines() {
+            if !repl.run_line(&line?).await {
+                break;
+            }
+        }
+        if repl.failed_commands > 0 {
+            bail!("{} command(s) of the script failed.", repl.failed_commands);
+        }
+    }
+    Ok(())
+}
+
+impl Repl<'_> {
+    /// Runs a line, and returns whether the session continues.
+    async fn run_line(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        match line {
+            "" => {}
+            ":quit" | ":exit" => return false,
+            _ => {
+                if let Err(err) = self.run_command(line).await {
+                    error!(self.env.get_logger(), "{:#}", err);
+                    self.failed_commands += 1;
+                }
+            }
+        }
+        true
+    }
+
+    async fn run_command(&mut self, line: &str) -> DfxResult {
+        if let Some(command) = line.strip_prefix(':') {
+            let (command, argument) = split_word(command);
+            match (command, argument) {
+                ("help", "") => println!("{}", HELP),
+                ("methods", "") => {
+                    for (name, func) in &self.methods {
+                        println!("{} : {}", name, func);
+                    }
+                }
+                ("vars", "") => {
+                    for (name, value) in &self.variables {
+                        println!("${} = {}", name, value);
+                    }
+                }
+                ("identity", "") => println!("{}", self.identity),
+                ("identity", name) => self.switch_identity(name)?,
+                _ => bail!("Unknown command ':{}'. Type :help for help.", command),
+            }
+        } else if let Some(assignment) = line.strip_prefix("let ") {
+            let (name, call) = assignment
+                .split_once('=')
+                .context("Expected `let <name> = <method> <arguments>`.")?;
+            let name = name.trim();
+            if !Regex::new("^[A-Za-z_][A-Za-z0-9_]*$")?.is_match(name) {
+                bail!("Invalid variable name '{}'.", name);
+            }
+            let result = self.call(call.trim()).await?;
+            let value = result
+                .args
+                .into_iter()
+                .next()
+                .context("The method returned no value to store.")?;
+            self.variables.insert(name.to_string(), value);
+        } else {
+            self.call(line).await?;
+        }
+        Ok(())
+    }
+
+    /// Calls a method, prints the result and returns it.
+    async fn call(&self, line: &str) -> DfxResult<IDLArgs> {
+        let (method, arguments) = split_word(line);
+        let func = self.methods.get(method).with_context(|| {
+            format!(
+                "Unknown method '{}'. Type :methods to list the methods.",
+                method
+            )
+        })?;
+        let method_type = Some((self.types.clone(), func.clone()));
+        let arguments = match arguments {
+            "" => "()".to_string(),
+            arguments => self.substitute(arguments)?,
+        };
+        let arg = blob_from_arguments(
+            Some(self.env),
+            Some(&arguments),
+            None,
+            None,
+            &method_type,
+            false,
+            false,
+        )?;
+        let blob = if func.is_query() {
+            self.agent
+                .query(&self.canister_id, method)
+                .with_arg(arg)
+                .call()
+                .await
+                .context("Failed query call.")?
+        } else {
+            self.agent
+                .update(&self.canister_id, method)
+                .with_arg(arg)
+                .await
+                .context("Failed update call.")?
+        };
+        print_idl_blob(&blob, self.output.as_deref(), &method_type)?;
+        Ok(IDLArgs::from_bytes_with_types(
+            &blob,
+            &self.types,
+            &func.rets,
+        )?)
+    }
+
+    fn switch_identity(&mut self, name: &str) -> DfxResult {
+        let identity = self
+            .env
+            .new_identity_manager()?
+            .instantiate_identity_from_name(name, self.env.get_logger())?;
+        // the agent keeps the connection and the root key, and only changes the sender
+        self.agent.set_identity(*identity);
+        self.identity = name.to_string();
+        Ok(())
+    }
+
+    /// Replaces `$name` with the Candid value of the variable.
+    fn substitute(&self, arguments: &str) -> DfxResult<String> {
+        let variable = Regex::new(r"\$([A-Za-z_][A-Za-z0-9_]*)")?;
+        let mut result = String::new();
+        let mut last = 0;
+        for captures in variable.captures_iter(arguments) {
+            let (whole, name) = (captures.get(0).unwrap(), &captures[1]);
+            let value = self
+                .variables
+                .get(name)
+                .with_context(|| format!("Unknown variable '${}'.", name))?;
+            result.push_str(&arguments[last..whole.start()]);
+            result.push_str(&value.to_string());
+            last = whole.end();
+        }
+        result.push_str(&arguments[last..]);
+        Ok(result)
+    }
+
+    fn completion(&self) -> ReplCompletion {
+        ReplCompletion {
+            fields: method_labels(&self.types, &self.methods),
+            variables: self
+                .variables
+                .keys()
+                .map(|name| format!("${}", name))
+                .collect(),
+        }
+    }
+}
+
+/// Completes the method names, the names of the record fields and variant cases of the method arguments,
+/// and the variables.
+struct ReplCompletion {
+    fields: BTreeMap<String, BTreeSet<String>>,
+    variables: Vec<String>,
+}
+
+impl Completion for ReplCompletion {
+    fn get(&self, input: &str) -> Option<String> {
+        let start = input
+            .rfind(|c: char| c.is_whitespace() || "(){};,=".contains(c))
+            .map_or(0, |i| i + 1);
+        let (head, word) = input.split_at(start);
+        if word.is_empty() {
+            return None;
+        }
+        let call = match head.trim_start().strip_prefix("let ") {
+            Some(assignment) => assignment.split_once('=').map(|(_, call)| call),
+            None => Some(head),
+        };
+        let candidates: Vec<&String> = match call.map(|call| split_word(call.trim_start())) {
+            Some(("", _)) => self.fields.keys().collect(),
+            Some((method, _)) => self
+                .fields
+                .get(method)
+                .into_iter()
+                .flatten()
+                .chain(&self.variables)
+                .collect(),
+            None => vec![],
+        };
+        let completed = common_prefix(candidates.into_iter().filter(|c| c.starts_with(word)))?;
+        (completed.len() > word.len()).then(|| format!("{}{}", head, completed))
+    }
+}
+
+fn common_prefix<'a>(mut candidates: impl Iterator<Item = &'a String>) -> Option<String> {
+    let first = candidates.next()?.clone();
+    Some(candidates.fold(first, |prefix, candidate| {
+        prefix
+            .chars()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect()
+    }))
+}
+
+/// Splits the first word from the rest of the line.
+fn split_word(line: &str) -> (&str, &str) {
+    match line.find(|c: char| c.is_whitespace() || c == '(') {
+        Some(i) => (&line[..i], line[i..].trim()),
+        None => (line, ""),
+    }
+}
+
+/// Collects the names of the record fields and variant cases in the arguments of each method.
+fn method_labels(
+    env: &TypeEnv,
+    methods: &BTreeMap<String, Function>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    methods
+        .iter()
+        .map(|(name, func)| {
+            let mut labels = BTreeSet::new();
+            for ty in &func.args {
+                collect_labels(env, ty, &mut BTreeSet::new(), &mut labels);
+            }
+            (name.clone(), labels)
+        })
+        .collect()
+}
+
+/// Collects the names of the record fields and variant cases in a type.
+fn collect_labels(
+    env: &TypeEnv,
+    ty: &Type,
+    seen: &mut BTreeSet<String>,
+    labels: &mut BTreeSet<String>,
+) {
+    match ty.as_ref() {
+        TypeInner::Var(name) => {
+            if seen.insert(name.clone()) {
+                if let Ok(ty) = env.find_type(name) {
+                    collect_labels(env, ty, seen, labels);
+                }
+            }
+        }
+        TypeInner::Opt(ty) | TypeInner::Vec(ty) => collect_labels(env, ty, seen, labels),
+        TypeInner::Record(fields) | TypeInner::Variant(fields) => {
+            for field in fields {
+                if let Label::Named(name) = field.id.as_ref() {
+                    labels.insert(name.clone());
+                }
+                collect_labels(env, &field.ty, seen, labels);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The history of the lines entered in the REPL, kept across sessions in the dfx config directory.
+struct ReplHistory {
+    path: Option<PathBuf>,
+    lines: VecDeque<String>,
+}
+
+impl ReplHistory {
+    fn load() -> Self {
+        let path = get_user_dfx_config_dir()
+            .ok()
+            .map(|dir| dir.join("repl_history"));
+        let lines = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|content| {
+                content
+                    .lines()
+                    .rev()
+                    .take(MAX_HISTORY)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { path, lines }
+    }
+}
+
+impl History<String> for ReplHistory {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.lines.get(pos).cloned()
+    }
+
+    fn write(&mut self, line: &String) {
+        if line.trim().is_empty() || self.lines.front() == Some(line) {
+            return;
+        }
+        self.lines.push_front(line.clone());
+        self.lines.truncate(MAX_HISTORY);
+        if let Some(path) = &self.path {
+            let content: Vec<_> = self.lines.iter().rev().map(String::as_str).collect();
+            // the history is a convenience, so failing to save it does not end the session
+            let _ = std::fs::write(path, content.join("\n") + "\n");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE_DID: &str = r#"
+        type Profile = record { name : text; nickname : opt text; kind : variant { user; admin } };
+        service : {
+            get_profile : (text) -> (Profile) query;
+            set_profile : (Profile) -> ();
+            set_name : (text) -> ();
+        }
+    "#;
+
+    fn method_labels_of(did: &str) -> BTreeMap<String, BTreeSet<String>> {
+        let (env, actor) = CandidSource::Text(did).load().unwrap();
+        let actor = actor.unwrap();
+        let methods = env
+            .as_service(&actor)
+            .unwrap()
+            .iter()
+            .map(|(name, _)| (name.clone(), env.get_method(&actor, name).unwrap().clone()))
+            .collect();
+        method_labels(&env, &methods)
+    }
+
+    fn completion() -> ReplCompletion {
+        ReplCompletion {
+            fields: method_labels_of(PROFILE_DID),
+            variables: vec!["$profile".to_string()],
+        }
+    }
+
+    #[test]
+    fn collects_the_labels_of_method_arguments() {
+        let labels = method_labels_of(PROFILE_DID);
+        let labels_of =
+            |method: &str| -> Vec<&str> { labels[method].iter().map(String::as_str).collect() };
+        assert_eq!(
+            labels_of("set_profile"),
+            vec!["admin", "kind", "name", "nickname", "user"]
+        );
+        assert!(labels_of("get_profile").is_empty());
+        assert!(labels_of("set_name").is_empty());
+
+        let labels = method_labels_of(
+            r#"
+            type Tree = variant { leaf : nat; node : record { left : Tree; right : Tree } };
+            service : { insert : (vec opt Tree) -> () }
+        "#,
+        );
+        assert_eq!(
+            labels["insert"]
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            vec!["leaf", "left", "node", "right"]
+        );
+    }
+
+    #[test]
+    fn completes_methods_fields_and_variables() {
+        let completion = completion();
+        assert_eq!(completion.get("get"), Some("get_profile".to_string()));
+        assert_eq!(completion.get("se"), Some("set_".to_string()));
+        assert_eq!(completion.get("set_"), None);
+        assert_eq!(
+            completion.get("let p = get_p"),
+            Some("let p = get_profile".to_string())
+        );
+        assert_eq!(
+            completion.get("set_profile (record { nic"),
+            Some("set_profile (record { nickname".to_string())
+        );
+        assert_eq!(
+            completion.get("set_profile (record { name = \"a\"; kind = variant { adm"),
+            Some("set_profile (record { name = \"a\"; kind = variant { admin".to_string())
+        );
+        assert_eq!(
+            completion.get("set_profile ($pro"),
+            Some("set_profile ($profile".to_string())
+        );
+        assert_eq!(completion.get("set_name (record { nic"), None);
+    }
+
+    #[test]
+    fn splits_the_method_from_its_arguments() {
+        assert_eq!(split_word("greet (\"world\")"), ("greet", "(\"world\")"));
+        assert_eq!(split_word("greet(\"world\")"), ("greet", "(\"world\")"));
+        assert_eq!(split_word("read"), ("read", ""));
+    }
+}
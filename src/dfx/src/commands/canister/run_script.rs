@@ -0,0 +1,322 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::operations::canister::get_canister_id_and_candid_path;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::util::clap::parsers::file_parser;
+use crate::util::json_to_candid::json_to_idl_args;
+use crate::util::{blob_from_arguments, fetch_remote_did_file, get_candid_type};
+use anyhow::{bail, Context};
+use candid::types::value::IDLValue;
+use candid::types::{Function, TypeEnv, TypeInner};
+use candid::{IDLArgs, Principal};
+use candid_parser::utils::CandidSource;
+use clap::Parser;
+use dfx_core::identity::CallSender;
+use fn_error_context::context;
+use ic_agent::Agent;
+use idl2json::{idl2json, Idl2JsonOptions};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use slog::info;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Runs a script of canister calls, and checks their results.
+/// The script is a YAML or JSON file with a list of steps. Each step calls a canister method,
+/// and can check the result, capture it for the arguments of later steps, and use another identity.
+#[derive(Parser)]
+pub struct RunScriptOpts {
+    /// Specifies the path of the script.
+    #[arg(value_parser = file_parser)]
+    script: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Script {
+    /// The identity that sends the calls of the steps that do not specify one.
+    /// By default, the selected identity.
+    #[serde(default)]
+    identity: Option<String>,
+
+    steps: Vec<Step>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Step {
+    /// The name or id of the canister to call.
+    canister: String,
+
+    /// The method to call.
+    method: String,
+
+    /// The arguments, in Candid.
+    #[serde(default)]
+    args: Option<String>,
+
+    /// The arguments as JSON values, one per argument, converted to the Candid types of the method.
+    #[serde(default)]
+    json_args: Option<Vec<JsonValue>>,
+
+    /// The identity that sends the call.
+    #[serde(default)]
+    identity: Option<String>,
+
+    /// The expected result, in Candid.
+    #[serde(default)]
+    expect: Option<String>,
+
+    /// The expected result, in the JSON format of `dfx canister call --output json`.
+    #[serde(default)]
+    expect_json: Option<JsonValue>,
+
+    /// Expects the call to fail with an error that contains this text.
+    #[serde(default)]
+    expect_error: Option<String>,
+
+    /// The name of a variable that captures the first value of the result.
+    /// Later steps can use it as `${name}` in `args`, `expect` and `json_args`.
+    #[serde(default)]
+    capture: Option<String>,
+}
+
+/// Runs the steps of a script, with one agent per identity and the Candid interfaces of the canisters it calls.
+struct ScriptRunner<'a> {
+    env: &'a dyn Environment,
+    agents: HashMap<String, Agent>,
+    interfaces: HashMap<Principal, Option<String>>,
+    variables: BTreeMap<String, IDLValue>,
+}
+
+pub async fn exec(
+    env: &dyn Environment,
+    opts: RunScriptOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    if call_sender != &CallSender::SelectedId {
+        bail!("Scripts send their calls from identities, and cannot send them through a wallet.");
+    }
+    let log = env.get_logger();
+    let script = load_script(&opts.script)?;
+
+    fetch_root_key_if_needed(env).await?;
+
+    let mut runner = ScriptRunner {
+        env,
+        agents: HashMap::new(),
+        interfaces: HashMap::new(),
+        variables: BTreeMap::new(),
+    };
+    for (i, step) in script.steps.iter().enumerate() {
+        info!(
+            log,
+            "Step {}/{}: {}.{}",
+            i + 1,
+            script.steps.len(),
+            step.canister,
+            step.method
+        );
+        let identity = step.identity.as_deref().or(script.identity.as_deref());
+        runner.run_step(step, identity).await.with_context(|| {
+            format!("Step {} ({}.{}) failed.", i + 1, step.canister, step.method)
+        })?;
+    }
+    info!(log, "All {} steps passed.", script.steps.len());
+    Ok(())
+}
+
+#[context("Failed to load script {}.", path.display())]
+fn load_script(path: &Path) -> DfxResult<Script> {
+    let content = dfx_core::fs::read_to_string(path)?;
+    // YAML is a superset of JSON, so this reads JSON scripts too
+    Ok(serde_yaml_ng::from_str(&content)?)
+}
+
+impl ScriptRunner<'_> {
+    async fn run_step(&mut self, step: &Step, identity: Option<&str>) -> DfxResult {
+        let agent = self.agent(identity)?;
+        let (canister_id, candid_path) = get_canister_id_and_candid_path(self.env, &step.canister)?;
+        if !self.interfaces.contains_key(&canister_id) {
+            let did = match fetch_remote_did_file(&agent, canister_id).await {
+                Some(did) => Some(did),
+                None => candid_path.and_then(|path| dfx_core::fs::read_to_string(&path).ok()),
+            };
+            self.interfaces.insert(canister_id, did);
+        }
+        let method_type = self.interfaces[&canister_id]
+            .as_deref()
+            .and_then(|did| get_candid_type(CandidSource::Text(did), &step.method));
+
+        let arg = self.arguments(step, &method_type)?;
+        let is_query = method_type.as_ref().is_some_and(|(_, f)| f.is_query());
+        let response = if is_query {
+            agent
+                .query(&canister_id, &step.method)
+                .with_arg(arg)
+                .call()
+                .await
+        } else {
+            agent.update(&canister_id, &step.method).with_arg(arg).await
+        };
+
+        let blob = match (response, &step.expect_error) {
+            (Ok(blob), None) => blob,
+            (Err(err), None) => return Err(err).context("The call failed."),
+            (Err(err), Some(expected)) if err.to_string().contains(expected.as_str()) => {
+                info!(
+                    self.env.get_logger(),
+                    "The call failed as expected: {}", err
+                );
+                return Ok(());
+            }
+            (Err(err), Some(expected)) => bail!(
+                "The call failed with '{}', but an error containing '{}' was expected.",
+                err,
+                expected
+            ),
+            (Ok(_), Some(expected)) => bail!(
+                "The call succeeded, but an error containing '{}' was expected.",
+                expected
+            ),
+        };
+        let result = decode(&blob, &method_type)?;
+        println!("{}", result);
+
+        if let Some(expected) = &step.expect {
+            let expected = self.substitute(expected);
+            let expected = candid_parser::parse_idl_args(&expected)
+                .with_context(|| format!("Invalid expected result '{}'.", expected))?;
+            // encoding and decoding the expected result gives it the same types and field labels as the result
+            let expected = match &method_type {
+                Some((env, func)) => expected.to_bytes_with_types(env, &func.rets)?,
+                None => expected.to_bytes()?,
+            };
+            let expected = decode(&expected, &method_type)?;
+            if result != expected {
+                bail!("The result is {}, but {} was expected.", result, expected);
+            }
+        }
+        if let Some(expected) = &step.expect_json {
+            let actual = to_json(&result);
+            if actual != *expected {
+                bail!("The result is {}, but {} was expected.", actual, expected);
+            }
+        }
+        if let Some(name) = &step.capture {
+            let value = result
+                .args
+                .first()
+                .cloned()
+                .context("The call returned no value to capture.")?;
+            self.variables.insert(name.clone(), value);
+        }
+        Ok(())
+    }
+
+    /// The agent that sends calls as the given identity, or as the selected identity.
+    fn agent(&mut self, identity: Option<&str>) -> DfxResult<Agent> {
+        let Some(name) = identity else {
+            return Ok(self.env.get_agent().clone());
+        };
+        if let Some(agent) = self.agents.get(name) {
+            return Ok(agent.clone());
+        }
+        let identity = self
+            .env
+            .new_identity_manager()?
+            .instantiate_identity_from_name(name, self.env.get_logger())?;
+        // the agents share the connection and the root key of the agent of the selected identity
+        let mut agent = self.env.get_agent().clone();
+        agent.set_identity(*identity);
+        self.agents.insert(name.to_string(), agent.clone());
+        Ok(agent)
+    }
+
+    fn arguments(
+        &self,
+        step: &Step,
+        method_type: &Option<(TypeEnv, Function)>,
+    ) -> DfxResult<Vec<u8>> {
+        match (&step.args, &step.json_args) {
+            (Some(_), Some(_)) => bail!("A step cannot have both `args` and `json_args`."),
+            (_, Some(json_args)) => {
+                let (env, func) = method_type
+                    .as_ref()
+                    .context("JSON arguments require the Candid interface of the canister.")?;
+                let json_args: Vec<_> = json_args.iter().map(|v| self.substitute_json(v)).collect();
+                Ok(json_to_idl_args(env, &func.args, &json_args)?
+                    .to_bytes_with_types(env, &func.args)?)
+            }
+            (Some(args), None) => blob_from_arguments(
+                Some(self.env),
+                Some(&self.substitute(args)),
+                None,
+                None,
+                method_type,
+                false,
+                false,
+            ),
+            (None, None) => {
+                let all_optional = method_type.as_ref().map_or(true, |(_, func)| {
+                    func.args
+                        .iter()
+                        .all(|t| matches!(t.as_ref(), TypeInner::Opt(_)))
+                });
+                if !all_optional {
+                    bail!("The method expects arguments, but the step has no `args`.");
+                }
+                blob_from_arguments(Some(self.env), None, None, None, method_type, false, false)
+            }
+        }
+    }
+
+    /// Replaces `${name}` with the Candid value of the captured variable.
+    fn substitute(&self, text: &str) -> String {
+        self.variables
+            .iter()
+            .fold(text.to_string(), |text, (name, value)| {
+                text.replace(&format!("${{{name}}}"), &value.to_string())
+            })
+    }
+
+    /// Replaces the strings `"${name}"` with the JSON value of the captured variable.
+    fn substitute_json(&self, value: &JsonValue) -> JsonValue {
+        match value {
+            JsonValue::String(s) => s
+                .strip_prefix("${")
+                .and_then(|s| s.strip_suffix('}'))
+                .and_then(|name| self.variables.get(name))
+                .map_or_else(
+                    || value.clone(),
+                    |v| idl2json(v, &Idl2JsonOptions::default()),
+                ),
+            JsonValue::Array(items) => {
+                JsonValue::Array(items.iter().map(|v| self.substitute_json(v)).collect())
+            }
+            JsonValue::Object(map) => JsonValue::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.substitute_json(v)))
+                    .collect(),
+            ),
+            _ => value.clone(),
+        }
+    }
+}
+
+fn decode(blob: &[u8], method_type: &Option<(TypeEnv, Function)>) -> DfxResult<IDLArgs> {
+    let args = match method_type {
+        Some((env, func)) => IDLArgs::from_bytes_with_types(blob, env, &func.rets)?,
+        None => IDLArgs::from_bytes(blob)?,
+    };
+    Ok(args)
+}
+
+/// The result in JSON: the value if there is one, otherwise an array of the values.
+fn to_json(result: &IDLArgs) -> JsonValue {
+    let options = Idl2JsonOptions::default();
+    match result.args.as_slice() {
+        [value] => idl2json(value, &options),
+        values => JsonValue::Array(values.iter().map(|v| idl2json(v, &options)).collect()),
+    }
+}
@@ -0,0 +1,146 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::operations::canister::settings_diff::{
+    get_settings_diff, ControllerChanges, SettingDrift,
+};
+use crate::lib::output::print_output;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::util::clap::canister_tag_opt::CanisterTagOpt;
+use anyhow::bail;
+use candid::Principal;
+use clap::{Parser, Subcommand};
+use dfx_core::identity::CallSender;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
+
+/// Compares the settings of canisters with dfx.json.
+#[derive(Parser)]
+pub struct SettingsOpts {
+    #[command(subcommand)]
+    subcmd: SubCommand,
+}
+
+#[derive(Subcommand)]
+pub enum SubCommand {
+    /// Reports the settings of canisters that differ from dfx.json, without updating them.
+    /// `dfx deploy` updates them.
+    Diff(SettingsDiffOpts),
+}
+
+#[derive(Parser)]
+pub struct SettingsDiffOpts {
+    /// Specifies the name of the canister to compare.
+    /// You must specify either a canister name or the --all flag.
+    canister: Option<String>,
+
+    /// Compares the settings of all of the canisters configured in the dfx.json file.
+    #[arg(long, required_unless_present_any(["canister", "tag", "exclude"]))]
+    all: bool,
+
+    #[command(flatten)]
+    canister_tags: CanisterTagOpt,
+}
+
+/// The settings of canisters that differ from dfx.json, as printed by `dfx canister settings diff`.
+#[derive(Serialize)]
+#[serde(transparent)]
+struct SettingsDiffOutput(Vec<CanisterSettingsDiff>);
+
+#[derive(Serialize)]
+struct CanisterSettingsDiff {
+    canister: String,
+    canister_id: Principal,
+    drift: Vec<SettingDrift>,
+    /// The controllers to add and remove, if they differ from dfx.json.
+    controllers: Option<ControllerChanges>,
+}
+
+impl Display for SettingsDiffOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, canister) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            if canister.drift.is_empty() && canister.controllers.is_none() {
+                write!(f, "{}: matches dfx.json", canister.canister)?;
+                continue;
+            }
+            write!(f, "{}:", canister.canister)?;
+            for drift in &canister.drift {
+                write!(
+                    f,
+                    "\n  {}: {} -> {}",
+                    drift.setting, drift.current, drift.desired
+                )?;
+            }
+            if let Some(controllers) = &canister.controllers {
+                for added in &controllers.added {
+                    write!(f, "\n  controllers: add {}", added)?;
+                }
+                for removed in &controllers.removed {
+                    write!(f, "\n  controllers: remove {}", removed)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub async fn exec(
+    env: &dyn Environment,
+    opts: SettingsOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    fetch_root_key_if_needed(env).await?;
+    match opts.subcmd {
+        SubCommand::Diff(opts) => diff(env, opts, call_sender).await,
+    }
+}
+
+async fn diff(
+    env: &dyn Environment,
+    opts: SettingsDiffOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    opts.canister_tags
+        .ensure_no_canister(opts.canister.as_deref())?;
+    if env.get_network_descriptor().is_playground() {
+        bail!("The settings of canisters borrowed from a playground cannot be read.");
+    }
+    let config = env.get_config_or_anyhow()?;
+    let canister_id_store = env.get_canister_id_store()?;
+    let single_canister = opts.canister.is_some();
+    let canister_names = if let Some(canister) = opts.canister {
+        vec![canister]
+    } else if config.get_config().canisters.is_some() {
+        opts.canister_tags.select(config.get_config())?
+    } else {
+        vec![]
+    };
+
+    let mut canisters = vec![];
+    for canister in canister_names {
+        if config
+            .get_config()
+            .is_remote_canister(&canister, &env.get_network_descriptor().name)?
+        {
+            continue;
+        }
+        // With --all, canisters that are not created yet have nothing to compare.
+        let canister_id = if single_canister {
+            canister_id_store.get(&canister)?
+        } else if let Some(canister_id) = canister_id_store.find(&canister) {
+            canister_id
+        } else {
+            continue;
+        };
+        let diff = get_settings_diff(env, &canister, canister_id, call_sender).await?;
+        canisters.push(CanisterSettingsDiff {
+            canister,
+            canister_id,
+            drift: diff.drift,
+            controllers: diff.controllers,
+        });
+    }
+    print_output(env, &SettingsDiffOutput(canisters))
+}
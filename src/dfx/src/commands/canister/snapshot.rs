@@ -0,0 +1,165 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::operations::canister;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::util::clap::parsers::snapshot_id_parser;
+use candid::Principal;
+use clap::{Parser, Subcommand};
+use dfx_core::identity::CallSender;
+use slog::info;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// Manages snapshots of the state of a canister.
+/// A canister must be stopped to take a snapshot of it or to load a snapshot into it.
+#[derive(Parser)]
+pub struct SnapshotOpts {
+    #[command(subcommand)]
+    subcmd: SubCommand,
+}
+
+#[derive(Subcommand)]
+pub enum SubCommand {
+    /// Takes a snapshot of the Wasm module, memory and stable memory of a stopped canister.
+    Create(SnapshotCreateOpts),
+    /// Lists the snapshots of a canister.
+    List(SnapshotListOpts),
+    /// Loads a snapshot into a stopped canister, replacing its module and state.
+    Load(SnapshotLoadOpts),
+    /// Deletes a snapshot of a canister.
+    Delete(SnapshotDeleteOpts),
+}
+
+#[derive(Parser)]
+pub struct SnapshotCreateOpts {
+    /// Specifies the name or id of the canister to take a snapshot of.
+    canister: String,
+
+    /// Specifies the id of an existing snapshot to replace with the new snapshot.
+    #[arg(long, value_parser = snapshot_id_parser)]
+    replace: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct SnapshotListOpts {
+    /// Specifies the name or id of the canister to list the snapshots of.
+    canister: String,
+}
+
+#[derive(Parser)]
+pub struct SnapshotLoadOpts {
+    /// Specifies the name or id of the canister to load the snapshot into.
+    canister: String,
+
+    /// Specifies the id of the snapshot to load.
+    #[arg(value_parser = snapshot_id_parser)]
+    snapshot: String,
+}
+
+#[derive(Parser)]
+pub struct SnapshotDeleteOpts {
+    /// Specifies the name or id of the canister the snapshot belongs to.
+    canister: String,
+
+    /// Specifies the id of the snapshot to delete.
+    #[arg(value_parser = snapshot_id_parser)]
+    snapshot: String,
+}
+
+pub async fn exec(
+    env: &dyn Environment,
+    opts: SnapshotOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    fetch_root_key_if_needed(env).await?;
+    match opts.subcmd {
+        SubCommand::Create(opts) => create(env, opts, call_sender).await,
+        SubCommand::List(opts) => list(env, opts, call_sender).await,
+        SubCommand::Load(opts) => load(env, opts, call_sender).await,
+        SubCommand::Delete(opts) => delete(env, opts, call_sender).await,
+    }
+}
+
+async fn create(
+    env: &dyn Environment,
+    opts: SnapshotCreateOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    let canister_id = canister_id(env, &opts.canister)?;
+    let replace = opts.replace.as_deref().map(hex::decode).transpose()?;
+    let snapshot = canister::take_canister_snapshot(env, canister_id, replace, call_sender).await?;
+    info!(
+        env.get_logger(),
+        "Created a new snapshot of canister {}. Snapshot ID: {}",
+        opts.canister,
+        hex::encode(snapshot.id)
+    );
+    Ok(())
+}
+
+async fn list(
+    env: &dyn Environment,
+    opts: SnapshotListOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    let canister_id = canister_id(env, &opts.canister)?;
+    let snapshots = canister::list_canister_snapshots(env, canister_id, call_sender).await?;
+    if snapshots.is_empty() {
+        info!(
+            env.get_logger(),
+            "No snapshots found for canister {}.", opts.canister
+        );
+    }
+    for snapshot in snapshots {
+        let taken_at =
+            OffsetDateTime::from_unix_timestamp_nanos(snapshot.taken_at_timestamp as i128)?
+                .format(&Rfc3339)?;
+        println!(
+            "{}: {} bytes, taken at {}",
+            hex::encode(snapshot.id),
+            snapshot.total_size,
+            taken_at
+        );
+    }
+    Ok(())
+}
+
+async fn load(
+    env: &dyn Environment,
+    opts: SnapshotLoadOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    let canister_id = canister_id(env, &opts.canister)?;
+    canister::load_canister_snapshot(env, canister_id, &hex::decode(&opts.snapshot)?, call_sender)
+        .await?;
+    info!(
+        env.get_logger(),
+        "Loaded snapshot {} into canister {}.", opts.snapshot, opts.canister
+    );
+    Ok(())
+}
+
+async fn delete(
+    env: &dyn Environment,
+    opts: SnapshotDeleteOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    let canister_id = canister_id(env, &opts.canister)?;
+    canister::delete_canister_snapshot(
+        env,
+        canister_id,
+        &hex::decode(&opts.snapshot)?,
+        call_sender,
+    )
+    .await?;
+    info!(
+        env.get_logger(),
+        "Deleted snapshot {} of canister {}.", opts.snapshot, opts.canister
+    );
+    Ok(())
+}
+
+fn canister_id(env: &dyn Environment, canister: &str) -> DfxResult<Principal> {
+    let canister_id_store = env.get_canister_id_store()?;
+    Ok(Principal::from_text(canister).or_else(|_| canister_id_store.get(canister))?)
+}
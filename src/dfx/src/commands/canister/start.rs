@@ -2,6 +2,7 @@ use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::operations::canister;
 use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::util::clap::canister_tag_opt::CanisterTagOpt;
 use candid::Principal;
 use clap::Parser;
 use dfx_core::identity::CallSender;
@@ -14,8 +15,11 @@ pub struct CanisterStartOpts {
     canister: Option<String>,
 
     /// Starts all of the canisters configured in the dfx.json file.
-    #[arg(long, required_unless_present("canister"))]
+    #[arg(long, required_unless_present_any(["canister", "tag", "exclude"]))]
     all: bool,
+
+    #[command(flatten)]
+    canister_tags: CanisterTagOpt,
 }
 
 async fn start_canister(
@@ -46,13 +50,15 @@ pub async fn exec(
     call_sender: &CallSender,
 ) -> DfxResult {
     fetch_root_key_if_needed(env).await?;
+    opts.canister_tags
+        .ensure_no_canister(opts.canister.as_deref())?;
 
     if let Some(canister) = opts.canister.as_deref() {
         start_canister(env, canister, call_sender).await
-    } else if opts.all {
+    } else if opts.all || opts.canister_tags.is_set() {
         let config = env.get_config_or_anyhow()?;
-        if let Some(canisters) = &config.get_config().canisters {
-            for canister in canisters.keys() {
+        if config.get_config().canisters.is_some() {
+            for canister in &opts.canister_tags.select(config.get_config())? {
                 start_canister(env, canister, call_sender).await?;
             }
         }
@@ -1,12 +1,17 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::operations::canister;
+use crate::lib::output::{print_output, print_output_with};
 use crate::lib::root_key::fetch_root_key_if_needed;
-use candid::Principal;
+use crate::util::clap::canister_tag_opt::CanisterTagOpt;
+use candid::{Nat, Principal};
 use clap::Parser;
 use dfx_core::identity::CallSender;
 use fn_error_context::context;
-use ic_utils::interfaces::management_canister::LogVisibility;
+use ic_utils::interfaces::management_canister::{LogVisibility, StatusCallResult};
+use itertools::Itertools;
+use serde::{Serialize, Serializer};
+use std::fmt::{self, Display, Formatter};
 
 /// Returns the current status of a canister: Running, Stopping, or Stopped. Also carries information like balance, current settings, memory used and everything returned by 'info'.
 #[derive(Parser)]
@@ -16,8 +21,145 @@ pub struct CanisterStatusOpts {
     canister: Option<String>,
 
     /// Returns status information for all of the canisters configured in the dfx.json file.
-    #[arg(long, required_unless_present("canister"))]
+    #[arg(long, required_unless_present_any(["canister", "tag", "exclude"]))]
     all: bool,
+
+    #[command(flatten)]
+    canister_tags: CanisterTagOpt,
+}
+
+/// The status of a canister, as printed by `dfx --json canister status`.
+/// Amounts of cycles and other arbitrary-precision numbers are strings of decimal digits.
+#[derive(Serialize)]
+struct CanisterStatusOutput {
+    canister: String,
+    canister_id: Principal,
+    /// One of `running`, `stopping` or `stopped`.
+    status: String,
+    controllers: Vec<Principal>,
+    #[serde(serialize_with = "decimal")]
+    memory_allocation: Nat,
+    #[serde(serialize_with = "decimal")]
+    compute_allocation: Nat,
+    #[serde(serialize_with = "decimal")]
+    freezing_threshold: Nat,
+    #[serde(serialize_with = "decimal")]
+    memory_size: Nat,
+    #[serde(serialize_with = "decimal")]
+    cycles: Nat,
+    #[serde(serialize_with = "decimal")]
+    reserved_cycles: Nat,
+    #[serde(serialize_with = "optional_decimal")]
+    reserved_cycles_limit: Option<Nat>,
+    #[serde(serialize_with = "optional_decimal")]
+    wasm_memory_limit: Option<Nat>,
+    module_hash: Option<String>,
+    query_stats: QueryStatsOutput,
+    /// Either `controllers` or `public`.
+    log_visibility: String,
+}
+
+#[derive(Serialize)]
+struct QueryStatsOutput {
+    #[serde(serialize_with = "decimal")]
+    num_calls_total: Nat,
+    #[serde(serialize_with = "decimal")]
+    num_instructions_total: Nat,
+    #[serde(serialize_with = "decimal")]
+    request_payload_bytes_total: Nat,
+    #[serde(serialize_with = "decimal")]
+    response_payload_bytes_total: Nat,
+}
+
+fn decimal<S: Serializer>(n: &Nat, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&n.0.to_string())
+}
+
+fn optional_decimal<S: Serializer>(n: &Option<Nat>, serializer: S) -> Result<S::Ok, S::Error> {
+    match n {
+        Some(n) => serializer.serialize_some(&n.0.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl CanisterStatusOutput {
+    fn new(canister: &str, canister_id: Principal, status: &StatusCallResult) -> Self {
+        let settings = &status.settings;
+        let mut controllers = settings.controllers.clone();
+        controllers.sort_by_key(Principal::to_text);
+        Self {
+            canister: canister.to_string(),
+            canister_id,
+            status: status.status.to_string().to_lowercase(),
+            controllers,
+            memory_allocation: settings.memory_allocation.clone(),
+            compute_allocation: settings.compute_allocation.clone(),
+            freezing_threshold: settings.freezing_threshold.clone(),
+            memory_size: status.memory_size.clone(),
+            cycles: status.cycles.clone(),
+            reserved_cycles: status.reserved_cycles.clone(),
+            reserved_cycles_limit: settings.reserved_cycles_limit.clone(),
+            wasm_memory_limit: settings.wasm_memory_limit.clone(),
+            module_hash: status
+                .module_hash
+                .as_ref()
+                .map(|hash| format!("0x{}", hex::encode(hash))),
+            query_stats: QueryStatsOutput {
+                num_calls_total: status.query_stats.num_calls_total.clone(),
+                num_instructions_total: status.query_stats.num_instructions_total.clone(),
+                request_payload_bytes_total: status.query_stats.request_payload_bytes_total.clone(),
+                response_payload_bytes_total: status
+                    .query_stats
+                    .response_payload_bytes_total
+                    .clone(),
+            },
+            log_visibility: log_visibility(&settings.log_visibility).to_string(),
+        }
+    }
+}
+
+impl Display for CanisterStatusOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let status = match self.status.as_str() {
+            "running" => "Running",
+            "stopping" => "Stopping",
+            "stopped" => "Stopped",
+            other => other,
+        };
+        let controllers = self.controllers.iter().map(Principal::to_text).join(" ");
+        let reserved_cycles_limit = match &self.reserved_cycles_limit {
+            Some(limit) => format!("{} Cycles", limit),
+            None => "Not Set".to_string(),
+        };
+        let wasm_memory_limit = match &self.wasm_memory_limit {
+            Some(limit) => format!("{} Bytes", limit),
+            None => "Not Set".to_string(),
+        };
+        let query_stats = &self.query_stats;
+
+        write!(f, "Canister status call result for {canister}.\nStatus: {status}\nControllers: {controllers}\nMemory allocation: {memory_allocation}\nCompute allocation: {compute_allocation}\nFreezing threshold: {freezing_threshold}\nMemory Size: {memory_size:?}\nBalance: {balance} Cycles\nReserved: {reserved} Cycles\nReserved cycles limit: {reserved_cycles_limit}\nWasm memory limit: {wasm_memory_limit}\nModule hash: {module_hash}\nNumber of queries: {queries_total}\nInstructions spent in queries: {query_instructions_total}\nTotal query request payload size (bytes): {query_req_payload_total}\nTotal query response payload size (bytes): {query_resp_payload_total}\nLog visibility: {log_visibility}",
+            canister = self.canister,
+            memory_allocation = self.memory_allocation,
+            compute_allocation = self.compute_allocation,
+            freezing_threshold = self.freezing_threshold,
+            memory_size = self.memory_size,
+            balance = self.cycles,
+            reserved = self.reserved_cycles,
+            module_hash = self.module_hash.as_deref().unwrap_or("None"),
+            queries_total = query_stats.num_calls_total,
+            query_instructions_total = query_stats.num_instructions_total,
+            query_req_payload_total = query_stats.request_payload_bytes_total,
+            query_resp_payload_total = query_stats.response_payload_bytes_total,
+            log_visibility = self.log_visibility,
+        )
+    }
+}
+
+fn log_visibility(log_visibility: &LogVisibility) -> &'static str {
+    match log_visibility {
+        LogVisibility::Controllers => "controllers",
+        LogVisibility::Public => "public",
+    }
 }
 
 #[context("Failed to get canister status for '{}'.", canister)]
@@ -25,53 +167,13 @@ async fn canister_status(
     env: &dyn Environment,
     canister: &str,
     call_sender: &CallSender,
-) -> DfxResult {
+) -> DfxResult<CanisterStatusOutput> {
     let canister_id_store = env.get_canister_id_store()?;
     let canister_id =
         Principal::from_text(canister).or_else(|_| canister_id_store.get(canister))?;
 
     let status = canister::get_canister_status(env, canister_id, call_sender).await?;
-
-    let mut controllers: Vec<_> = status
-        .settings
-        .controllers
-        .iter()
-        .map(Principal::to_text)
-        .collect();
-    controllers.sort();
-
-    let reserved_cycles_limit = if let Some(limit) = status.settings.reserved_cycles_limit {
-        format!("{} Cycles", limit)
-    } else {
-        "Not Set".to_string()
-    };
-
-    let wasm_memory_limit = if let Some(limit) = status.settings.wasm_memory_limit {
-        format!("{} Bytes", limit)
-    } else {
-        "Not Set".to_string()
-    };
-    let log_visibility = match status.settings.log_visibility {
-        LogVisibility::Controllers => "controllers",
-        LogVisibility::Public => "public",
-    };
-
-    println!("Canister status call result for {canister}.\nStatus: {status}\nControllers: {controllers}\nMemory allocation: {memory_allocation}\nCompute allocation: {compute_allocation}\nFreezing threshold: {freezing_threshold}\nMemory Size: {memory_size:?}\nBalance: {balance} Cycles\nReserved: {reserved} Cycles\nReserved cycles limit: {reserved_cycles_limit}\nWasm memory limit: {wasm_memory_limit}\nModule hash: {module_hash}\nNumber of queries: {queries_total}\nInstructions spent in queries: {query_instructions_total}\nTotal query request payload size (bytes): {query_req_payload_total}\nTotal query response payload size (bytes): {query_resp_payload_total}\nLog visibility: {log_visibility}",
-        status = status.status,
-        controllers = controllers.join(" "),
-        memory_allocation = status.settings.memory_allocation,
-        compute_allocation = status.settings.compute_allocation,
-        freezing_threshold = status.settings.freezing_threshold,
-        memory_size = status.memory_size,
-        balance = status.cycles,
-        reserved = status.reserved_cycles,
-        module_hash = status.module_hash.map_or_else(|| "None".to_string(), |v| format!("0x{}", hex::encode(v))),
-        queries_total = status.query_stats.num_calls_total,
-        query_instructions_total = status.query_stats.num_instructions_total,
-        query_req_payload_total = status.query_stats.request_payload_bytes_total,
-        query_resp_payload_total = status.query_stats.response_payload_bytes_total,
-    );
-    Ok(())
+    Ok(CanisterStatusOutput::new(canister, canister_id, &status))
 }
 
 pub async fn exec(
@@ -80,17 +182,26 @@ pub async fn exec(
     call_sender: &CallSender,
 ) -> DfxResult {
     fetch_root_key_if_needed(env).await?;
+    opts.canister_tags
+        .ensure_no_canister(opts.canister.as_deref())?;
 
     if let Some(canister) = opts.canister.as_deref() {
-        canister_status(env, canister, call_sender).await
-    } else if opts.all {
+        let output = canister_status(env, canister, call_sender).await?;
+        print_output(env, &output)
+    } else if opts.all || opts.canister_tags.is_set() {
         let config = env.get_config_or_anyhow()?;
-        if let Some(canisters) = &config.get_config().canisters {
-            for canister in canisters.keys() {
-                canister_status(env, canister, call_sender).await?;
+        let mut outputs = vec![];
+        if config.get_config().canisters.is_some() {
+            for canister in &opts.canister_tags.select(config.get_config())? {
+                outputs.push(canister_status(env, canister, call_sender).await?);
             }
         }
-        Ok(())
+        print_output_with(env, &outputs, |outputs| {
+            for output in outputs {
+                println!("{output}");
+            }
+            Ok(())
+        })
     } else {
         unreachable!()
     }
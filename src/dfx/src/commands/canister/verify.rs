@@ -0,0 +1,165 @@
+use crate::lib::builders::{BuildConfig, BuildOutput, WasmBuildOutput};
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::models::canister::{Canister, CanisterPool};
+use crate::lib::operations::canister::add_canisters_with_ids;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::lib::state_tree::canister_info::read_state_tree_canister_module_hash;
+use crate::lib::wasm::file::{bytes_to_module, decompress_bytes};
+use anyhow::{anyhow, bail};
+use clap::Parser;
+use ic_agent::AgentError;
+use ic_wasm::metadata::{get_metadata, remove_metadata};
+use sha2::{Digest, Sha256};
+use slog::{info, warn};
+use std::ffi::OsStr;
+
+/// Builds a canister locally and checks that the module deployed on the network is the same,
+/// so that anyone can audit which code a canister runs.
+#[derive(Parser)]
+pub struct CanisterVerifyOpts {
+    /// Specifies the name of the canister to verify.
+    canister: String,
+}
+
+pub async fn exec(env: &dyn Environment, opts: CanisterVerifyOpts) -> DfxResult {
+    let log = env.get_logger();
+    let agent = env.get_agent();
+    let config = env.get_config_or_anyhow()?;
+    let canister_name = opts.canister.as_str();
+    let canister_id = env.get_canister_id_store()?.get(canister_name)?;
+
+    fetch_root_key_if_needed(env).await?;
+    let deployed_hash = read_state_tree_canister_module_hash(agent, canister_id)
+        .await?
+        .ok_or_else(|| anyhow!("Canister '{}' has no module installed.", canister_name))?;
+
+    let required_canisters = config
+        .get_config()
+        .get_canister_names_with_dependencies(Some(canister_name))?;
+    let canisters_to_load = add_canisters_with_ids(&required_canisters, env, &config);
+    let canister_pool = CanisterPool::load(env, false, &canisters_to_load)?;
+    // the output of the builder tells which post-processing step diverged, so previous builds are not reused
+    let build_config =
+        BuildConfig::from_config(&config, env.get_network_descriptor().is_playground())?
+            .with_canisters_to_build(required_canisters)
+            .with_force_rebuild(true);
+    info!(log, "Building canister '{}'...", canister_name);
+    canister_pool.build_or_fail(log, &build_config).await?;
+    let canister = canister_pool
+        .get_first_canister_with_name(canister_name)
+        .ok_or_else(|| anyhow!("Canister '{}' was not built.", canister_name))?;
+
+    let steps = post_processing_steps(&canister)?;
+    let (_, installed) = steps.last().unwrap();
+    let local_hash = Sha256::digest(installed);
+    if local_hash[..] == deployed_hash[..] {
+        info!(
+            log,
+            "Canister '{}' matches the local build. Module hash: 0x{}",
+            canister_name,
+            hex::encode(deployed_hash)
+        );
+        return Ok(());
+    }
+
+    warn!(
+        log,
+        "The module hash of canister '{}' is 0x{}, but the local build has module hash 0x{}.",
+        canister_name,
+        hex::encode(&deployed_hash),
+        hex::encode(local_hash)
+    );
+    let matching_step = steps[..steps.len() - 1]
+        .iter()
+        .find(|(_, bytes)| Sha256::digest(bytes)[..] == deployed_hash[..]);
+    if let Some((step, _)) = matching_step {
+        warn!(
+            log,
+            "The deployed module matches this step of the local build: {}. The post-processing after that step differs, for example the `gzip`, `shrink`, `optimize` or `metadata` settings.",
+            step
+        );
+    } else {
+        let local_module = bytes_to_module(&module_bytes(&canister, installed)?)?;
+        let mut metadata_differs = false;
+        for (name, local) in metadata_sections(&local_module) {
+            let state = match agent.read_state_canister_metadata(canister_id, &name).await {
+                Ok(deployed) if deployed == local => continue,
+                Ok(_) => "differs",
+                Err(AgentError::LookupPathAbsent(_)) => "is missing from the deployed module",
+                Err(e) => {
+                    warn!(log, "Metadata '{}' could not be read: {}", name, e);
+                    continue;
+                }
+            };
+            metadata_differs = true;
+            warn!(log, "Metadata '{}' {}.", name, state);
+        }
+        if !metadata_differs {
+            warn!(
+                log,
+                "The code of the deployed module differs from the local build. It was built from different sources, or with a different toolchain or build settings."
+            );
+        }
+    }
+    bail!(
+        "The deployed module of canister '{}' does not match the local build.",
+        canister_name
+    );
+}
+
+/// The module after each post-processing step, from the output of the builder to the module that is installed.
+fn post_processing_steps(canister: &Canister) -> DfxResult<Vec<(&'static str, Vec<u8>)>> {
+    let info = canister.get_info();
+    let wasm_path = info.get_build_wasm_path();
+    let mut steps = vec![];
+    if let Some(BuildOutput {
+        wasm: WasmBuildOutput::File(path),
+        ..
+    }) = canister.get_build_output()
+    {
+        // some builders write their output where post-processing writes the installed module
+        if *path != wasm_path {
+            steps.push(("the output of the builder", dfx_core::fs::read(path)?));
+        }
+    }
+    let installed = dfx_core::fs::read(&wasm_path)?;
+    let module = module_bytes(canister, &installed)?;
+    let mut m = bytes_to_module(&module)?;
+    for (name, _) in metadata_sections(&m) {
+        remove_metadata(&mut m, &name);
+    }
+    steps.push((
+        "the optimized module, before metadata is added",
+        m.emit_wasm(),
+    ));
+    if module != installed {
+        steps.push(("the module with metadata, before it is gzipped", module));
+    }
+    steps.push(("the installed module", installed));
+    Ok(steps)
+}
+
+/// The uncompressed bytes of the installed module.
+fn module_bytes(canister: &Canister, installed: &[u8]) -> DfxResult<Vec<u8>> {
+    if canister.get_info().get_build_wasm_path().extension() == Some(OsStr::new("gz")) {
+        decompress_bytes(installed)
+    } else {
+        Ok(installed.to_vec())
+    }
+}
+
+/// The names and contents of the canister metadata sections of a module.
+fn metadata_sections(m: &walrus::Module) -> Vec<(String, Vec<u8>)> {
+    m.customs
+        .iter()
+        .filter_map(|(_, section)| {
+            let name = section
+                .name()
+                .strip_prefix("icp:public ")
+                .or_else(|| section.name().strip_prefix("icp:private "))?;
+            let content = get_metadata(m, name)?;
+            Some((name.to_string(), content.to_vec()))
+        })
+        .collect()
+}
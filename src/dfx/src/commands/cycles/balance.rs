@@ -1,12 +1,14 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::operations::cycles_ledger;
+use crate::lib::output::print_output_with;
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::util::clap::parsers::icrc_subaccount_parser;
 use crate::util::{format_as_trillions, pretty_thousand_separators};
 use candid::Principal;
 use clap::Parser;
 use icrc_ledger_types::icrc1::account::Subaccount;
+use serde::Serialize;
 
 /// Get the cycle balance of the selected Identity's cycles wallet.
 #[derive(Parser)]
@@ -24,6 +26,16 @@ pub struct CyclesBalanceOpts {
     precise: bool,
 }
 
+/// The result of `dfx --json cycles balance`.
+#[derive(Serialize)]
+struct CyclesBalanceOutput {
+    owner: Principal,
+    /// The subaccount in hex, if one was given.
+    subaccount: Option<String>,
+    /// The balance in cycles, as a string of decimal digits.
+    cycles: String,
+}
+
 pub async fn exec(env: &dyn Environment, opts: CyclesBalanceOpts) -> DfxResult {
     fetch_root_key_if_needed(env).await?;
 
@@ -36,14 +48,20 @@ pub async fn exec(env: &dyn Environment, opts: CyclesBalanceOpts) -> DfxResult {
 
     let balance = cycles_ledger::balance(agent, owner, opts.subaccount).await?;
 
-    if opts.precise {
-        println!("{} cycles.", balance);
-    } else {
-        println!(
-            "{} TC (trillion cycles).",
-            pretty_thousand_separators(format_as_trillions(balance))
-        );
-    }
-
-    Ok(())
+    let output = CyclesBalanceOutput {
+        owner,
+        subaccount: opts.subaccount.map(hex::encode),
+        cycles: balance.to_string(),
+    };
+    print_output_with(env, &output, |_| {
+        if opts.precise {
+            println!("{} cycles.", balance);
+        } else {
+            println!(
+                "{} TC (trillion cycles).",
+                pretty_thousand_separators(format_as_trillions(balance))
+            );
+        }
+        Ok(())
+    })
 }
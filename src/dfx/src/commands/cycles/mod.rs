@@ -10,6 +10,7 @@ mod balance;
 mod convert;
 mod redeem_faucet_coupon;
 pub mod top_up;
+mod top_up_all;
 mod transfer;
 
 /// Helper commands to manage the user's cycles.
@@ -29,10 +30,20 @@ enum SubCommand {
     Balance(balance::CyclesBalanceOpts),
     Convert(convert::ConvertOpts),
     TopUp(top_up::TopUpOpts),
+    TopUpAll(top_up_all::TopUpAllOpts),
     Transfer(transfer::TransferOpts),
     RedeemFaucetCoupon(redeem_faucet_coupon::RedeemFaucetCouponOpts),
 }
 
+impl CyclesOpts {
+    pub fn supports_json(&self) -> bool {
+        matches!(
+            self.subcmd,
+            SubCommand::Balance(_) | SubCommand::TopUpAll(_)
+        )
+    }
+}
+
 pub fn exec(env: &dyn Environment, opts: CyclesOpts) -> DfxResult {
     let agent_env = create_agent_environment(env, opts.network.to_network_name())?;
     let runtime = Runtime::new().expect("Unable to create a runtime");
@@ -42,6 +53,7 @@ pub fn exec(env: &dyn Environment, opts: CyclesOpts) -> DfxResult {
             SubCommand::Balance(v) => balance::exec(&agent_env, v).await,
             SubCommand::Convert(v) => convert::exec(&agent_env, v).await,
             SubCommand::TopUp(v) => top_up::exec(&agent_env, v).await,
+            SubCommand::TopUpAll(v) => top_up_all::exec(&agent_env, v).await,
             SubCommand::Transfer(v) => transfer::exec(&agent_env, v).await,
             SubCommand::RedeemFaucetCoupon(v) => redeem_faucet_coupon::exec(&agent_env, v).await,
         }
@@ -0,0 +1,313 @@
+use crate::commands::ledger::top_up::MEMO_TOP_UP_CANISTER;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::identity::wallet::wallet_canister_id;
+use crate::lib::ledger_types::Memo;
+use crate::lib::nns_types::icpts::{ICPTs, TRANSACTION_FEE};
+use crate::lib::operations::canister::{deposit_cycles, get_canister_status};
+use crate::lib::operations::cmc::{notify_top_up, transfer_cmc};
+use crate::lib::operations::cycles_ledger;
+use crate::lib::operations::ledger::xdr_permyriad_per_icp;
+use crate::lib::output::print_output;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::util::clap::parsers::icrc_subaccount_parser;
+use crate::util::{format_as_trillions, pretty_thousand_separators};
+use anyhow::{bail, Context};
+use candid::Principal;
+use clap::Parser;
+use dfx_core::config::model::dfinity::{CyclesFundingSource, CyclesPolicy};
+use dfx_core::identity::CallSender;
+use icrc_ledger_types::icrc1::account::Subaccount;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+use slog::info;
+use std::fmt::{self, Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Tops up the project canisters whose cycles balance is below the minimum balance of their `cycles` policy in dfx.json.
+#[derive(Parser)]
+pub struct TopUpAllOpts {
+    /// Prints the top-ups without sending any cycles.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Transfer cycles from this subaccount, for canisters funded from the cycles ledger.
+    #[arg(long, value_parser = icrc_subaccount_parser)]
+    from_subaccount: Option<Subaccount>,
+
+    /// Specify a wallet canister id through which to read the balances of the canisters,
+    /// and from which to send cycles to the canisters funded from a wallet.
+    /// If none specified, canisters funded from a wallet use the selected Identity's wallet canister,
+    /// and the balances of the others are read as the selected Identity.
+    #[arg(long)]
+    wallet: Option<Principal>,
+}
+
+/// The result of `dfx cycles top-up-all`.
+#[derive(Serialize)]
+struct TopUpAllOutput {
+    dry_run: bool,
+    canisters: Vec<CanisterTopUp>,
+}
+
+#[derive(Serialize)]
+struct CanisterTopUp {
+    canister: String,
+    canister_id: Option<Principal>,
+    /// The balance before the top-up, in cycles, as a string of decimal digits.
+    balance: Option<String>,
+    /// The cycles sent to the canister or, with --dry-run, that would be sent.
+    top_up: String,
+    result: TopUpResult,
+    /// The error, if the top-up failed.
+    error: Option<String>,
+}
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+enum TopUpResult {
+    ToppedUp,
+    WouldTopUp,
+    AboveMinimum,
+    NotCreated,
+    Failed,
+}
+
+pub async fn exec(env: &dyn Environment, opts: TopUpAllOpts) -> DfxResult {
+    let config = env.get_config_or_anyhow()?;
+    let policies: Vec<(String, CyclesPolicy)> = config
+        .get_config()
+        .canisters
+        .iter()
+        .flatten()
+        .filter_map(|(name, canister)| Some((name.clone(), canister.cycles.clone()?)))
+        .collect();
+    if policies.is_empty() {
+        info!(
+            env.get_logger(),
+            "No canister in dfx.json has a cycles policy."
+        );
+        return Ok(());
+    }
+
+    fetch_root_key_if_needed(env).await?;
+    let canister_id_store = env.get_canister_id_store()?;
+
+    let mut canisters = vec![];
+    for (name, policy) in policies {
+        let Some(canister_id) = canister_id_store.find(&name) else {
+            canisters.push(CanisterTopUp {
+                canister: name,
+                canister_id: None,
+                balance: None,
+                top_up: "0".to_string(),
+                result: TopUpResult::NotCreated,
+                error: None,
+            });
+            continue;
+        };
+        let mut top_up = CanisterTopUp {
+            canister: name,
+            canister_id: Some(canister_id),
+            balance: None,
+            top_up: "0".to_string(),
+            result: TopUpResult::Failed,
+            error: None,
+        };
+        let balance = match get_balance(env, canister_id, policy.funding_source, &opts).await {
+            Ok(balance) => balance,
+            Err(err) => {
+                top_up.error = Some(format!("{err:#}"));
+                canisters.push(top_up);
+                continue;
+            }
+        };
+        top_up.balance = Some(balance.to_string());
+        let Some(amount) = policy.top_up_amount(balance) else {
+            top_up.result = TopUpResult::AboveMinimum;
+            canisters.push(top_up);
+            continue;
+        };
+        if opts.dry_run {
+            top_up.top_up = amount.to_string();
+            top_up.result = TopUpResult::WouldTopUp;
+        } else {
+            match send_cycles(env, canister_id, amount, policy.funding_source, &opts).await {
+                Ok(sent) => {
+                    top_up.top_up = sent.to_string();
+                    top_up.result = TopUpResult::ToppedUp;
+                }
+                Err(err) => top_up.error = Some(format!("{err:#}")),
+            }
+        }
+        canisters.push(top_up);
+    }
+
+    let failed = canisters
+        .iter()
+        .filter(|c| c.result == TopUpResult::Failed)
+        .count();
+    print_output(
+        env,
+        &TopUpAllOutput {
+            dry_run: opts.dry_run,
+            canisters,
+        },
+    )?;
+    if failed > 0 {
+        bail!("Failed to top up {} canister(s).", failed);
+    }
+    Ok(())
+}
+
+/// Reads the balance of a canister as a controller: through the wallet that funds it, or as the selected identity.
+async fn get_balance(
+    env: &dyn Environment,
+    canister_id: Principal,
+    source: CyclesFundingSource,
+    opts: &TopUpAllOpts,
+) -> DfxResult<u128> {
+    let call_sender = match (opts.wallet, source) {
+        (Some(wallet_id), _) => CallSender::Wallet(wallet_id),
+        (None, CyclesFundingSource::Wallet) => CallSender::Wallet(funding_wallet(env, opts)?),
+        (None, _) => CallSender::SelectedId,
+    };
+    let status = get_canister_status(env, canister_id, &call_sender).await?;
+    status
+        .cycles
+        .0
+        .to_u128()
+        .context("The cycles balance does not fit in 128 bits.")
+}
+
+/// Sends cycles to a canister from the funding source of its policy, and returns the number of cycles it received.
+async fn send_cycles(
+    env: &dyn Environment,
+    canister_id: Principal,
+    amount: u128,
+    source: CyclesFundingSource,
+    opts: &TopUpAllOpts,
+) -> DfxResult<u128> {
+    let agent = env.get_agent();
+    match source {
+        CyclesFundingSource::CyclesLedger => {
+            let created_at_time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos() as u64;
+            cycles_ledger::withdraw(
+                agent,
+                env.get_logger(),
+                canister_id,
+                amount,
+                created_at_time,
+                opts.from_subaccount,
+            )
+            .await?;
+            Ok(amount)
+        }
+        CyclesFundingSource::Wallet => {
+            let wallet_id = funding_wallet(env, opts)?;
+            deposit_cycles(env, canister_id, &CallSender::Wallet(wallet_id), amount).await?;
+            Ok(amount)
+        }
+        CyclesFundingSource::Icp => {
+            // 1 ICP is worth xdr_permyriad_per_icp / 10_000 XDR, and 1 XDR buys 10^12 cycles,
+            // so 1 e8s buys xdr_permyriad_per_icp cycles.
+            let cycles_per_e8s = xdr_permyriad_per_icp(agent).await? as u128;
+            if cycles_per_e8s == 0 {
+                bail!("The cycles minting canister reported an ICP/XDR conversion rate of 0.");
+            }
+            let e8s = amount.div_ceil(cycles_per_e8s);
+            let amount = ICPTs::from_e8s(
+                e8s.try_into()
+                    .context("The number of e8s does not fit in 64 bits.")?,
+            );
+            let height = transfer_cmc(
+                agent,
+                env.get_logger(),
+                Memo(MEMO_TOP_UP_CANISTER),
+                amount,
+                TRANSACTION_FEE,
+                None,
+                canister_id,
+                None,
+            )
+            .await?;
+            let cycles = notify_top_up(agent, canister_id, height)
+                .await
+                .with_context(|| {
+                    format!("Failed to notify the top-up at block height {height}.")
+                })?;
+            Ok(cycles)
+        }
+    }
+}
+
+/// The wallet given with --wallet, or else the wallet of the selected identity.
+fn funding_wallet(env: &dyn Environment, opts: &TopUpAllOpts) -> DfxResult<Principal> {
+    if let Some(wallet_id) = opts.wallet {
+        return Ok(wallet_id);
+    }
+    let identity = env
+        .get_selected_identity()
+        .context("No identity is selected.")?;
+    wallet_canister_id(env.get_network_descriptor(), identity)?
+        .with_context(|| format!("Identity {identity} has no wallet on this network."))
+}
+
+fn trillions(cycles: &str) -> String {
+    let cycles = cycles.parse().unwrap_or_default();
+    pretty_thousand_separators(format_as_trillions(cycles))
+}
+
+impl Display for TopUpAllOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut total = 0u128;
+        let mut count = 0;
+        for canister in &self.canisters {
+            write!(f, "{}: ", canister.canister)?;
+            if let Some(balance) = &canister.balance {
+                write!(f, "balance {} TC, ", trillions(balance))?;
+            }
+            match canister.result {
+                TopUpResult::ToppedUp => {
+                    write!(f, "topped up with {} TC", trillions(&canister.top_up))?
+                }
+                TopUpResult::WouldTopUp => write!(
+                    f,
+                    "would be topped up with {} TC",
+                    trillions(&canister.top_up)
+                )?,
+                TopUpResult::AboveMinimum => write!(f, "above the minimum balance")?,
+                TopUpResult::NotCreated => write!(f, "not created on this network")?,
+                TopUpResult::Failed => write!(
+                    f,
+                    "failed: {}",
+                    canister.error.as_deref().unwrap_or_default()
+                )?,
+            }
+            writeln!(f)?;
+            if matches!(
+                canister.result,
+                TopUpResult::ToppedUp | TopUpResult::WouldTopUp
+            ) {
+                total += canister.top_up.parse::<u128>().unwrap_or_default();
+                count += 1;
+            }
+        }
+        let verb = if self.dry_run {
+            "Would top up"
+        } else {
+            "Topped up"
+        };
+        write!(
+            f,
+            "{} {} of {} canister(s) with {} TC in total.",
+            verb,
+            count,
+            self.canisters.len(),
+            pretty_thousand_separators(format_as_trillions(total))
+        )
+    }
+}
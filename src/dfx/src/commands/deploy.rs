@@ -3,12 +3,13 @@ use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::network::network_opt::NetworkOpt;
-use crate::lib::operations::canister::deploy_canisters::deploy_canisters;
 use crate::lib::operations::canister::deploy_canisters::DeployMode::{
     ComputeEvidence, ForceReinstallSingleCanister, NormalDeploy, PrepareForProposal,
 };
+use crate::lib::operations::canister::deploy_canisters::{deploy_canisters, DeployOptions};
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::util::clap::argument_from_cli::ArgumentFromCliLongOpt;
+use crate::util::clap::canister_tag_opt::CanisterTagOpt;
 use crate::util::clap::parsers::{cycle_amount_parser, icrc_subaccount_parser};
 use crate::util::clap::subnet_selection_opt::SubnetSelectionOpt;
 use crate::util::url::{construct_frontend_url, construct_ui_canister_url};
@@ -22,6 +23,7 @@ use ic_utils::interfaces::management_canister::builders::InstallMode;
 use icrc_ledger_types::icrc1::account::Subaccount;
 use slog::info;
 use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::str::FromStr;
 use tokio::runtime::Runtime;
@@ -34,6 +36,9 @@ pub struct DeployOpts {
     /// If you don’t specify a canister name, all canisters defined in the dfx.json file are deployed.
     canister_name: Option<String>,
 
+    #[command(flatten)]
+    canister_tags: CanisterTagOpt,
+
     #[command(flatten)]
     argument_from_cli: ArgumentFromCliLongOpt,
 
@@ -108,6 +113,24 @@ pub struct DeployOpts {
     #[command(flatten)]
     subnet_selection: SubnetSelectionOpt,
 
+    /// The maximum number of canisters to build at the same time.
+    /// Canisters are only built once the canisters they depend on are built.
+    #[arg(long, short = 'j', default_value = "1")]
+    jobs: NonZeroUsize,
+
+    /// Build all canisters, even those whose build inputs did not change since their last build.
+    #[arg(long)]
+    force_rebuild: bool,
+
+    /// Instrument the Wasm of the canisters, so that `dfx canister profile` can fetch the instructions spent in each function.
+    #[arg(long)]
+    instrument: bool,
+
+    /// Prints what the deploy would do to each canister, without creating, installing or updating any canister.
+    /// Only reads the state of the network. Canisters that do not exist yet, or whose dependencies do not exist yet, are not built.
+    #[arg(long, conflicts_with_all(["by_proposal", "compute_evidence", "always_assist"]))]
+    plan: bool,
+
     /// Always use Candid assist when the argument types are all optional.
     #[arg(
         long,
@@ -118,11 +141,19 @@ pub struct DeployOpts {
     always_assist: bool,
 }
 
+impl DeployOpts {
+    /// Only the plan printed with `--plan` is available as JSON.
+    pub fn supports_json(&self) -> bool {
+        self.plan
+    }
+}
+
 pub fn exec(env: &dyn Environment, opts: DeployOpts) -> DfxResult {
     let env = create_agent_environment(env, opts.network.to_network_name())?;
     let runtime = Runtime::new().expect("Unable to create a runtime");
 
     let canister_name = opts.canister_name.as_deref();
+    opts.canister_tags.ensure_no_canister(canister_name)?;
     let (argument_from_cli, argument_type) = opts.argument_from_cli.get_argument_and_type()?;
     if argument_from_cli.is_some() && canister_name.is_none() {
         bail!("The init argument can only be set when deploying a single canister.");
@@ -178,27 +209,35 @@ pub fn exec(env: &dyn Environment, opts: DeployOpts) -> DfxResult {
 
     runtime.block_on(fetch_root_key_if_needed(&env))?;
 
+    let options = DeployOptions {
+        some_canister: canister_name,
+        canister_tags: &opts.canister_tags,
+        argument: argument_from_cli.as_deref(),
+        argument_type: argument_type.as_deref(),
+        deploy_mode: &deploy_mode,
+        upgrade_unchanged: opts.upgrade_unchanged,
+        with_cycles,
+        created_at_time: opts.created_at_time,
+        specified_id_from_cli: opts.specified_id,
+        from_subaccount: opts.from_subaccount,
+        no_wallet: opts.no_wallet,
+        skip_consent: opts.yes,
+        env_file,
+        no_asset_upgrade: opts.no_asset_upgrade,
+        always_assist: opts.always_assist,
+        jobs: opts.jobs.get(),
+        force_rebuild: opts.force_rebuild,
+        instrument: opts.instrument,
+        plan: opts.plan,
+    };
     runtime.block_on(deploy_canisters(
         &env,
-        canister_name,
-        argument_from_cli.as_deref(),
-        argument_type.as_deref(),
-        &deploy_mode,
-        opts.upgrade_unchanged,
-        with_cycles,
-        opts.created_at_time,
-        opts.specified_id,
+        &options,
         &call_sender,
-        opts.from_subaccount,
-        opts.no_wallet,
-        opts.yes,
-        env_file,
-        opts.no_asset_upgrade,
         &mut subnet_selection,
-        opts.always_assist,
     ))?;
 
-    if matches!(deploy_mode, NormalDeploy | ForceReinstallSingleCanister(_)) {
+    if !opts.plan && matches!(deploy_mode, NormalDeploy | ForceReinstallSingleCanister(_)) {
         display_urls(&env)?;
     }
     Ok(())
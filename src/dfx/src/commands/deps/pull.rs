@@ -12,19 +12,18 @@ use crate::lib::network::network_opt::NetworkOpt;
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::lib::state_tree::canister_info::read_state_tree_canister_module_hash;
 use crate::lib::wasm::file::{decompress_bytes, read_wasm_module};
-use crate::util::download_file;
+use crate::util::{download_file, write_to_tempfile_then_rename};
 use anyhow::{anyhow, bail, Context};
 use candid::Principal;
 use clap::Parser;
 use dfx_core::config::model::dfinity::Pullable;
-use dfx_core::fs::composite::{ensure_dir_exists, ensure_parent_dir_exists};
+use dfx_core::fs::composite::ensure_parent_dir_exists;
 use fn_error_context::context;
 use ic_agent::{Agent, AgentError};
 use ic_wasm::metadata::get_metadata;
 use sha2::{Digest, Sha256};
 use slog::{error, info, trace, warn, Logger};
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
-use std::io::Write;
 use std::path::Path;
 
 /// Pull canisters upon which the project depends.
@@ -318,19 +317,6 @@ async fn get_hash_on_chain(
     }
 }
 
-#[context("Failed to write to a tempfile then rename it to {}", path.display())]
-fn write_to_tempfile_then_rename(content: &[u8], path: &Path) -> DfxResult {
-    assert!(path.is_absolute());
-    let dir = dfx_core::fs::parent(path)?;
-    ensure_dir_exists(&dir)?;
-    let mut f = tempfile::NamedTempFile::new_in(&dir)
-        .with_context(|| format!("Failed to create a NamedTempFile in {dir:?}"))?;
-    f.write_all(content)
-        .with_context(|| format!("Failed to write the NamedTempFile at {:?}", f.path()))?;
-    dfx_core::fs::rename(f.path(), path)?;
-    Ok(())
-}
-
 #[context("Failed to copy candid path of pull dependency {name}")]
 pub fn copy_service_candid_to_project(
     project_root: &Path,
@@ -1,9 +1,11 @@
 use crate::config::cache::DiskBasedCache;
 use crate::lib::agent::create_anonymous_agent_environment;
 use crate::lib::builders::BuildConfig;
+use crate::lib::canister_info::rust::RustCanisterInfo;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::models::canister::CanisterPool;
+use crate::util::clap::canister_tag_opt::CanisterTagOpt;
 use clap::Parser;
 use tokio::runtime::Runtime;
 
@@ -14,6 +16,9 @@ pub struct GenerateOpts {
     /// If you do not specify a canister name, generates types for all canisters.
     canister_name: Option<String>,
 
+    #[command(flatten)]
+    canister_tags: CanisterTagOpt,
+
     // Deprecated/hidden because it had/has no effect.
     // Cannot use 'hide' on a flattened  object - inlined the flattened network specifier
     #[arg(long, global = true, hide = true)]
@@ -31,21 +36,29 @@ pub fn exec(env: &dyn Environment, opts: GenerateOpts) -> DfxResult {
     // already.
     DiskBasedCache::install(&env.get_cache().version_str())?;
 
+    opts.canister_tags
+        .ensure_no_canister(opts.canister_name.as_deref())?;
+
     // Option can be None which means generate types for all canisters
-    let canisters_to_load = config
-        .get_config()
-        .get_canister_names_with_dependencies(opts.canister_name.as_deref())?;
+    let canisters_to_load = opts
+        .canister_tags
+        .select_with_dependencies(config.get_config(), opts.canister_name.as_deref())?;
     let canisters_to_generate = canisters_to_load.clone().into_iter().collect();
 
     let canister_pool_load = CanisterPool::load(&env, false, &canisters_to_load)?;
 
-    // If generate for motoko canister, build first
+    // If generate for motoko canister, or for a rust canister whose candid is extracted from its wasm, build first
     let mut build_before_generate = Vec::new();
     let mut build_dependees = Vec::new();
     for canister in canister_pool_load.get_canister_list() {
         let canister_name = canister.get_name();
         if let Some(info) = canister_pool_load.get_first_canister_with_name(canister_name) {
-            if info.get_info().is_motoko() {
+            let extracts_candid = info.get_info().is_rust()
+                && info
+                    .get_info()
+                    .as_info::<RustCanisterInfo>()?
+                    .extracts_candid();
+            if info.get_info().is_motoko() || extracts_candid {
                 build_before_generate.push(canister_name.to_string());
             }
             for dependent_canister in config
@@ -72,7 +85,7 @@ pub fn exec(env: &dyn Environment, opts: GenerateOpts) -> DfxResult {
         .unwrap_or(false)
     {
         let canister_pool_build = CanisterPool::load(&env, true, &build_dependees)?;
-        slog::info!(log, "Building canisters before generate for Motoko");
+        slog::info!(log, "Building canisters before generate");
         let runtime = Runtime::new().expect("Unable to create a runtime");
         runtime.block_on(canister_pool_build.build_or_fail(log, &build_config))?;
     }
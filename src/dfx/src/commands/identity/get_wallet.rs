@@ -3,14 +3,30 @@ use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::identity::wallet::get_or_create_wallet;
 use crate::lib::network::network_opt::NetworkOpt;
+use crate::lib::output::print_output;
 use crate::lib::root_key::fetch_root_key_if_needed;
+use candid::Principal;
 use clap::Parser;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
 use tokio::runtime::Runtime;
 
 /// Gets the canister ID for the wallet associated with your identity on a network.
 #[derive(Parser)]
 pub struct GetWalletOpts {}
 
+/// The result of `dfx identity get-wallet`.
+#[derive(Serialize)]
+struct GetWalletOutput {
+    wallet: Principal,
+}
+
+impl Display for GetWalletOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.wallet)
+    }
+}
+
 pub fn exec(env: &dyn Environment, _opts: GetWalletOpts, network: NetworkOpt) -> DfxResult {
     let agent_env = create_agent_environment(env, network.to_network_name())?;
     let runtime = Runtime::new().expect("Unable to create a runtime");
@@ -23,13 +39,8 @@ pub fn exec(env: &dyn Environment, _opts: GetWalletOpts, network: NetworkOpt) ->
         .to_string();
     let network = agent_env.get_network_descriptor();
 
-    runtime.block_on(async {
-        println!(
-            "{}",
-            get_or_create_wallet(&agent_env, network, &identity_name).await?
-        );
-        DfxResult::Ok(())
-    })?;
+    let wallet = runtime
+        .block_on(async { get_or_create_wallet(&agent_env, network, &identity_name).await })?;
 
-    Ok(())
+    print_output(env, &GetWalletOutput { wallet })
 }
@@ -1,27 +1,46 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
+use crate::lib::output::print_output_with;
 use clap::Parser;
+use serde::Serialize;
 use std::io::Write;
 
 /// Lists existing identities.
 #[derive(Parser)]
 pub struct ListOpts {}
 
+/// An identity, as listed by `dfx --json identity list`.
+#[derive(Serialize)]
+struct IdentityListEntry {
+    name: String,
+    /// Whether this is the identity that commands use by default.
+    selected: bool,
+}
+
 pub fn exec(env: &dyn Environment, _opts: ListOpts) -> DfxResult {
     let mgr = env.new_identity_manager()?;
     let identities = mgr.get_identity_names(env.get_logger())?;
     let current_identity = mgr.get_selected_identity_name();
-    for identity in identities {
-        if current_identity == &identity {
-            // same identity, suffix with '*'.
-            print!("{}", identity);
-            std::io::stdout().flush()?;
-            eprint!(" *");
-            std::io::stderr().flush()?;
-            println!();
-        } else {
-            println!("{}", identity);
+    let entries: Vec<_> = identities
+        .into_iter()
+        .map(|name| IdentityListEntry {
+            selected: current_identity == &name,
+            name,
+        })
+        .collect();
+    print_output_with(env, &entries, |entries| {
+        for entry in entries {
+            if entry.selected {
+                // same identity, suffix with '*'.
+                print!("{}", entry.name);
+                std::io::stdout().flush()?;
+                eprint!(" *");
+                std::io::stderr().flush()?;
+                println!();
+            } else {
+                println!("{}", entry.name);
+            }
         }
-    }
-    Ok(())
+        Ok(())
+    })
 }
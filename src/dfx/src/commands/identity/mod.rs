@@ -44,6 +44,18 @@ enum SubCommand {
     Whoami(whoami::WhoAmIOpts),
 }
 
+impl IdentityOpts {
+    pub fn supports_json(&self) -> bool {
+        matches!(
+            self.subcmd,
+            SubCommand::GetPrincipal(_)
+                | SubCommand::GetWallet(_)
+                | SubCommand::List(_)
+                | SubCommand::Whoami(_)
+        )
+    }
+}
+
 pub fn exec(env: &dyn Environment, opts: IdentityOpts) -> DfxResult {
     match opts.subcmd {
         SubCommand::DeployWallet(v) => deploy_wallet::exec(env, v, opts.network),
@@ -1,21 +1,36 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
+use crate::lib::output::print_output;
 use anyhow::anyhow;
+use candid::Principal;
 use clap::Parser;
 use ic_agent::identity::Identity;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
 
 /// Shows the textual representation of the Principal associated with the current identity.
 #[derive(Parser)]
 pub struct GetPrincipalOpts {}
 
+/// The result of `dfx identity get-principal`.
+#[derive(Serialize)]
+struct GetPrincipalOutput {
+    principal: Principal,
+}
+
+impl Display for GetPrincipalOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.principal)
+    }
+}
+
 pub fn exec(env: &dyn Environment, _opts: GetPrincipalOpts) -> DfxResult {
     let identity = env
         .new_identity_manager()?
         .instantiate_selected_identity(env.get_logger())?;
-    let principal_id = identity
+    let principal = identity
         .as_ref()
         .sender()
         .map_err(|err| anyhow!("{}", err))?;
-    println!("{}", principal_id.to_text());
-    Ok(())
+    print_output(env, &GetPrincipalOutput { principal })
 }
@@ -1,14 +1,28 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
+use crate::lib::output::print_output;
 use clap::Parser;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
 
 /// Shows the name of the current identity.
 #[derive(Parser)]
 pub struct WhoAmIOpts {}
 
+/// The result of `dfx identity whoami`.
+#[derive(Serialize)]
+struct WhoAmIOutput {
+    name: String,
+}
+
+impl Display for WhoAmIOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
 pub fn exec(env: &dyn Environment, _opts: WhoAmIOpts) -> DfxResult {
     let mgr = env.new_identity_manager()?;
-    let identity = mgr.get_selected_identity_name();
-    println!("{}", identity);
-    Ok(())
+    let name = mgr.get_selected_identity_name().clone();
+    print_output(env, &WhoAmIOutput { name })
 }
@@ -23,6 +23,11 @@ enum InfoType {
     WebserverPort,
     /// Show the path to network configuration file
     NetworksJsonPath,
+    /// Show the configuration of a canister with the overrides for the selected network applied
+    CanisterConfig {
+        /// The name of the canister as defined in dfx.json
+        canister: String,
+    },
 }
 
 #[derive(Parser)]
@@ -56,6 +61,18 @@ pub fn exec(env: &dyn Environment, opts: InfoOpts) -> DfxResult {
             .to_str()
             .context("Failed to convert networks.json path to a string.")?
             .to_string(),
+        InfoType::CanisterConfig { canister } => {
+            let env = create_anonymous_agent_environment(env, opts.network.to_network_name())?;
+            let config = env.get_config_or_anyhow()?;
+            let canister_config = config
+                .get_config()
+                .canisters
+                .as_ref()
+                .and_then(|canisters| canisters.get(&canister))
+                .with_context(|| format!("Cannot find canister '{}'.", canister))?;
+            serde_json::to_string_pretty(canister_config)
+                .context("Failed to serialize canister configuration.")?
+        }
     };
     println!("{}", value);
     Ok(())
@@ -1,11 +1,15 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::nns_types::account_identifier::{AccountIdentifier, Subaccount};
+use crate::lib::nns_types::icpts::ICPTs;
 use crate::lib::operations::ledger;
+use crate::lib::output::print_output;
 use crate::lib::root_key::fetch_root_key_if_needed;
 use anyhow::anyhow;
 use candid::Principal;
 use clap::Parser;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
 /// Prints the account balance of the user
@@ -23,6 +27,22 @@ pub struct BalanceOpts {
     ledger_canister_id: Option<Principal>,
 }
 
+/// The result of `dfx ledger balance`.
+#[derive(Serialize)]
+struct LedgerBalanceOutput {
+    account_id: String,
+    /// The balance in e8s (10^-8 ICP), as a string of decimal digits.
+    e8s: String,
+    #[serde(skip)]
+    balance: ICPTs,
+}
+
+impl Display for LedgerBalanceOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.balance)
+    }
+}
+
 pub async fn exec(env: &dyn Environment, opts: BalanceOpts) -> DfxResult {
     fetch_root_key_if_needed(env).await?;
     let sender = env
@@ -40,7 +60,12 @@ pub async fn exec(env: &dyn Environment, opts: BalanceOpts) -> DfxResult {
 
     let balance = ledger::balance(agent, &acc_id, opts.ledger_canister_id).await?;
 
-    println!("{balance}");
-
-    Ok(())
+    print_output(
+        env,
+        &LedgerBalanceOutput {
+            account_id: acc_id.to_hex(),
+            e8s: balance.get_e8s().to_string(),
+            balance,
+        },
+    )
 }
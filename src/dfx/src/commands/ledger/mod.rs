@@ -14,7 +14,7 @@ pub mod create_canister;
 mod fabricate_cycles;
 mod notify;
 pub mod show_subnet_types;
-mod top_up;
+pub mod top_up;
 mod transfer;
 
 /// Ledger commands.
@@ -40,6 +40,12 @@ enum SubCommand {
     Transfer(transfer::TransferOpts),
 }
 
+impl LedgerOpts {
+    pub fn supports_json(&self) -> bool {
+        matches!(self.subcmd, SubCommand::Balance(_))
+    }
+}
+
 pub fn exec(env: &dyn Environment, opts: LedgerOpts) -> DfxResult {
     let agent_env = create_agent_environment(env, opts.network.to_network_name())?;
     let runtime = Runtime::new().expect("Unable to create a runtime");
@@ -67,6 +67,20 @@ pub enum DfxCommand {
     Wallet(wallet::WalletOpts),
 }
 
+impl DfxCommand {
+    /// Whether the command can print its result as JSON, with the global `--json` flag.
+    pub fn supports_json(&self) -> bool {
+        match self {
+            DfxCommand::Canister(v) => v.supports_json(),
+            DfxCommand::Cycles(v) => v.supports_json(),
+            DfxCommand::Deploy(v) => v.supports_json(),
+            DfxCommand::Identity(v) => v.supports_json(),
+            DfxCommand::Ledger(v) => v.supports_json(),
+            _ => false,
+        }
+    }
+}
+
 pub fn exec(env: &dyn Environment, cmd: DfxCommand) -> DfxResult {
     match cmd {
         DfxCommand::Beta(v) => beta::exec(env, v),
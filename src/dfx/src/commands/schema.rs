@@ -1,7 +1,9 @@
 use crate::lib::{error::DfxResult, metadata::dfx::DfxMetadata};
 use anyhow::Context;
 use clap::{Parser, ValueEnum};
-use dfx_core::config::model::dfinity::{ConfigInterface, TopLevelConfigNetworks};
+use dfx_core::config::model::dfinity::{
+    ConfigInterface, IncludedCanistersConfig, TopLevelConfigNetworks,
+};
 use dfx_core::extension::manifest::{ExtensionDependencies, ExtensionManifest};
 use schemars::schema_for;
 use std::path::PathBuf;
@@ -13,6 +15,8 @@ enum ForFile {
     DfxMetadata,
     ExtensionDependencies,
     ExtensionManifest,
+    AssetsConfig,
+    IncludedCanisters,
 }
 
 /// Prints the schema for dfx.json.
@@ -32,6 +36,8 @@ pub fn exec(opts: SchemaOpts) -> DfxResult {
         Some(ForFile::DfxMetadata) => schema_for!(DfxMetadata),
         Some(ForFile::ExtensionDependencies) => schema_for!(ExtensionDependencies),
         Some(ForFile::ExtensionManifest) => schema_for!(ExtensionManifest),
+        Some(ForFile::AssetsConfig) => ic_asset::asset_config_schema(),
+        Some(ForFile::IncludedCanisters) => schema_for!(IncludedCanistersConfig),
         _ => schema_for!(ConfigInterface),
     };
     let nice_schema =
@@ -14,9 +14,11 @@ use console::style;
 use dfx_core::config::cache::Cache;
 use dfx_core::config::model::network_descriptor::NetworkDescriptor;
 use fn_error_context::context;
+use ic_asset::error::AssetLoadConfigError;
+use ic_asset::AssetSourceDirectoryConfiguration;
 use slog::{o, Logger};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// Set of extras that can be specified in the dfx.json.
@@ -127,14 +129,17 @@ impl CanisterBuilder for AssetsBuilder {
         build_frontend(
             pool.get_logger(),
             info.get_workspace_root(),
+            info.get_working_dir(),
             &config.network_name,
             vars,
             &build,
             workspace.as_deref(),
+            config.output_prefix(info.get_name()),
         )?;
 
         let assets_canister_info = info.as_info::<AssetsCanisterInfo>()?;
         assets_canister_info.assert_source_paths()?;
+        validate_asset_config(pool.get_logger(), &assets_canister_info.get_source_paths())?;
 
         Ok(())
     }
@@ -150,6 +155,32 @@ impl CanisterBuilder for AssetsBuilder {
     }
 }
 
+/// Loads the `.ic-assets.json` files of every source directory in strict mode,
+/// so that misspelled fields fail the build instead of being ignored.
+/// The unknown fields nested in rules, which the asset sync ignores, only produce a warning each.
+fn validate_asset_config(logger: &Logger, source_paths: &[PathBuf]) -> DfxResult {
+    for source_path in source_paths {
+        let source_path = dfx_core::fs::canonicalize(source_path)?;
+        let context = || format!("Invalid asset configuration in {}.", source_path.display());
+        match AssetSourceDirectoryConfiguration::load_strict(&source_path) {
+            Err(err @ AssetLoadConfigError::InvalidAssetConfigField(..)) => {
+                let config =
+                    AssetSourceDirectoryConfiguration::load(&source_path).with_context(context)?;
+                if config.get_ignored_fields().is_empty() {
+                    slog::warn!(logger, "{}", err);
+                }
+                for field in config.get_ignored_fields() {
+                    slog::warn!(logger, "Invalid field in asset config file {}", field);
+                }
+            }
+            result => {
+                result.with_context(context)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 fn unpack_did(generate_output_dir: &Path) -> DfxResult<()> {
     let mut canister_assets =
         util::assets::assetstorage_canister().context("Failed to load asset canister archive.")?;
@@ -179,10 +210,12 @@ fn unpack_did(generate_output_dir: &Path) -> DfxResult<()> {
 fn build_frontend(
     logger: &slog::Logger,
     project_root: &Path,
+    working_dir: &Path,
     network_name: &str,
     vars: Vec<super::Env<'_>>,
     build: &[String],
     workspace: Option<&str>,
+    output_prefix: Option<&str>,
 ) -> DfxResult {
     let custom_build_frontend = !build.is_empty();
     let build_frontend = project_root.join("package.json").exists();
@@ -197,7 +230,7 @@ fn build_frontend(
                 command
             );
 
-            super::run_command(command, &vars, project_root)
+            super::run_command(command, &vars, working_dir, output_prefix)
                 .with_context(|| format!("Failed to run {}.", command))?;
         }
     } else if build_frontend {
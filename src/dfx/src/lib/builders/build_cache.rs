@@ -0,0 +1,218 @@
+//! Skipping the build of canisters whose inputs did not change since their last build.
+//!
+//! The inputs of a build are fingerprinted: the files it reads, the build settings from dfx.json,
+//! the environment variables of the build steps, the toolchain versions, the files of metadata sections
+//! and the interfaces of the canisters it depends on. The fingerprint of the last successful build is
+//! stored next to its outputs in `.dfx/<network>/canisters/<name>`.
+//! The post-build steps that do not change these outputs, like checking the Wasm size, generating
+//! the JavaScript bindings and building the frontend of asset canisters, run even if the build is skipped.
+use crate::config::dfx_version_str;
+use crate::lib::builders::{get_and_write_environment_variables, BuildConfig};
+use crate::lib::canister_info::CanisterInfo;
+use crate::lib::error::DfxResult;
+use crate::lib::models::canister::CanisterPool;
+use anyhow::bail;
+use candid::Principal as CanisterId;
+use dfx_core::config::model::canister_includes::{expand_include_pattern, is_glob_pattern};
+use fn_error_context::context;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const FINGERPRINT_FILE_NAME: &str = "build-fingerprint";
+
+/// The inputs of a build that the builder of a canister knows about.
+/// The files declared in the `build_inputs` field of dfx.json are added to these.
+#[derive(Debug, Default)]
+pub struct BuildInputs {
+    /// Files that the build reads.
+    pub files: Vec<PathBuf>,
+    /// Other values that the build output depends on, like toolchain versions.
+    pub values: Vec<String>,
+}
+
+impl BuildInputs {
+    /// Adds all files below `dir`, leaving out hidden directories, `node_modules` and the directories in `skip`.
+    #[context("Failed to list the files in {}.", dir.display())]
+    pub fn add_dir(&mut self, dir: &Path, skip: &[&Path]) -> DfxResult {
+        let walk = WalkDir::new(dir).into_iter().filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || !(entry.file_type().is_dir()
+                    && (name.starts_with('.')
+                        || name == "node_modules"
+                        || skip.contains(&entry.path())))
+        });
+        for entry in walk {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                self.files.push(entry.into_path());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Computes the fingerprint of the inputs of a build.
+/// Returns None if the inputs are unknown, because neither the builder nor dfx.json declare any files.
+#[context("Failed to compute the build fingerprint of canister '{}'.", info.get_name())]
+pub fn fingerprint(
+    pool: &CanisterPool,
+    info: &CanisterInfo,
+    config: &BuildConfig,
+    inputs: Option<BuildInputs>,
+    dependencies: &[CanisterId],
+) -> DfxResult<Option<String>> {
+    let mut inputs = match inputs {
+        Some(inputs) => inputs,
+        None if info.get_build_inputs().is_empty() => return Ok(None),
+        None => BuildInputs::default(),
+    };
+    for pattern in info.get_build_inputs() {
+        inputs
+            .files
+            .extend(expand_build_input(info.get_workspace_root(), pattern)?);
+    }
+    inputs.files.sort();
+    inputs.files.dedup();
+
+    let mut hasher = Sha256::new();
+    let mut add = |value: &[u8]| {
+        // the length keeps adjacent values from running into each other
+        hasher.update((value.len() as u64).to_le_bytes());
+        hasher.update(value);
+    };
+    add(dfx_version_str().as_bytes());
+    add(config.network_name.as_bytes());
+    add(format!("{:?} {}", config.profile, config.instrument).as_bytes());
+    add(info.describe_build_settings().as_bytes());
+
+    let mut vars =
+        get_and_write_environment_variables(info, &config.network_name, pool, dependencies, None)?;
+    vars.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (name, value) in vars {
+        add(name.as_bytes());
+        add(value.as_encoded_bytes());
+    }
+
+    for dependency in dependencies {
+        let Some(canister) = pool.get_canister(dependency) else {
+            continue;
+        };
+        let interface = canister
+            .get_info()
+            .get_remote_candid_if_remote()
+            .unwrap_or_else(|| canister.get_info().get_service_idl_path());
+        add_file(&mut add, &interface)?;
+    }
+    for file in &inputs.files {
+        add_file(&mut add, file)?;
+    }
+    // metadata sections are attached to the Wasm module after the build
+    for section in info.metadata().sections.values() {
+        if let Some(path) = &section.path {
+            add_file(&mut add, &info.get_workspace_root().join(path))?;
+        }
+    }
+    for value in &inputs.values {
+        add(value.as_bytes());
+    }
+    Ok(Some(hex::encode(hasher.finalize())))
+}
+
+/// Whether the outputs of the last build of the canister were built from inputs with this fingerprint.
+pub fn is_up_to_date(info: &CanisterInfo, fingerprint: &str) -> bool {
+    let outputs = [
+        info.get_build_wasm_path(),
+        info.get_service_idl_path(),
+        info.get_constructor_idl_path(),
+    ];
+    outputs.iter().all(|path| path.exists())
+        && std::fs::read_to_string(info.get_output_root().join(FINGERPRINT_FILE_NAME))
+            .is_ok_and(|saved| saved == fingerprint)
+}
+
+/// Records the fingerprint of the inputs of a successful build.
+pub fn save_fingerprint(info: &CanisterInfo, fingerprint: &str) -> DfxResult {
+    dfx_core::fs::write(
+        info.get_output_root().join(FINGERPRINT_FILE_NAME),
+        fingerprint,
+    )?;
+    Ok(())
+}
+
+/// Forgets the fingerprint of the last build, so that the canister is built again even if its inputs do not change.
+pub fn clear_fingerprint(info: &CanisterInfo) -> DfxResult {
+    let path = info.get_output_root().join(FINGERPRINT_FILE_NAME);
+    if path.exists() {
+        dfx_core::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+fn add_file(add: &mut impl FnMut(&[u8]), path: &Path) -> DfxResult {
+    add(path.as_os_str().as_encoded_bytes());
+    if path.exists() {
+        add(&dfx_core::fs::read(path)?);
+    } else {
+        add(b"<missing>");
+    }
+    Ok(())
+}
+
+/// Returns the files matched by a path or glob pattern in the `build_inputs` field of dfx.json.
+/// A path of a directory matches all files in it.
+#[context("Failed to find the files of build input '{}'.", pattern)]
+fn expand_build_input(root: &Path, pattern: &str) -> DfxResult<Vec<PathBuf>> {
+    if is_glob_pattern(pattern) {
+        return Ok(expand_include_pattern(root, pattern)?);
+    }
+    let path = root.join(pattern);
+    if path.is_dir() {
+        Ok(expand_include_pattern(root, &format!("{pattern}/**"))?)
+    } else if path.is_file() {
+        Ok(vec![path])
+    } else {
+        bail!("Build input {} does not exist.", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_build_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for path in ["src/a.js", "src/b.ts", "src/nested/c.js", "package.json"] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        let relative = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(
+            relative(expand_build_input(root, "package.json").unwrap()),
+            ["package.json"]
+        );
+        assert_eq!(
+            relative(expand_build_input(root, "src").unwrap()),
+            ["src/a.js", "src/b.ts", "src/nested/c.js"]
+        );
+        assert_eq!(
+            relative(expand_build_input(root, "src/*.js").unwrap()),
+            ["src/a.js"]
+        );
+        assert_eq!(
+            relative(expand_build_input(root, "src/**/*.js").unwrap()),
+            ["src/a.js", "src/nested/c.js"]
+        );
+        assert!(expand_build_input(root, "missing.json").is_err());
+    }
+}
@@ -6,14 +6,16 @@ use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::models::canister::CanisterPool;
-use crate::util::download_file_to_path;
-use anyhow::{anyhow, Context};
+use crate::util::{download_file, download_file_to_path, write_to_tempfile_then_rename};
+use anyhow::{anyhow, bail, Context};
 use candid::Principal as CanisterId;
 use console::style;
+use dfx_core::config::cache::get_cache_root;
 use fn_error_context::context;
+use sha2::{Digest, Sha256};
 use slog::info;
 use slog::Logger;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use url::Url;
 
 /// Set of extras that can be specified in the dfx.json.
@@ -127,8 +129,13 @@ impl CanisterBuilder for CustomBuilder {
                 command
             );
 
-            super::run_command(&command, &vars, info.get_workspace_root())
-                .with_context(|| format!("Failed to run {}.", command))?;
+            super::run_command(
+                &command,
+                &vars,
+                info.get_working_dir(),
+                config.output_prefix(info.get_name()),
+            )
+            .with_context(|| format!("Failed to run {}.", command))?;
         }
 
         Ok(BuildOutput {
@@ -159,13 +166,53 @@ pub async fn custom_download(info: &CanisterInfo, pool: &CanisterPool) -> DfxRes
         build: _,
         dependencies: _,
     } = CustomBuilderExtra::try_from(info, pool)?;
+    let custom_info = info.as_info::<CustomCanisterInfo>()?;
 
     if let Some(url) = input_wasm_url {
-        download_file_to_path(&url, &wasm).await?;
+        download_with_hash(&url, custom_info.get_wasm_hash(), &wasm).await?;
     }
     if let Some(url) = input_candid_url {
-        download_file_to_path(&url, &candid).await?;
+        download_with_hash(&url, custom_info.get_candid_hash(), &candid).await?;
     }
 
     Ok(())
 }
+
+/// Downloads a file. If its hash is known, the download is checked against it,
+/// and cached in the dfx cache so that the file is only downloaded once.
+#[context("Failed to download {} to {}.", url, to.display())]
+async fn download_with_hash(url: &Url, hash: Option<&str>, to: &Path) -> DfxResult {
+    let Some(hash) = hash else {
+        return download_file_to_path(url, to).await;
+    };
+    let expected =
+        hex::decode(hash).with_context(|| format!("Failed to decode {hash} as sha256 hash."))?;
+    let cached_path = get_cache_root()?
+        .join("downloads")
+        .join(hex::encode(&expected));
+    let cached = if cached_path.exists() {
+        Some(dfx_core::fs::read(&cached_path)?)
+            .filter(|content| Sha256::digest(content)[..] == expected[..])
+    } else {
+        None
+    };
+    let content = match cached {
+        Some(content) => content,
+        None => {
+            let content = download_file(url).await?;
+            let actual = Sha256::digest(&content);
+            if actual[..] != expected[..] {
+                bail!(
+                    "The hash of the download is {}, but {} was expected.",
+                    hex::encode(actual),
+                    hash
+                );
+            }
+            write_to_tempfile_then_rename(&content, &cached_path)?;
+            content
+        }
+    };
+    dfx_core::fs::composite::ensure_parent_dir_exists(to)?;
+    dfx_core::fs::write(to, content)?;
+    Ok(())
+}
@@ -6,7 +6,7 @@ use crate::lib::models::canister::CanisterPool;
 use anyhow::{bail, Context};
 use candid::Principal as CanisterId;
 use candid_parser::utils::CandidSource;
-use dfx_core::config::model::dfinity::{Config, Profile};
+use dfx_core::config::model::dfinity::{Config, Profile, RustBindingsTarget};
 use dfx_core::network::provider::get_network_context;
 use dfx_core::util;
 use fn_error_context::context;
@@ -16,17 +16,19 @@ use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::fmt::Write;
 use std::fs;
-use std::io::Read;
+use std::io::{Read, Write as _};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 mod assets;
+pub mod build_cache;
 mod custom;
 mod motoko;
 mod pull;
 mod rust;
 
+pub use build_cache::BuildInputs;
 pub use custom::custom_download;
 
 #[derive(Debug)]
@@ -90,6 +92,18 @@ pub trait CanisterBuilder {
         Ok(())
     }
 
+    /// Returns the files and other inputs that the build of the canister depends on,
+    /// so that the build can be skipped if they did not change.
+    /// Returns None if the builder cannot tell, in which case only the `build_inputs` declared in dfx.json are used.
+    fn get_build_inputs(
+        &self,
+        _pool: &CanisterPool,
+        _info: &CanisterInfo,
+        _config: &BuildConfig,
+    ) -> DfxResult<Option<BuildInputs>> {
+        Ok(None)
+    }
+
     /// Generate type declarations for the canister
     fn generate(
         &self,
@@ -208,6 +222,34 @@ pub trait CanisterBuilder {
             eprintln!("  {}", &output_mo_path.display());
         }
 
+        // Rust
+        if bindings.contains(&"rs".to_string()) {
+            let output_rs_path = generate_output_dir
+                .join(info.get_name())
+                .with_extension("rs");
+            let mut rust_config = candid_parser::bindings::rust::Config::new();
+            let actor = match info.get_declarations_config().rust_target {
+                RustBindingsTarget::Agent => {
+                    rust_config.set_target(candid_parser::bindings::rust::Target::Agent);
+                    ty.clone()
+                }
+                RustBindingsTarget::Canister => {
+                    rust_config.set_target(candid_parser::bindings::rust::Target::CanisterCall);
+                    ty.clone()
+                }
+                RustBindingsTarget::Types => None,
+            };
+            let content = ensure_trailing_newline(candid_parser::bindings::rust::compile(
+                &rust_config,
+                &env,
+                &actor,
+            ));
+            std::fs::write(&output_rs_path, content).with_context(|| {
+                format!("Failed to write to {}.", output_rs_path.to_string_lossy())
+            })?;
+            eprintln!("  {}", &output_rs_path.display());
+        }
+
         // Candid
         if bindings.contains(&"did".to_string()) {
             let output_did_path = generate_output_dir
@@ -331,6 +373,7 @@ pub fn execute_command(
     vars: &[Env<'_>],
     cwd: &Path,
     catch_output: bool,
+    output_prefix: Option<&str>,
 ) -> DfxResult<Vec<u8>> {
     // No commands, noop.
     if command.is_empty() {
@@ -350,7 +393,7 @@ pub fn execute_command(
         sh_cmd
     };
 
-    if !catch_output {
+    if !catch_output && output_prefix.is_none() {
         cmd.stdin(Stdio::inherit())
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit());
@@ -361,6 +404,12 @@ pub fn execute_command(
     let output = cmd
         .output()
         .with_context(|| format!("Error executing custom build step {cmd:#?}"))?;
+    if let Some(prefix) = output_prefix {
+        if !catch_output {
+            print_prefixed_output(prefix, &output.stdout);
+        }
+        print_prefixed_output(prefix, &output.stderr);
+    }
     if output.status.success() {
         Ok(output.stdout)
     } else {
@@ -370,13 +419,31 @@ pub fn execute_command(
     }
 }
 
-pub fn run_command(command: &str, vars: &[Env<'_>], cwd: &Path) -> DfxResult<()> {
-    execute_command(command, vars, cwd, false)?;
+pub fn run_command(
+    command: &str,
+    vars: &[Env<'_>],
+    cwd: &Path,
+    output_prefix: Option<&str>,
+) -> DfxResult<()> {
+    execute_command(command, vars, cwd, false, output_prefix)?;
     Ok(())
 }
 
 pub fn command_output(command: &str, vars: &[Env<'_>], cwd: &Path) -> DfxResult<Vec<u8>> {
-    execute_command(command, vars, cwd, true)
+    execute_command(command, vars, cwd, true, None)
+}
+
+/// Prints captured output of a build step to stderr, with every line prefixed by `[<prefix>]`.
+pub fn print_prefixed_output(prefix: &str, output: &[u8]) {
+    if output.is_empty() {
+        return;
+    }
+    let mut prefixed = String::new();
+    for line in String::from_utf8_lossy(output).lines() {
+        writeln!(prefixed, "[{prefix}] {line}").unwrap();
+    }
+    // a single write keeps the lines of one step together
+    let _ = std::io::stderr().lock().write_all(prefixed.as_bytes());
 }
 
 type Env<'a> = (Cow<'static, str>, Cow<'a, OsStr>);
@@ -443,7 +510,11 @@ pub fn get_and_write_environment_variables<'a>(
     Ok(vars)
 }
 
+/// Serializes updates of the env file by canisters that are built in parallel.
+static ENV_FILE_LOCK: Mutex<()> = Mutex::new(());
+
 fn write_environment_variables(vars: &[Env<'_>], write_path: &Path) -> DfxResult {
+    let _guard = ENV_FILE_LOCK.lock().unwrap();
     const START_TAG: &str = "\n# DFX CANISTER ENVIRONMENT VARIABLES";
     const END_TAG: &str = "\n# END DFX CANISTER ENVIRONMENT VARIABLES";
     let mut write_string = String::from(START_TAG);
@@ -498,6 +569,14 @@ pub struct BuildConfig {
     pub canisters_to_build: Option<Vec<String>>,
     /// If environment variables should be output to a `.env` file, `env_file` is set to its path.
     pub env_file: Option<PathBuf>,
+    /// The maximum number of canisters that are built at the same time.
+    pub jobs: usize,
+    /// If true, canisters are built even if their build inputs did not change since the last build.
+    pub force_rebuild: bool,
+    /// If true, the Wasm of all canisters is instrumented for `dfx canister profile`.
+    pub instrument: bool,
+    /// If true, a breakdown of the size of the Wasm of each canister is printed after it is built.
+    pub size_report: bool,
 }
 
 impl BuildConfig {
@@ -518,6 +597,10 @@ impl BuildConfig {
             lsp_root: network_root.join("lsp/"),
             canisters_to_build: None,
             env_file: config.get_output_env_file(None)?,
+            jobs: 1,
+            force_rebuild: false,
+            instrument: false,
+            size_report: false,
         })
     }
 
@@ -538,6 +621,34 @@ impl BuildConfig {
     pub fn with_env_file(self, env_file: Option<PathBuf>) -> Self {
         Self { env_file, ..self }
     }
+
+    pub fn with_jobs(self, jobs: usize) -> Self {
+        Self { jobs, ..self }
+    }
+
+    pub fn with_force_rebuild(self, force_rebuild: bool) -> Self {
+        Self {
+            force_rebuild,
+            ..self
+        }
+    }
+
+    pub fn with_instrument(self, instrument: bool) -> Self {
+        Self { instrument, ..self }
+    }
+
+    pub fn with_size_report(self, size_report: bool) -> Self {
+        Self {
+            size_report,
+            ..self
+        }
+    }
+
+    /// When canisters are built in parallel, returns the prefix for output lines of build steps of the canister.
+    /// Their output is then captured and printed at once, so that the output of different canisters does not interleave.
+    pub fn output_prefix<'a>(&self, canister_name: &'a str) -> Option<&'a str> {
+        (self.jobs > 1).then_some(canister_name)
+    }
 }
 
 pub struct BuilderPool {
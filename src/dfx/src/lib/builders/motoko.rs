@@ -1,5 +1,5 @@
 use crate::lib::builders::{
-    BuildConfig, BuildOutput, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
+    BuildConfig, BuildInputs, BuildOutput, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
 };
 use crate::lib::canister_info::motoko::MotokoCanisterInfo;
 use crate::lib::canister_info::CanisterInfo;
@@ -14,17 +14,21 @@ use candid::Principal as CanisterId;
 use dfx_core::config::cache::Cache;
 use dfx_core::config::model::dfinity::{MetadataVisibility, Profile};
 use fn_error_context::context;
+use lazy_static::lazy_static;
+use regex::Regex;
 use slog::{info, o, trace, warn, Logger};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 use std::process::Output;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 pub struct MotokoBuilder {
     logger: slog::Logger,
     cache: Arc<dyn Cache>,
+    /// The direct imports of each Motoko file, so that `moc --print-deps` runs once per file.
+    imports: Mutex<HashMap<PathBuf, Vec<MotokoImport>>>,
 }
 unsafe impl Send for MotokoBuilder {}
 unsafe impl Sync for MotokoBuilder {}
@@ -37,50 +41,108 @@ impl MotokoBuilder {
                 "module" => "motoko"
             }),
             cache: env.get_cache(),
+            imports: Mutex::new(HashMap::new()),
         })
     }
-}
 
-#[context("Failed to find imports for canister at '{}'.", info.get_main_path().display())]
-fn get_imports(cache: &dyn Cache, info: &MotokoCanisterInfo) -> DfxResult<BTreeSet<MotokoImport>> {
-    #[context("Failed recursive dependency detection at {}.", file.display())]
-    fn get_imports_recursive(
-        cache: &dyn Cache,
-        file: &Path,
-        result: &mut BTreeSet<MotokoImport>,
-    ) -> DfxResult {
-        if result.contains(&MotokoImport::Relative(file.to_path_buf())) {
-            return Ok(());
+    #[context("Failed to find imports for canister at '{}'.", info.get_main_path().display())]
+    fn get_imports(&self, info: &MotokoCanisterInfo) -> DfxResult<BTreeSet<MotokoImport>> {
+        let mut result = BTreeSet::new();
+        let mut pending = vec![info.get_main_path().to_path_buf()];
+        while let Some(file) = pending.pop() {
+            if !result.insert(MotokoImport::Relative(file.clone())) {
+                continue;
+            }
+            for import in self.print_deps(&file)? {
+                match import {
+                    MotokoImport::Relative(path) => pending.push(path),
+                    _ => {
+                        result.insert(import);
+                    }
+                }
+            }
         }
+        Ok(result)
+    }
 
-        result.insert(MotokoImport::Relative(file.to_path_buf()));
+    /// Returns the direct imports of a Motoko file.
+    #[context("Failed to find the imports of {}.", file.display())]
+    fn print_deps(&self, file: &Path) -> DfxResult<Vec<MotokoImport>> {
+        if let Some(imports) = self.imports.lock().unwrap().get(file) {
+            return Ok(imports.clone());
+        }
 
-        let mut command = cache.get_binary_command("moc")?;
+        let mut command = self.cache.get_binary_command("moc")?;
         let command = command.arg("--print-deps").arg(file);
         let output = command
             .output()
             .with_context(|| format!("Error executing {:#?}", command))?;
         let output = String::from_utf8_lossy(&output.stdout);
+        let imports = output
+            .lines()
+            .map(|line| MotokoImport::try_from(line).context("Failed to create MotokoImport."))
+            .collect::<DfxResult<Vec<_>>>()?;
+
+        self.imports
+            .lock()
+            .unwrap()
+            .insert(file.to_path_buf(), imports.clone());
+        Ok(imports)
+    }
 
-        for line in output.lines() {
-            let import = MotokoImport::try_from(line).context("Failed to create MotokoImport.")?;
-            match import {
-                MotokoImport::Relative(path) => {
-                    get_imports_recursive(cache, path.as_path(), result)?;
-                }
-                _ => {
-                    result.insert(import);
+    /// Returns the Motoko files that the canister is compiled from: the main file and the files it imports,
+    /// including the files of packages, recursively.
+    #[context("Failed to find source files for canister at '{}'.", info.get_main_path().display())]
+    fn get_source_files(
+        &self,
+        info: &MotokoCanisterInfo,
+        package_arguments: &PackageArguments,
+    ) -> DfxResult<BTreeSet<PathBuf>> {
+        let packages = package_dirs(package_arguments);
+        let mut files = BTreeSet::new();
+        let mut pending = vec![info.get_main_path().to_path_buf()];
+        while let Some(file) = pending.pop() {
+            if files.contains(&file) {
+                continue;
+            }
+            for import in self.print_deps(&file)? {
+                match import {
+                    MotokoImport::Relative(path) => pending.push(path),
+                    MotokoImport::Lib(name) => pending.extend(resolve_lib_import(&packages, &name)),
+                    MotokoImport::Canister(_) | MotokoImport::Ic(_) => {}
                 }
             }
+            files.insert(file);
         }
-
-        Ok(())
+        Ok(files)
     }
+}
 
-    let mut result = BTreeSet::new();
-    get_imports_recursive(cache, info.get_main_path(), &mut result)?;
+/// Returns the directories of the packages passed to moc with `--package <name> <dir>`.
+fn package_dirs(package_arguments: &PackageArguments) -> BTreeMap<&str, &Path> {
+    let mut packages = BTreeMap::new();
+    let mut args = package_arguments.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--package" {
+            if let (Some(name), Some(dir)) = (args.next(), args.next()) {
+                packages.insert(name.as_str(), Path::new(dir.as_str()));
+            }
+        }
+    }
+    packages
+}
 
-    Ok(result)
+/// Returns the file that an import like `mo:base/List` refers to.
+/// `mo:<package>` refers to `lib.mo` in the package, and so does a path to a directory in it.
+fn resolve_lib_import(packages: &BTreeMap<&str, &Path>, name: &str) -> Option<PathBuf> {
+    let (package, path) = name.split_once('/').unwrap_or((name, ""));
+    let dir = packages.get(package)?;
+    [
+        dir.join(format!("{path}.mo")),
+        dir.join(path).join("lib.mo"),
+    ]
+    .into_iter()
+    .find(|file| file.is_file())
 }
 
 impl CanisterBuilder for MotokoBuilder {
@@ -91,7 +153,7 @@ impl CanisterBuilder for MotokoBuilder {
         info: &CanisterInfo,
     ) -> DfxResult<Vec<CanisterId>> {
         let motoko_info = info.as_info::<MotokoCanisterInfo>()?;
-        let imports = get_imports(self.cache.as_ref(), &motoko_info)?;
+        let imports = self.get_imports(&motoko_info)?;
 
         Ok(imports
             .iter()
@@ -106,6 +168,32 @@ impl CanisterBuilder for MotokoBuilder {
             .collect())
     }
 
+    #[context("Failed to get build inputs for canister '{}'.", info.get_name())]
+    fn get_build_inputs(
+        &self,
+        _pool: &CanisterPool,
+        info: &CanisterInfo,
+        _config: &BuildConfig,
+    ) -> DfxResult<Option<BuildInputs>> {
+        let motoko_info = info.as_info::<MotokoCanisterInfo>()?;
+        let package_arguments =
+            package_arguments::load(self.cache.as_ref(), motoko_info.get_packtool())?;
+        let files = self
+            .get_source_files(&motoko_info, &package_arguments)?
+            .into_iter()
+            .collect();
+
+        // moc can be replaced with DFX_MOC_PATH
+        let mut moc = self.cache.get_binary_command("moc")?;
+        let version = moc
+            .arg("--version")
+            .output()
+            .context("Failed to run 'moc --version'.")?;
+        let mut values = package_arguments;
+        values.push(String::from_utf8_lossy(&version.stdout).into_owned());
+        Ok(Some(BuildInputs { files, values }))
+    }
+
     #[context("Failed to build Motoko canister '{}'.", canister_info.get_name())]
     fn build(
         &self,
@@ -136,7 +224,8 @@ impl CanisterBuilder for MotokoBuilder {
             .with_context(|| format!("Failed to create {}.", idl_dir_path.to_string_lossy()))?;
 
         // If the management canister is being imported, emit the candid file.
-        if get_imports(cache.as_ref(), &motoko_info)?
+        if self
+            .get_imports(&motoko_info)?
             .contains(&MotokoImport::Ic("aaaaa-aa".to_string()))
         {
             let management_idl_path = idl_dir_path.join("aaaaa-aa.did");
@@ -173,6 +262,7 @@ impl CanisterBuilder for MotokoBuilder {
             },
             suppress_warning: false,
             input: input_path,
+            compiler_options: motoko_info.get_compiler_options(),
             package_arguments: &moc_arguments,
             candid_service_metadata_visibility,
             candid_args_metadata_visibility,
@@ -214,6 +304,7 @@ struct MotokoParams<'a> {
     build_target: BuildTarget,
     idl_path: &'a Path,
     idl_map: &'a CanisterIdMap,
+    compiler_options: &'a [String],
     package_arguments: &'a PackageArguments,
     candid_service_metadata_visibility: MetadataVisibility,
     candid_args_metadata_visibility: MetadataVisibility,
@@ -246,6 +337,7 @@ impl MotokoParams<'_> {
                 cmd.args(["--actor-alias", name, canister_id]);
             }
         };
+        cmd.args(self.compiler_options);
         cmd.args(self.package_arguments);
     }
 }
@@ -259,7 +351,7 @@ fn motoko_compile(logger: &Logger, cache: &dyn Cache, params: &MotokoParams<'_>)
     Ok(())
 }
 
-#[derive(Debug, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialOrd, Ord, PartialEq, Eq)]
 enum MotokoImport {
     Canister(String),
     Ic(String),
@@ -349,8 +441,140 @@ fn run_command(
             info!(logger, "{}", String::from_utf8_lossy(&output.stdout));
         }
         if !suppress_warning && !output.stderr.is_empty() {
-            warn!(logger, "{}", String::from_utf8_lossy(&output.stderr));
+            let (diagnostics, other) = parse_diagnostics(&String::from_utf8_lossy(&output.stderr));
+            for diagnostic in diagnostics {
+                // the fields are also in the message, because the default log drain only prints the message
+                warn!(logger, "{} [{}] at {}:{}:{}: {}",
+                    diagnostic.severity,
+                    diagnostic.code,
+                    diagnostic.file,
+                    diagnostic.line,
+                    diagnostic.column,
+                    diagnostic.message;
+                    "file" => &diagnostic.file,
+                    "line" => diagnostic.line,
+                    "column" => diagnostic.column,
+                    "severity" => &diagnostic.severity,
+                    "code" => &diagnostic.code,
+                );
+            }
+            if !other.is_empty() {
+                warn!(logger, "{}", other);
+            }
         }
         Ok(output)
     }
 }
+
+lazy_static! {
+    // for example `src/main.mo:3.7-3.8: warning [M0194], unused identifier x`
+    static ref DIAGNOSTIC_HEADER: Regex =
+        Regex::new(r"^(.+):(\d+)\.(\d+)-\d+\.\d+: (\w+) \[(M\d+)\], ").unwrap();
+}
+
+/// A diagnostic that moc reported, such as a warning.
+#[derive(Debug, PartialEq, Eq)]
+struct MotokoDiagnostic {
+    file: String,
+    line: u32,
+    column: u32,
+    severity: String,
+    code: String,
+    /// The description of the diagnostic, which can span several lines.
+    message: String,
+}
+
+/// Splits the output of moc into diagnostics and the lines that precede the first diagnostic.
+fn parse_diagnostics(stderr: &str) -> (Vec<MotokoDiagnostic>, String) {
+    let mut diagnostics: Vec<MotokoDiagnostic> = vec![];
+    let mut other = String::new();
+    for line in stderr.lines() {
+        if let Some(captures) = DIAGNOSTIC_HEADER.captures(line) {
+            diagnostics.push(MotokoDiagnostic {
+                file: captures[1].to_string(),
+                line: captures[2].parse().unwrap_or_default(),
+                column: captures[3].parse().unwrap_or_default(),
+                severity: captures[4].to_string(),
+                code: captures[5].to_string(),
+                message: line[captures[0].len()..].to_string(),
+            });
+        } else if let Some(diagnostic) = diagnostics.last_mut() {
+            diagnostic.message.push('\n');
+            diagnostic.message.push_str(line);
+        } else {
+            if !other.is_empty() {
+                other.push('\n');
+            }
+            other.push_str(line);
+        }
+    }
+    (diagnostics, other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_diagnostics() {
+        let stderr = "\
+src/main.mo:3.27-3.98: warning [M0145], this pattern of type
+  Nat
+does not cover value
+  0 or 1 or _
+src/lib.mo:12.7-12.8: warning [M0194], unused identifier x (delete or rename to wildcard `_` or `_x`)
+";
+        let (diagnostics, other) = parse_diagnostics(stderr);
+        assert_eq!(other, "");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0],
+            MotokoDiagnostic {
+                file: "src/main.mo".to_string(),
+                line: 3,
+                column: 27,
+                severity: "warning".to_string(),
+                code: "M0145".to_string(),
+                message: "this pattern of type\n  Nat\ndoes not cover value\n  0 or 1 or _"
+                    .to_string(),
+            }
+        );
+        assert_eq!(diagnostics[1].code, "M0194");
+        assert_eq!(diagnostics[1].line, 12);
+        assert_eq!(
+            diagnostics[1].message,
+            "unused identifier x (delete or rename to wildcard `_` or `_x`)"
+        );
+    }
+
+    #[test]
+    fn resolves_lib_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base");
+        std::fs::create_dir_all(base.join("Nested")).unwrap();
+        for file in ["lib.mo", "List.mo", "Nested/lib.mo"] {
+            std::fs::write(base.join(file), "").unwrap();
+        }
+        let arguments = vec![
+            "--package".to_string(),
+            "base".to_string(),
+            base.to_string_lossy().into_owned(),
+        ];
+        let packages = package_dirs(&arguments);
+
+        assert_eq!(
+            resolve_lib_import(&packages, "base"),
+            Some(base.join("lib.mo"))
+        );
+        assert_eq!(
+            resolve_lib_import(&packages, "base/List"),
+            Some(base.join("List.mo"))
+        );
+        assert_eq!(
+            resolve_lib_import(&packages, "base/Nested"),
+            Some(base.join("Nested").join("lib.mo"))
+        );
+        assert_eq!(resolve_lib_import(&packages, "base/Missing"), None);
+        assert_eq!(resolve_lib_import(&packages, "other/List"), None);
+    }
+}
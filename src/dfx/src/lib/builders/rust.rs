@@ -1,5 +1,5 @@
 use crate::lib::builders::{
-    BuildConfig, BuildOutput, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
+    BuildConfig, BuildInputs, BuildOutput, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
 };
 use crate::lib::canister_info::rust::RustCanisterInfo;
 use crate::lib::canister_info::CanisterInfo;
@@ -9,13 +9,16 @@ use crate::lib::models::canister::CanisterPool;
 use anyhow::{anyhow, bail, Context};
 use candid::Principal as CanisterId;
 use fn_error_context::context;
-use slog::{info, o};
-use std::path::PathBuf;
+use slog::{info, o, Logger};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::process::Stdio;
+use std::sync::OnceLock;
 
 pub struct RustBuilder {
     logger: slog::Logger,
+    /// The output of `rustc --version --verbose`, which is the same for all canisters of the project.
+    rustc_version: OnceLock<String>,
 }
 
 impl RustBuilder {
@@ -25,8 +28,23 @@ impl RustBuilder {
             logger: env.get_logger().new(o! {
                 "module" => "rust"
             }),
+            rustc_version: OnceLock::new(),
         })
     }
+
+    fn get_rustc_version(&self, workspace_root: &Path) -> DfxResult<String> {
+        if let Some(version) = self.rustc_version.get() {
+            return Ok(version.clone());
+        }
+        // the toolchain can be pinned per project with rust-toolchain.toml
+        let rustc = Command::new("rustc")
+            .args(["--version", "--verbose"])
+            .current_dir(workspace_root)
+            .output()
+            .context("Failed to run 'rustc --version'.")?;
+        let version = String::from_utf8_lossy(&rustc.stdout).into_owned();
+        Ok(self.rustc_version.get_or_init(|| version).clone())
+    }
 }
 
 impl CanisterBuilder for RustBuilder {
@@ -50,6 +68,33 @@ impl CanisterBuilder for RustBuilder {
         Ok(dependencies)
     }
 
+    #[context("Failed to get build inputs for canister '{}'.", info.get_name())]
+    fn get_build_inputs(
+        &self,
+        _pool: &CanisterPool,
+        info: &CanisterInfo,
+        _config: &BuildConfig,
+    ) -> DfxResult<Option<BuildInputs>> {
+        let rust_info = info.as_info::<RustCanisterInfo>()?;
+        let mut inputs = BuildInputs::default();
+        for dir in rust_info.get_member_dirs() {
+            inputs.add_dir(dir, &[rust_info.get_target_dir()])?;
+        }
+        let workspace_root = rust_info.get_cargo_workspace_root();
+        inputs.files.push(workspace_root.join("Cargo.toml"));
+        inputs.files.push(workspace_root.join("Cargo.lock"));
+
+        inputs
+            .values
+            .push(self.get_rustc_version(info.get_workspace_root())?);
+        inputs.values.extend(rust_info.get_cargo_build_args());
+        inputs.values.extend(
+            ["RUSTFLAGS", "CARGO_ENCODED_RUSTFLAGS"]
+                .map(|var| std::env::var(var).unwrap_or_default()),
+        );
+        Ok(Some(inputs))
+    }
+
     #[context("Failed to build Rust canister '{}'.", canister_info.get_name())]
     fn build(
         &self,
@@ -62,17 +107,13 @@ impl CanisterBuilder for RustBuilder {
 
         let canister_id = canister_info.get_canister_id().unwrap();
 
+        let cargo_args = rust_info.get_cargo_build_args();
+        let output_prefix = config.output_prefix(canister_info.get_name());
         let mut cargo = Command::new("cargo");
-        cargo
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .arg("build")
-            .arg("--target")
-            .arg("wasm32-unknown-unknown")
-            .arg("--release")
-            .arg("-p")
-            .arg(package)
-            .arg("--locked");
+        cargo.args(&cargo_args);
+        if output_prefix.is_none() {
+            cargo.stdout(Stdio::inherit()).stderr(Stdio::inherit());
+        }
 
         let dependencies = self
             .get_dependencies(pool, canister_info)
@@ -88,17 +129,49 @@ impl CanisterBuilder for RustBuilder {
             cargo.env(key.as_ref(), val);
         }
 
-        info!(
-            self.logger,
-            "Executing: cargo build --target wasm32-unknown-unknown --release -p {} --locked",
-            package
-        );
+        info!(self.logger, "Executing: cargo {}", cargo_args.join(" "));
         let output = cargo.output().context("Failed to run 'cargo build'. You might need to run `cargo update` (or a similar command like `cargo vendor`) if you have updated `Cargo.toml`, because `dfx build` uses the --locked flag with Cargo.")?;
+        if let Some(prefix) = output_prefix {
+            super::print_prefixed_output(prefix, &output.stdout);
+            super::print_prefixed_output(prefix, &output.stderr);
+        }
 
         if !output.status.success() {
             bail!("Failed to compile the rust package: {}", package);
         }
 
+        if rust_info.needs_wasi2ic() {
+            let input = rust_info.get_cargo_wasm_path();
+            let output = rust_info.get_output_wasm_path();
+            info!(
+                self.logger,
+                "Executing: wasi2ic {} {}",
+                input.display(),
+                output.display()
+            );
+            let status = Command::new("wasi2ic")
+                .arg(input)
+                .arg(output)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .status()
+                .context("Failed to run 'wasi2ic'. It is needed for WASI targets and can be installed with `cargo install wasi2ic`.")?;
+            if !status.success() {
+                bail!(
+                    "Failed to convert the Wasm of rust package {} with wasi2ic",
+                    package
+                );
+            }
+        }
+
+        if rust_info.extracts_candid() {
+            extract_candid(
+                &self.logger,
+                rust_info.get_output_wasm_path(),
+                rust_info.get_output_idl_path(),
+            )?;
+        }
+
         Ok(BuildOutput {
             canister_id,
             wasm: WasmBuildOutput::File(rust_info.get_output_wasm_path().to_path_buf()),
@@ -117,3 +190,24 @@ impl CanisterBuilder for RustBuilder {
         Ok(output_idl_path.to_path_buf())
     }
 }
+
+/// Writes the Candid interface exported by `ic_cdk::export_candid!()` in the Wasm module to `idl_path`.
+#[context("Failed to extract the Candid interface from {}.", wasm_path.display())]
+fn extract_candid(logger: &Logger, wasm_path: &Path, idl_path: &Path) -> DfxResult {
+    info!(
+        logger,
+        "Executing: candid-extractor {}",
+        wasm_path.display()
+    );
+    let output = Command::new("candid-extractor")
+        .arg(wasm_path)
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Failed to run 'candid-extractor'. It is needed for Rust canisters without a `candid` field and can be installed with `cargo install candid-extractor`.")?;
+    if !output.status.success() {
+        bail!("candid-extractor failed. Make sure the canister calls `ic_cdk::export_candid!()`.");
+    }
+    dfx_core::fs::composite::ensure_parent_dir_exists(idl_path)?;
+    dfx_core::fs::write(idl_path, output.stdout)?;
+    Ok(())
+}
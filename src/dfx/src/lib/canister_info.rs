@@ -3,6 +3,7 @@ use crate::lib::error::DfxResult;
 use crate::lib::metadata::config::CanisterMetadataConfig;
 
 use anyhow::{anyhow, bail, Context};
+use byte_unit::Byte;
 use candid::Principal as CanisterId;
 use candid::Principal;
 use core::panic;
@@ -43,6 +44,7 @@ pub struct CanisterInfo {
     remote_candid: Option<PathBuf>, // always exists if the field is configured
 
     workspace_root: PathBuf,
+    working_dir: PathBuf, // where the build and post-install commands run
     output_root: PathBuf, // <project dir>/.dfx/<network>/canisters/<canister>
 
     canister_id: Option<CanisterId>,
@@ -61,8 +63,11 @@ pub struct CanisterInfo {
     pull_dependencies: Vec<(String, CanisterId)>,
     tech_stack: Option<TechStack>,
     gzip: bool,
+    instrument: bool,
+    max_wasm_size: Option<Byte>,
     init_arg: Option<String>,
     init_arg_file: Option<String>,
+    build_inputs: Vec<String>,
 }
 
 impl CanisterInfo {
@@ -131,6 +136,7 @@ impl CanisterInfo {
             bindings: declarations_config_pre
                 .bindings
                 .or_else(|| Some(vec!["js".to_string(), "ts".to_string(), "did".to_string()])),
+            rust_target: declarations_config_pre.rust_target,
             env_override: declarations_config_pre.env_override,
             node_compatibility: declarations_config_pre.node_compatibility,
         };
@@ -157,6 +163,7 @@ impl CanisterInfo {
             remote_id,
             remote_candid,
             workspace_root: workspace_root.to_path_buf(),
+            working_dir: config.get_canister_dir(name).to_path_buf(),
             output_root,
             canister_id,
             packtool: build_defaults.get_packtool(),
@@ -172,8 +179,11 @@ impl CanisterInfo {
             tech_stack: canister_config.tech_stack.clone(),
             pull_dependencies,
             gzip,
+            instrument: canister_config.instrument.unwrap_or(false),
+            max_wasm_size: canister_config.max_wasm_size,
             init_arg,
             init_arg_file,
+            build_inputs: canister_config.build_inputs.clone(),
         };
 
         Ok(canister_info)
@@ -204,6 +214,10 @@ impl CanisterInfo {
     pub fn get_workspace_root(&self) -> &Path {
         &self.workspace_root
     }
+
+    pub fn get_working_dir(&self) -> &Path {
+        &self.working_dir
+    }
     pub fn get_output_root(&self) -> &Path {
         &self.output_root
     }
@@ -234,6 +248,34 @@ impl CanisterInfo {
         &self.packtool
     }
 
+    /// The paths or glob patterns of the files the build reads, as declared in dfx.json.
+    pub fn get_build_inputs(&self) -> &[String] {
+        &self.build_inputs
+    }
+
+    /// A description of all settings from dfx.json that affect the build output.
+    pub fn describe_build_settings(&self) -> String {
+        // serialized instead of debug-formatted, because the tech stack is made of hash maps
+        let tech_stack = serde_json::to_value(&self.tech_stack)
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        format!(
+            "{:?} {:?} {:?} {:?} {:?} {:?} {:?} {} {:?} {:?} {:?} {:?}",
+            self.type_specific,
+            self.args,
+            self.packtool,
+            self.main,
+            self.shrink,
+            self.optimize,
+            self.metadata,
+            tech_stack,
+            self.gzip,
+            self.instrument,
+            self.max_wasm_size,
+            self.dependencies,
+        )
+    }
+
     pub fn get_post_install(&self) -> &[String] {
         &self.post_install
     }
@@ -375,6 +417,14 @@ impl CanisterInfo {
         self.gzip
     }
 
+    pub fn get_instrument(&self) -> bool {
+        self.instrument
+    }
+
+    pub fn get_max_wasm_size(&self) -> Option<Byte> {
+        self.max_wasm_size
+    }
+
     /// Get the init arg from the dfx.json configuration.
     ///
     /// If the `init_arg` field is defined, it will be returned.
@@ -16,6 +16,8 @@ pub struct CustomCanisterInfo {
     input_candid_url: Option<Url>,
     output_idl_path: PathBuf,
     build: Vec<String>,
+    wasm_hash: Option<String>,
+    candid_hash: Option<String>,
 }
 
 impl CustomCanisterInfo {
@@ -34,24 +36,33 @@ impl CustomCanisterInfo {
     pub fn get_build_tasks(&self) -> &[String] {
         &self.build
     }
+    pub fn get_wasm_hash(&self) -> Option<&str> {
+        self.wasm_hash.as_deref()
+    }
+    pub fn get_candid_hash(&self) -> Option<&str> {
+        self.candid_hash.as_deref()
+    }
 }
 
 impl CanisterInfoFactory for CustomCanisterInfo {
     fn create(info: &CanisterInfo) -> DfxResult<Self> {
         let workspace_root = info.get_workspace_root();
-        let (wasm, build, candid) = if let CanisterTypeProperties::Custom {
-            wasm,
-            build,
-            candid,
-        } = info.type_specific.clone()
-        {
-            (wasm, build.into_vec(), candid)
-        } else {
-            bail!(
-                "Attempted to construct a custom canister from a type:{} canister config",
-                info.type_specific.name()
-            )
-        };
+        let (wasm, build, candid, wasm_hash, candid_hash) =
+            if let CanisterTypeProperties::Custom {
+                wasm,
+                build,
+                candid,
+                wasm_hash,
+                candid_hash,
+            } = info.type_specific.clone()
+            {
+                (wasm, build.into_vec(), candid, wasm_hash, candid_hash)
+            } else {
+                bail!(
+                    "Attempted to construct a custom canister from a type:{} canister config",
+                    info.type_specific.name()
+                )
+            };
         let (input_wasm_url, output_wasm_path) = if let Ok(input_wasm_url) = Url::parse(&wasm) {
             if !build.is_empty() {
                 bail!(
@@ -89,12 +100,27 @@ impl CanisterInfoFactory for CustomCanisterInfo {
                 (None, workspace_root.join(candid))
             };
 
+        if wasm_hash.is_some() && input_wasm_url.is_none() {
+            bail!(
+                "Canister '{}' has a wasm_hash, but its wasm field is not a URL.",
+                info.name
+            );
+        }
+        if candid_hash.is_some() && input_candid_url.is_none() {
+            bail!(
+                "Canister '{}' has a candid_hash, but its candid field is not a URL.",
+                info.name
+            );
+        }
+
         Ok(Self {
             input_wasm_url,
             output_wasm_path,
             input_candid_url,
             output_idl_path,
             build,
+            wasm_hash,
+            candid_hash,
         })
     }
 }
@@ -1,7 +1,7 @@
 use crate::lib::canister_info::{CanisterInfo, CanisterInfoFactory};
 use crate::lib::error::DfxResult;
-use anyhow::{ensure, Context};
-use dfx_core::config::model::dfinity::CanisterTypeProperties;
+use anyhow::{bail, Context};
+use dfx_core::config::model::dfinity::{CanisterTypeProperties, MotokoWarningLevel};
 use std::path::{Path, PathBuf};
 
 pub struct MotokoCanisterInfo {
@@ -17,6 +17,7 @@ pub struct MotokoCanisterInfo {
 
     packtool: Option<String>,
     moc_args: Option<String>,
+    compiler_options: Vec<String>,
 }
 
 impl MotokoCanisterInfo {
@@ -50,17 +51,51 @@ impl MotokoCanisterInfo {
     pub fn get_args(&self) -> &Option<String> {
         &self.moc_args
     }
+    /// The arguments for `moc` that correspond to the compiler options in dfx.json.
+    pub fn get_compiler_options(&self) -> &[String] {
+        &self.compiler_options
+    }
 }
 
 impl CanisterInfoFactory for MotokoCanisterInfo {
     fn create(info: &CanisterInfo) -> DfxResult<MotokoCanisterInfo> {
         let workspace_root = info.get_workspace_root();
         let name = info.get_name();
-        ensure!(
-            matches!(info.type_specific, CanisterTypeProperties::Motoko { .. }),
-            "Attempted to construct a custom canister from a type:{} canister config",
-            info.type_specific.name()
-        );
+        let CanisterTypeProperties::Motoko {
+            incremental_gc,
+            stable_regions,
+            max_stable_pages,
+            warnings_as_errors,
+            warning_levels,
+        } = &info.type_specific
+        else {
+            bail!(
+                "Attempted to construct a custom canister from a type:{} canister config",
+                info.type_specific.name()
+            );
+        };
+        let mut compiler_options = vec![];
+        if *incremental_gc {
+            compiler_options.push("--incremental-gc".to_string());
+        }
+        if *stable_regions {
+            compiler_options.push("--stable-regions".to_string());
+        }
+        if let Some(pages) = max_stable_pages {
+            compiler_options.extend(["--max-stable-pages".to_string(), pages.to_string()]);
+        }
+        if *warnings_as_errors {
+            compiler_options.push("-Werror".to_string());
+        }
+        for (code, level) in warning_levels {
+            let flag = match level {
+                MotokoWarningLevel::Allow => "-A",
+                MotokoWarningLevel::Warn => "-W",
+                MotokoWarningLevel::Error => "-E",
+            };
+            compiler_options.extend([flag.to_string(), code.clone()]);
+        }
+
         let main_path = info
             .get_main_file()
             .context("`main` attribute is required on Motoko canisters in dfx.json")?;
@@ -88,6 +123,7 @@ impl CanisterInfoFactory for MotokoCanisterInfo {
             output_assets_root,
             packtool: info.get_packtool().clone(),
             moc_args: info.get_args().clone(),
+            compiler_options,
         })
     }
 }
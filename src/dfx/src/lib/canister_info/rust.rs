@@ -6,10 +6,23 @@ use dfx_core::config::model::dfinity::CanisterTypeProperties;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
+pub const DEFAULT_RUST_TARGET: &str = "wasm32-unknown-unknown";
+pub const DEFAULT_RUST_PROFILE: &str = "release";
+
 pub struct RustCanisterInfo {
     package: String,
+    features: Vec<String>,
+    no_default_features: bool,
+    profile: String,
+    target: String,
+    cargo_args: Vec<String>,
+    cargo_wasm_path: PathBuf,
     output_wasm_path: PathBuf,
     output_idl_path: PathBuf,
+    extract_candid: bool,
+    cargo_workspace_root: PathBuf,
+    member_dirs: Vec<PathBuf>,
+    target_dir: PathBuf,
 }
 
 impl RustCanisterInfo {
@@ -17,6 +30,43 @@ impl RustCanisterInfo {
         &self.package
     }
 
+    /// The arguments passed to `cargo build`.
+    pub fn get_cargo_build_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "build".to_string(),
+            "--target".to_string(),
+            self.target.clone(),
+        ];
+        if self.profile == DEFAULT_RUST_PROFILE {
+            args.push("--release".to_string());
+        } else {
+            args.extend(["--profile".to_string(), self.profile.clone()]);
+        }
+        args.extend([
+            "-p".to_string(),
+            self.package.clone(),
+            "--locked".to_string(),
+        ]);
+        if !self.features.is_empty() {
+            args.extend(["--features".to_string(), self.features.join(",")]);
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        args.extend(self.cargo_args.iter().cloned());
+        args
+    }
+
+    /// Whether the Wasm produced by cargo must be converted with `wasi2ic` before it can be installed.
+    pub fn needs_wasi2ic(&self) -> bool {
+        is_wasi_target(&self.target)
+    }
+
+    /// The Wasm produced by `cargo build`.
+    pub fn get_cargo_wasm_path(&self) -> &Path {
+        self.cargo_wasm_path.as_path()
+    }
+
     pub fn get_output_wasm_path(&self) -> &Path {
         self.output_wasm_path.as_path()
     }
@@ -24,6 +74,25 @@ impl RustCanisterInfo {
     pub fn get_output_idl_path(&self) -> &Path {
         self.output_idl_path.as_path()
     }
+
+    /// Whether the Candid interface is extracted from the built Wasm instead of read from a file in the project.
+    pub fn extracts_candid(&self) -> bool {
+        self.extract_candid
+    }
+
+    /// The root of the cargo workspace that contains the package.
+    pub fn get_cargo_workspace_root(&self) -> &Path {
+        self.cargo_workspace_root.as_path()
+    }
+
+    /// The directories of all packages in the cargo workspace, which the package may depend on.
+    pub fn get_member_dirs(&self) -> &[PathBuf] {
+        &self.member_dirs
+    }
+
+    pub fn get_target_dir(&self) -> &Path {
+        self.target_dir.as_path()
+    }
 }
 
 impl CanisterInfoFactory for RustCanisterInfo {
@@ -38,15 +107,23 @@ impl CanisterInfoFactory for RustCanisterInfo {
             bail!("`cargo metadata` was unsuccessful");
         }
 
-        let (package, candid) =
-            if let CanisterTypeProperties::Rust { package, candid } = info.type_specific.clone() {
-                (package, candid)
-            } else {
-                bail!(
-                    "Attempted to construct a custom canister from a type:{} canister config",
-                    info.type_specific.name()
-                );
-            };
+        let CanisterTypeProperties::Rust {
+            package,
+            candid,
+            features,
+            no_default_features,
+            profile,
+            target,
+            cargo_args,
+        } = info.type_specific.clone()
+        else {
+            bail!(
+                "Attempted to construct a custom canister from a type:{} canister config",
+                info.type_specific.name()
+            );
+        };
+        let profile = profile.unwrap_or_else(|| DEFAULT_RUST_PROFILE.to_string());
+        let target_triple = target.unwrap_or_else(|| DEFAULT_RUST_TARGET.to_string());
         let metadata: Metadata = serde_json::from_slice(&metadata.stdout)
             .context("Failed to read metadata from `cargo metadata`")?;
         let package_info = metadata
@@ -81,22 +158,57 @@ impl CanisterInfoFactory for RustCanisterInfo {
         };
 
         let wasm_name = target.name.replace('-', "_");
+        let member_dirs = metadata
+            .packages
+            .iter()
+            .filter_map(|p| p.manifest_path.parent())
+            .map(|dir| dir.as_std_path().to_path_buf())
+            .collect();
         let workspace_root = info.get_workspace_root();
-        let output_wasm_path = metadata
+        // cargo places the dev and test profiles in `debug`, and the bench profile in `release`
+        let profile_dir = match profile.as_str() {
+            "dev" | "test" => "debug",
+            "bench" => "release",
+            other => other,
+        };
+        let cargo_wasm_path: PathBuf = metadata
             .target_directory
-            .join(format!("wasm32-unknown-unknown/release/{wasm_name}.wasm"))
+            .join(&target_triple)
+            .join(profile_dir)
+            .join(format!("{wasm_name}.wasm"))
             .into();
-        let candid = if let Some(remote_candid) = info.get_remote_candid_if_remote() {
-            remote_candid
+        let output_wasm_path = if is_wasi_target(&target_triple) {
+            cargo_wasm_path.with_file_name(format!("{wasm_name}_ic.wasm"))
         } else {
-            candid
+            cargo_wasm_path.clone()
+        };
+        let candid = info.get_remote_candid_if_remote().or(candid);
+        let extract_candid = candid.is_none();
+        let output_idl_path = match candid {
+            Some(candid) => workspace_root.join(candid),
+            None => info
+                .get_output_root()
+                .join(format!("{}.did", info.get_name())),
         };
-        let output_idl_path = workspace_root.join(candid);
 
         Ok(Self {
             package,
+            features,
+            no_default_features,
+            profile,
+            target: target_triple,
+            cargo_args,
+            cargo_wasm_path,
             output_wasm_path,
             output_idl_path,
+            extract_candid,
+            cargo_workspace_root: metadata.workspace_root.clone().into(),
+            member_dirs,
+            target_dir: metadata.target_directory.clone().into(),
         })
     }
 }
+
+fn is_wasi_target(target: &str) -> bool {
+    target.starts_with("wasm32-wasi")
+}
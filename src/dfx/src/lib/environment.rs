@@ -2,6 +2,7 @@ use crate::actors::pocketic::POCKETIC_EFFECTIVE_CANISTER_ID;
 use crate::config::cache::DiskBasedCache;
 use crate::config::dfx_version;
 use crate::lib::error::DfxResult;
+use crate::lib::output::OutputFormat;
 use crate::lib::progress_bar::ProgressBar;
 use crate::lib::warning::{is_warning_disabled, DfxWarning::MainnetPlainTextIdentity};
 use anyhow::anyhow;
@@ -50,6 +51,7 @@ pub trait Environment {
 
     fn get_logger(&self) -> &slog::Logger;
     fn get_verbose_level(&self) -> i64;
+    fn get_output_format(&self) -> OutputFormat;
     fn new_spinner(&self, message: Cow<'static, str>) -> ProgressBar;
     fn new_progress(&self, message: &str) -> ProgressBar;
 
@@ -102,6 +104,7 @@ pub struct EnvironmentImpl {
 
     logger: Option<slog::Logger>,
     verbose_level: i64,
+    output_format: OutputFormat,
 
     identity_override: Option<String>,
 
@@ -122,6 +125,7 @@ impl EnvironmentImpl {
             version: version.clone(),
             logger: None,
             verbose_level: 0,
+            output_format: OutputFormat::default(),
             identity_override: None,
             effective_canister_id: None,
             extension_manager,
@@ -143,6 +147,11 @@ impl EnvironmentImpl {
         self
     }
 
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
     pub fn with_effective_canister_id(mut self, effective_canister_id: Option<String>) -> Self {
         match effective_canister_id {
             None => {
@@ -230,6 +239,10 @@ impl Environment for EnvironmentImpl {
         self.verbose_level
     }
 
+    fn get_output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
     fn new_spinner(&self, message: Cow<'static, str>) -> ProgressBar {
         // Only show the progress bar if the level is INFO or more.
         if self.verbose_level >= 0 {
@@ -271,6 +284,8 @@ pub struct AgentEnvironment<'a> {
     network_descriptor: NetworkDescriptor,
     identity_manager: IdentityManager,
     effective_canister_id: Option<Principal>,
+    /// The project configuration with canister overrides applied for this network.
+    network_config: RefCell<Option<Arc<Config>>>,
 }
 
 impl<'a> AgentEnvironment<'a> {
@@ -310,6 +325,7 @@ impl<'a> AgentEnvironment<'a> {
             network_descriptor: network_descriptor.clone(),
             identity_manager,
             effective_canister_id,
+            network_config: RefCell::new(None),
         })
     }
 }
@@ -320,7 +336,13 @@ impl<'a> Environment for AgentEnvironment<'a> {
     }
 
     fn get_config(&self) -> Result<Option<Arc<Config>>, LoadDfxConfigError> {
-        self.backend.get_config()
+        let Some(config) = self.backend.get_config()? else {
+            return Ok(None);
+        };
+        let mut network_config = self.network_config.borrow_mut();
+        let network_config = network_config
+            .get_or_insert_with(|| Arc::new(config.for_network(&self.network_descriptor.name)));
+        Ok(Some(Arc::clone(network_config)))
     }
 
     fn get_networks_config(&self) -> Arc<NetworksConfig> {
@@ -361,6 +383,10 @@ impl<'a> Environment for AgentEnvironment<'a> {
         self.backend.get_verbose_level()
     }
 
+    fn get_output_format(&self) -> OutputFormat {
+        self.backend.get_output_format()
+    }
+
     fn new_spinner(&self, message: Cow<'static, str>) -> ProgressBar {
         self.backend.new_spinner(message)
     }
@@ -11,6 +11,12 @@ pub enum ProjectError {
     #[error("Can't convert string '{0}' to path")]
     ConvertingStringToPathFailed(String, #[source] std::convert::Infallible),
 
+    #[error("Can't convert path '{}' to a URL", .0.display())]
+    ConvertingPathToUrlFailed(std::path::PathBuf),
+
+    #[error("Can't convert URL '{0}' to a file path")]
+    ConvertingUrlToPathFailed(url::Url),
+
     #[error("Tried joining '{0}' and '{1}', but they form an invalid URL")]
     InvalidUrl(url::Url, String, #[source] url::ParseError),
 
@@ -46,4 +52,13 @@ pub enum ProjectError {
 
     #[error("Malformed network mapping '{0}': {1} network name is empty")]
     MalformedNetworkMapping(String, String),
+
+    #[error(transparent)]
+    IncludeCanisters(#[from] dfx_core::error::config::IncludeCanistersError),
+
+    #[error("Cannot expand include pattern '{1}' of '{0}': glob patterns are only supported for local files")]
+    CannotExpandRemoteIncludePattern(url::Url, String),
+
+    #[error("Canister '{0}' is defined more than once in '{1}' and its included files")]
+    DuplicateCanisterDefinition(String, url::Url),
 }
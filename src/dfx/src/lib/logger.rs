@@ -1,4 +1,5 @@
 use crate::config::dfx_version_str;
+use dfx_core::config::model::env_interpolation::redact_secrets;
 use slog::{Drain, Level, Logger};
 use std::fs::File;
 use std::path::PathBuf;
@@ -60,6 +61,31 @@ impl<D: slog_term::Decorator> slog::Drain for DfxFormat<D> {
     }
 }
 
+/// A Slog drain that redacts values substituted from environment variables in dfx.json.
+struct RedactSecrets<D: Drain>(D);
+
+impl<D: Drain> Drain for RedactSecrets<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn log(
+        &self,
+        record: &slog::Record<'_>,
+        values: &slog::OwnedKVList,
+    ) -> Result<Self::Ok, Self::Err> {
+        let msg = redact_secrets(&record.msg().to_string());
+        let record_static = slog::RecordStatic {
+            location: record.location(),
+            tag: record.tag(),
+            level: record.level(),
+        };
+        self.0.log(
+            &slog::Record::new(&record_static, &format_args!("{}", msg), record.kv()),
+            values,
+        )
+    }
+}
+
 /// Create a log drain.
 fn create_drain(mode: LoggingMode) -> Logger {
     match mode {
@@ -106,7 +132,7 @@ pub fn create_root_logger(verbose_level: i64, mode: LoggingMode) -> Logger {
         }
     };
 
-    let drain = slog::LevelFilter::new(create_drain(mode), log_level).fuse();
+    let drain = slog::LevelFilter::new(RedactSecrets(create_drain(mode)), log_level).fuse();
     let drain = slog_async::Async::new(drain).build().fuse();
 
     Logger::root(drain, slog::o!("version" => dfx_version_str()))
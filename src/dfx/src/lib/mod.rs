@@ -23,6 +23,7 @@ pub mod named_canister;
 pub mod network;
 pub mod nns_types;
 pub mod operations;
+pub mod output;
 pub mod package_arguments;
 pub mod program;
 pub mod progress_bar;
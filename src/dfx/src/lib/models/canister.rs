@@ -1,13 +1,14 @@
 use crate::lib::builders::{
-    custom_download, BuildConfig, BuildOutput, BuilderPool, CanisterBuilder, IdlBuildOutput,
-    WasmBuildOutput,
+    build_cache, custom_download, get_and_write_environment_variables, BuildConfig, BuildOutput,
+    BuilderPool, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
 };
 use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
 use crate::lib::error::{BuildError, DfxError, DfxResult};
 use crate::lib::metadata::dfx::DfxMetadata;
 use crate::lib::metadata::names::{CANDID_ARGS, CANDID_SERVICE, DFX};
-use crate::lib::wasm::file::{compress_bytes, read_wasm_module};
+use crate::lib::wasm::file::{bytes_to_module, compress_bytes, decompress_bytes, read_wasm_module};
+use crate::lib::wasm::size;
 use crate::util::assets;
 use anyhow::{anyhow, bail, Context};
 use candid::Principal as CanisterId;
@@ -23,14 +24,16 @@ use itertools::Itertools;
 use petgraph::graph::{DiGraph, NodeIndex};
 use rand::{thread_rng, RngCore};
 use slog::{error, info, trace, warn, Logger};
+use std::any::Any;
 use std::cell::RefCell;
 use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
 use std::ffi::OsStr;
 use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::Path;
 use std::process::{Command, Stdio};
-use std::sync::Arc;
+use std::sync::{mpsc, Arc};
 
 /// Represents a canister from a DFX project. It can be a virtual Canister.
 /// Multiple canister instances can have the same info, but would be differentiated
@@ -76,6 +79,51 @@ impl Canister {
         self.builder.postbuild(pool, &self.info, build_config)
     }
 
+    /// Returns the fingerprint of the inputs of the build,
+    /// or None if it is not known what the build depends on.
+    pub fn build_fingerprint(
+        &self,
+        pool: &CanisterPool,
+        build_config: &BuildConfig,
+    ) -> DfxResult<Option<String>> {
+        let inputs = self
+            .builder
+            .get_build_inputs(pool, &self.info, build_config)?;
+        let dependencies = self.builder.get_dependencies(pool, &self.info)?;
+        build_cache::fingerprint(pool, &self.info, build_config, inputs, &dependencies)
+    }
+
+    /// Uses the outputs of the previous build in `.dfx/<network>/canisters/<name>` instead of building the canister.
+    #[context("Failed to reuse the previous build of canister '{}'.", self.info.get_name())]
+    pub fn reuse_previous_build(
+        &self,
+        pool: &CanisterPool,
+        build_config: &BuildConfig,
+    ) -> DfxResult<&BuildOutput> {
+        // dependents expect the interface in the IDL and LSP roots, like after a build
+        let service_idl_path = self.info.get_service_idl_path();
+        for root in [&build_config.idl_root, &build_config.lsp_root] {
+            let target = root
+                .join(self.canister_id().to_text())
+                .with_extension("did");
+            dfx_core::fs::composite::ensure_parent_dir_exists(&target)?;
+            dfx_core::fs::copy(&service_idl_path, &target)?;
+            dfx_core::fs::set_permissions_readwrite(&target)?;
+        }
+
+        let output = BuildOutput {
+            canister_id: self.canister_id(),
+            wasm: WasmBuildOutput::File(self.info.get_build_wasm_path()),
+            idl: IdlBuildOutput::File(self.builder.get_candid_path(
+                pool,
+                &self.info,
+                build_config,
+            )?),
+        };
+        let _ = self.output.replace(Some(output));
+        Ok(self.get_build_output().unwrap())
+    }
+
     pub fn get_name(&self) -> &str {
         self.info.get_name()
     }
@@ -112,6 +160,7 @@ impl Canister {
     pub(crate) fn wasm_post_process(
         &self,
         logger: &Logger,
+        build_config: &BuildConfig,
         build_output: &BuildOutput,
     ) -> DfxResult {
         let build_output_wasm_path = match &build_output.wasm {
@@ -130,6 +179,8 @@ impl Canister {
 
         let mut m = read_wasm_module(build_output_wasm_path)?;
         let mut modified = false;
+        // the post-processing steps that modify the module, for the size report
+        let mut steps = vec![];
 
         // optimize or shrink
         if let Some(level) = info.get_optimize() {
@@ -143,12 +194,30 @@ impl Canister {
             )
             .context("Failed to optimize the Wasm module.")?;
             modified = true;
+            steps.push("optimized");
         } else if info.get_shrink() == Some(true)
             || (info.get_shrink().is_none() && (info.is_rust() || info.is_motoko()))
         {
             trace!(logger, "Shrinking Wasm");
             ic_wasm::shrink::shrink(&mut m);
             modified = true;
+            steps.push("shrunk");
+        }
+
+        // instrument after optimizing, so that the optimizer does not remove the instrumentation
+        if build_config.instrument || info.get_instrument() {
+            trace!(logger, "Instrumenting Wasm");
+            ic_wasm::instrumentation::instrument(
+                &mut m,
+                ic_wasm::instrumentation::Config {
+                    trace_only_funcs: vec![],
+                    start_address: None,
+                    page_limit: None,
+                },
+            )
+            .map_err(|e| anyhow!("Failed to instrument the Wasm module: {}", e))?;
+            modified = true;
+            steps.push("instrumented");
         }
 
         // metadata
@@ -287,21 +356,83 @@ impl Canister {
 
         // If not modified and not set "gzip" explicitly, copy the wasm file directly so that hash match.
         if !modified && !info.get_gzip() {
+            let wasm_size = dfx_core::fs::metadata(build_output_wasm_path)?.len();
+            if build_config.size_report {
+                self.report_size(logger, build_output_wasm_path, &m, &[], None)?;
+            }
+            self.check_wasm_size(wasm_size)?;
             dfx_core::fs::copy(build_output_wasm_path, &wasm_path)?;
-            return Ok(());
+        } else {
+            steps.push("with metadata");
+            let wasm = m.emit_wasm();
+            let new_bytes = if wasm_path.extension() == Some(OsStr::new("gz")) {
+                // gzip
+                // Unlike using gzip CLI, the compression below only takes the wasm bytes
+                // So as long as the wasm bytes are the same, the gzip file will be the same on different platforms.
+                trace!(logger, "Compressing Wasm");
+                Some(compress_bytes(&wasm)?)
+            } else {
+                None
+            };
+            if build_config.size_report {
+                // the function sizes are taken from the binary, so the emitted module is parsed again
+                let final_module = bytes_to_module(&wasm)?;
+                let post_processed = format!("Post-processed ({})", steps.join(", "));
+                self.report_size(
+                    logger,
+                    build_output_wasm_path,
+                    &final_module,
+                    &[(post_processed.as_str(), wasm.len())],
+                    new_bytes.as_ref().map(|bytes| bytes.len()),
+                )?;
+            }
+            let new_bytes = new_bytes.unwrap_or(wasm);
+            // checked before writing, so that an oversized module does not replace the previous build
+            self.check_wasm_size(new_bytes.len() as u64)?;
+            dfx_core::fs::write(&wasm_path, new_bytes)?;
         }
+        Ok(())
+    }
 
-        let new_bytes = if wasm_path.extension() == Some(OsStr::new("gz")) {
-            // gzip
-            // Unlike using gzip CLI, the compression below only takes the wasm bytes
-            // So as long as the wasm bytes are the same, the gzip file will be the same on different platforms.
-            trace!(logger, "Compressing Wasm");
-            compress_bytes(&m.emit_wasm())?
-        } else {
-            m.emit_wasm()
-        };
-        dfx_core::fs::write(&wasm_path, new_bytes)?;
+    /// Logs the size of the build output, the size after post-processing and the breakdown of the final module.
+    fn report_size(
+        &self,
+        logger: &Logger,
+        build_output_wasm_path: &Path,
+        final_module: &walrus::Module,
+        post_processed: &[(&str, usize)],
+        gzipped: Option<usize>,
+    ) -> DfxResult {
+        let mut build_output = dfx_core::fs::read(build_output_wasm_path)?;
+        if build_output_wasm_path.extension() == Some(OsStr::new("gz")) {
+            build_output = decompress_bytes(&build_output)?;
+        }
+        let mut steps = vec![("Build output", build_output.len())];
+        steps.extend(post_processed);
+        let final_size = steps.last().unwrap().1;
+        steps.extend(gzipped.map(|size| ("Gzipped", size)));
+        let sizes = size::measure(final_module, final_size);
+        info!(
+            logger,
+            "Size report of canister '{}':\n{}",
+            self.info.get_name(),
+            size::format_report(&steps, &sizes).trim_end()
+        );
+        Ok(())
+    }
 
+    /// Fails if the final Wasm module is larger than the `max_wasm_size` of the canister.
+    fn check_wasm_size(&self, wasm_size: u64) -> DfxResult {
+        if let Some(max_wasm_size) = self.info.get_max_wasm_size() {
+            if u128::from(wasm_size) > max_wasm_size.get_bytes() {
+                bail!(
+                    "The Wasm module of canister '{}' is {} bytes, which exceeds its max_wasm_size of {} bytes.",
+                    self.info.get_name(),
+                    wasm_size,
+                    max_wasm_size.get_bytes()
+                );
+            }
+        }
         Ok(())
     }
 
@@ -655,7 +786,34 @@ impl CanisterPool {
     ) -> DfxResult<()> {
         canister.candid_post_process(self.get_logger(), build_config, build_output)?;
 
-        canister.wasm_post_process(self.get_logger(), build_output)?;
+        canister.wasm_post_process(self.get_logger(), build_config, build_output)?;
+
+        build_canister_js(&canister.canister_id(), &canister.info)?;
+
+        canister.postbuild(self, build_config)
+    }
+
+    /// The post-build steps for a canister whose previous build is reused.
+    /// Its Wasm module was already post-processed, but the checks and the steps
+    /// that write outside of its build outputs still run.
+    fn step_postbuild_reused(&self, build_config: &BuildConfig, canister: &Canister) -> DfxResult {
+        if !canister.info.is_remote() {
+            let wasm_size = dfx_core::fs::metadata(&canister.info.get_build_wasm_path())?.len();
+            canister.check_wasm_size(wasm_size)?;
+        }
+
+        // the builders of these canisters write the env file while building,
+        // so it may be missing or be for another network
+        if canister.info.is_rust() || canister.info.is_custom() {
+            let dependencies = canister.builder.get_dependencies(self, &canister.info)?;
+            get_and_write_environment_variables(
+                &canister.info,
+                &build_config.network_name,
+                self,
+                &dependencies,
+                build_config.env_file.as_deref(),
+            )?;
+        }
 
         build_canister_js(&canister.canister_id(), &canister.info)?;
 
@@ -709,7 +867,7 @@ impl CanisterPool {
             .collect();
 
         let canisters_to_build = self.canisters_to_build(build_config);
-        let mut result = Vec::new();
+        let mut queue = Vec::new();
         for canister_id in &order {
             if let Some(canister) = self.get_canister(canister_id) {
                 if canisters_to_build
@@ -718,47 +876,204 @@ impl CanisterPool {
                     .contains(&canister.get_name())
                 {
                     trace!(log, "Building canister '{}'.", canister.get_name());
+                    queue.push(canister);
                 } else {
                     trace!(log, "Not building canister '{}'.", canister.get_name());
-                    continue;
                 }
-                result.push(
-                    self.step_prebuild(build_config, canister)
-                        .map_err(|e| {
-                            BuildError::PreBuildStepFailed(
-                                *canister_id,
-                                canister.get_name().to_string(),
-                                Box::new(e),
-                            )
-                        })
-                        .and_then(|_| {
-                            self.step_build(build_config, canister).map_err(|e| {
-                                BuildError::BuildStepFailed(
-                                    *canister_id,
+            }
+        }
+        let result = if build_config.jobs > 1 {
+            self.build_in_parallel(log, build_config, &graph, queue)
+        } else {
+            queue
+                .into_iter()
+                .map(|canister| self.build_canister(build_config, canister))
+                .collect()
+        };
+
+        self.step_postbuild_all(build_config, &order)
+            .map_err(|e| DfxError::new(BuildError::PostBuildAllStepFailed(Box::new(e))))?;
+
+        Ok(result)
+    }
+
+    fn build_canister<'a>(
+        &self,
+        build_config: &BuildConfig,
+        canister: &'a Canister,
+    ) -> Result<&'a BuildOutput, BuildError> {
+        let canister_id = canister.canister_id();
+        let prebuild_failed = |e| {
+            BuildError::PreBuildStepFailed(
+                canister_id,
+                canister.get_name().to_string(),
+                Box::new(e),
+            )
+        };
+        // builds for `dfx generate` use made-up canister ids, so they are neither reused nor recorded
+        let fingerprint = if build_config.build_mode_check {
+            None
+        } else {
+            canister
+                .build_fingerprint(self, build_config)
+                .map_err(prebuild_failed)?
+        };
+        if let Some(fingerprint) = &fingerprint {
+            // the size report is made while post-processing the build output
+            if !build_config.force_rebuild
+                && !build_config.size_report
+                && build_cache::is_up_to_date(canister.get_info(), fingerprint)
+            {
+                info!(
+                    self.logger,
+                    "Canister '{}' is up to date. Reusing its previous build.",
+                    canister.get_name()
+                );
+                return canister
+                    .reuse_previous_build(self, build_config)
+                    .map_err(|e| {
+                        BuildError::BuildStepFailed(
+                            canister_id,
+                            canister.get_name().to_string(),
+                            Box::new(e),
+                        )
+                    })
+                    .and_then(|o| {
+                        self.step_postbuild_reused(build_config, canister)
+                            .map_err(|e| {
+                                BuildError::PostBuildStepFailed(
+                                    canister_id,
                                     canister.get_name().to_string(),
                                     Box::new(e),
                                 )
                             })
-                        })
-                        .and_then(|o| {
-                            self.step_postbuild(build_config, canister, o)
-                                .map_err(|e| {
-                                    BuildError::PostBuildStepFailed(
-                                        *canister_id,
-                                        canister.get_name().to_string(),
-                                        Box::new(e),
-                                    )
-                                })
-                                .map(|_| o)
-                        }),
-                );
+                            .map(|_| o)
+                    });
             }
         }
+        // a failed build can leave the outputs of the previous build partially overwritten
+        build_cache::clear_fingerprint(canister.get_info()).map_err(prebuild_failed)?;
+
+        self.step_prebuild(build_config, canister)
+            .map_err(prebuild_failed)
+            .and_then(|_| {
+                self.step_build(build_config, canister).map_err(|e| {
+                    BuildError::BuildStepFailed(
+                        canister_id,
+                        canister.get_name().to_string(),
+                        Box::new(e),
+                    )
+                })
+            })
+            .and_then(|o| {
+                self.step_postbuild(build_config, canister, o)
+                    .map_err(|e| {
+                        BuildError::PostBuildStepFailed(
+                            canister_id,
+                            canister.get_name().to_string(),
+                            Box::new(e),
+                        )
+                    })
+                    .map(|_| o)
+            })
+            .and_then(|o| {
+                if let Some(fingerprint) = &fingerprint {
+                    build_cache::save_fingerprint(canister.get_info(), fingerprint).map_err(
+                        |e| {
+                            BuildError::PostBuildStepFailed(
+                                canister_id,
+                                canister.get_name().to_string(),
+                                Box::new(e),
+                            )
+                        },
+                    )?;
+                }
+                Ok(o)
+            })
+    }
 
-        self.step_postbuild_all(build_config, &order)
-            .map_err(|e| DfxError::new(BuildError::PostBuildAllStepFailed(Box::new(e))))?;
+    /// Builds up to `build_config.jobs` canisters at the same time, starting each canister once the canisters
+    /// it depends on are built. After the first failure no more canisters are started,
+    /// but the builds that are already running are allowed to finish.
+    fn build_in_parallel<'a>(
+        &'a self,
+        log: &Logger,
+        build_config: &BuildConfig,
+        graph: &DiGraph<CanisterId, ()>,
+        mut queue: Vec<&'a Canister>,
+    ) -> Vec<Result<&'a BuildOutput, BuildError>> {
+        let queued: HashSet<CanisterId> = queue.iter().map(|c| c.canister_id()).collect();
+        // the dependencies of each queued canister that are queued as well
+        let mut waiting_for: BTreeMap<CanisterId, HashSet<CanisterId>> = graph
+            .node_indices()
+            .filter(|ix| queued.contains(&graph[*ix]))
+            .map(|ix| {
+                let dependencies = graph
+                    .neighbors(ix)
+                    .map(|dep| graph[dep])
+                    .filter(|dep| queued.contains(dep))
+                    .collect();
+                (graph[ix], dependencies)
+            })
+            .collect();
 
-        Ok(result)
+        let mut result = Vec::new();
+        std::thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+            let mut running = 0;
+            let mut failed = false;
+            loop {
+                while !failed && running < build_config.jobs {
+                    let Some(position) = queue
+                        .iter()
+                        .position(|c| waiting_for[&c.canister_id()].is_empty())
+                    else {
+                        break;
+                    };
+                    let canister = queue.remove(position);
+                    info!(log, "Building canister '{}'.", canister.get_name());
+                    let sender = sender.clone();
+                    scope.spawn(move || {
+                        // a panicking build must still report, or the loop below waits for it forever
+                        let output = panic::catch_unwind(AssertUnwindSafe(|| {
+                            self.build_canister(build_config, canister)
+                        }))
+                        .unwrap_or_else(|payload| {
+                            Err(BuildError::BuildStepFailed(
+                                canister.canister_id(),
+                                canister.get_name().to_string(),
+                                Box::new(anyhow!(
+                                    "The build panicked: {}",
+                                    panic_message(payload.as_ref())
+                                )),
+                            ))
+                        });
+                        let _ = sender.send((canister.canister_id(), output));
+                    });
+                    running += 1;
+                }
+                if running == 0 {
+                    break;
+                }
+                let (canister_id, output) = receiver
+                    .recv()
+                    .expect("A build thread exited without reporting its result.");
+                running -= 1;
+                failed |= output.is_err();
+                result.push(output);
+                for dependencies in waiting_for.values_mut() {
+                    dependencies.remove(&canister_id);
+                }
+            }
+        });
+        if !queue.is_empty() {
+            warn!(
+                log,
+                "Did not build {} because of the failure above.",
+                queue.iter().map(|c| c.get_name()).join(", ")
+            );
+        }
+        result
     }
 
     /// Build all canisters, failing with the first that failed the build. Will return
@@ -861,6 +1176,14 @@ fn decode_path_to_str(path: &Path) -> DfxResult<&str> {
     })
 }
 
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("no message")
+}
+
 /// Create a canister JavaScript DID and Actor Factory.
 #[context("Failed to build canister js for canister '{}'.", canister_info.get_name())]
 fn build_canister_js(canister_id: &CanisterId, canister_info: &CanisterInfo) -> DfxResult {
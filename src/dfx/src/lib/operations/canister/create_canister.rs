@@ -51,7 +51,7 @@ pub async fn create_canister(
     let config = env.get_config_or_anyhow()?;
     let config_interface = config.get_config();
 
-    let mut canister_id_store = env.get_canister_id_store()?;
+    let canister_id_store = env.get_canister_id_store()?;
 
     let network_name = get_network_context()?;
 
@@ -121,6 +121,46 @@ The command line value will be used.",
         specified_id = None;
     }
 
+    create_and_register_canister(
+        env,
+        canister_name,
+        with_cycles,
+        specified_id,
+        call_sender,
+        no_wallet,
+        from_subaccount,
+        settings,
+        created_at_time,
+        subnet_selection,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Creates a canister and records its id under the given name in the canister id store.
+/// Unlike `create_canister`, the name does not have to be a canister in dfx.json.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_and_register_canister(
+    env: &dyn Environment,
+    canister_name: &str,
+    with_cycles: Option<u128>,
+    specified_id: Option<Principal>,
+    call_sender: &CallSender,
+    no_wallet: bool,
+    from_subaccount: Option<Subaccount>,
+    settings: DfxCanisterSettings,
+    created_at_time: Option<u64>,
+    subnet_selection: &mut SubnetSelectionType,
+) -> DfxResult<Principal> {
+    let log = env.get_logger();
+    let mut canister_id_store = env.get_canister_id_store()?;
+    let network_name = get_network_context()?;
+    let non_default_network = if network_name == "local" {
+        String::new()
+    } else {
+        format!("on network {} ", network_name)
+    };
+
     // Replace call_sender with wallet canister unless:
     // 1. specified_id is in effect OR
     // 2. --no-wallet is set explicitly OR
@@ -187,7 +227,7 @@ The command line value will be used.",
     );
     canister_id_store.add(canister_name, &canister_id, None)?;
 
-    Ok(())
+    Ok(cid)
 }
 
 async fn create_with_management_canister(
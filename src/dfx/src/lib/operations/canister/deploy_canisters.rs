@@ -9,10 +9,16 @@ use crate::lib::models::canister::CanisterPool;
 use crate::lib::operations::canister::deploy_canisters::DeployMode::{
     ComputeEvidence, ForceReinstallSingleCanister, NormalDeploy, PrepareForProposal,
 };
+use crate::lib::operations::canister::deploy_plan::{
+    plan_canister, plan_created_canister, DeployPlan,
+};
 use crate::lib::operations::canister::motoko_playground::reserve_canister_with_playground;
+use crate::lib::operations::canister::settings_diff::reconcile_settings;
 use crate::lib::operations::canister::{
     all_project_canisters_with_ids, create_canister, install_canister::install_canister,
 };
+use crate::lib::output::print_output;
+use crate::util::clap::canister_tag_opt::CanisterTagOpt;
 use crate::util::clap::subnet_selection_opt::SubnetSelectionType;
 use anyhow::{anyhow, bail, Context};
 use candid::Principal;
@@ -25,7 +31,9 @@ use ic_utils::interfaces::management_canister::attributes::{
 };
 use ic_utils::interfaces::management_canister::builders::{InstallMode, WasmMemoryLimit};
 use icrc_ledger_types::icrc1::account::Subaccount;
+use itertools::Itertools;
 use slog::info;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
 
@@ -37,27 +45,58 @@ pub enum DeployMode {
     ComputeEvidence(String),
 }
 
+/// The options of `dfx deploy`.
+pub struct DeployOptions<'a> {
+    pub some_canister: Option<&'a str>,
+    pub canister_tags: &'a CanisterTagOpt,
+    pub argument: Option<&'a str>,
+    pub argument_type: Option<&'a str>,
+    pub deploy_mode: &'a DeployMode,
+    pub upgrade_unchanged: bool,
+    pub with_cycles: Option<u128>,
+    pub created_at_time: Option<u64>,
+    pub specified_id_from_cli: Option<Principal>,
+    pub from_subaccount: Option<Subaccount>,
+    pub no_wallet: bool,
+    pub skip_consent: bool,
+    pub env_file: Option<PathBuf>,
+    pub no_asset_upgrade: bool,
+    pub always_assist: bool,
+    pub jobs: usize,
+    pub force_rebuild: bool,
+    pub instrument: bool,
+    /// Prints what the deploy would do instead of deploying.
+    pub plan: bool,
+}
+
 #[context("Failed while trying to deploy canisters.")]
-#[allow(clippy::too_many_arguments)]
 pub async fn deploy_canisters(
     env: &dyn Environment,
-    some_canister: Option<&str>,
-    argument: Option<&str>,
-    argument_type: Option<&str>,
-    deploy_mode: &DeployMode,
-    upgrade_unchanged: bool,
-    with_cycles: Option<u128>,
-    created_at_time: Option<u64>,
-    specified_id_from_cli: Option<Principal>,
+    options: &DeployOptions<'_>,
     call_sender: &CallSender,
-    from_subaccount: Option<Subaccount>,
-    no_wallet: bool,
-    skip_consent: bool,
-    env_file: Option<PathBuf>,
-    no_asset_upgrade: bool,
     subnet_selection: &mut SubnetSelectionType,
-    always_assist: bool,
 ) -> DfxResult {
+    let DeployOptions {
+        some_canister,
+        canister_tags,
+        argument,
+        argument_type,
+        deploy_mode,
+        upgrade_unchanged,
+        with_cycles,
+        created_at_time,
+        specified_id_from_cli,
+        from_subaccount,
+        no_wallet,
+        skip_consent,
+        ref env_file,
+        no_asset_upgrade,
+        always_assist,
+        jobs,
+        force_rebuild,
+        instrument,
+        plan,
+    } = *options;
     let log = env.get_logger();
 
     let config = env
@@ -75,7 +114,7 @@ pub async fn deploy_canisters(
         }
     }
 
-    let canisters_to_deploy = canister_with_dependencies(&config, some_canister)?;
+    let canisters_to_deploy = canister_with_dependencies(&config, some_canister, canister_tags)?;
 
     let canisters_to_build = match deploy_mode {
         PrepareForProposal(canister_name) | ComputeEvidence(canister_name) => {
@@ -103,7 +142,20 @@ pub async fn deploy_canisters(
         .filter(|canister_name| !pull_canisters_in_config.contains_key(canister_name))
         .collect();
 
-    if some_canister.is_some() {
+    if plan {
+        return print_deploy_plan(
+            env,
+            &config,
+            &canisters_to_deploy,
+            &canisters_to_install,
+            &initial_canister_id_store,
+            options,
+            call_sender,
+        )
+        .await;
+    }
+
+    if some_canister.is_some() || canister_tags.is_set() {
         info!(log, "Deploying: {}", canisters_to_install.join(" "));
     } else {
         info!(log, "Deploying all canisters.");
@@ -138,11 +190,26 @@ pub async fn deploy_canisters(
         &canisters_to_build,
         &config,
         env_file.clone(),
+        jobs,
+        force_rebuild,
+        instrument,
     )
     .await?;
 
     match deploy_mode {
         NormalDeploy | ForceReinstallSingleCanister(_) => {
+            if !env.get_network_descriptor().is_playground() {
+                // Canisters created by this deploy already have the other settings in dfx.json,
+                // but get their controllers from dfx.json here.
+                let canister_id_store = env.get_canister_id_store()?;
+                let canisters = canisters_to_install
+                    .iter()
+                    .map(|canister_name| {
+                        Ok((canister_name.clone(), canister_id_store.get(canister_name)?))
+                    })
+                    .collect::<DfxResult<Vec<_>>>()?;
+                reconcile_settings(env, &canisters, call_sender, skip_consent).await?;
+            }
             let force_reinstall = matches!(deploy_mode, ForceReinstallSingleCanister(_));
             install_canisters(
                 env,
@@ -175,14 +242,99 @@ pub async fn deploy_canisters(
     Ok(())
 }
 
+/// Prints what the deploy would do, without creating, installing or updating any canister.
+/// Only the canisters that exist, and whose dependencies exist, are built.
+#[context("Failed to plan the deployment.")]
+async fn print_deploy_plan(
+    env: &dyn Environment,
+    config: &Config,
+    canisters_to_deploy: &[String],
+    canisters_to_install: &[String],
+    canister_id_store: &CanisterIdStore,
+    options: &DeployOptions<'_>,
+    call_sender: &CallSender,
+) -> DfxResult {
+    // A canister is built with the ids of its dependencies, so it is not built before they exist.
+    let mut uncreated_dependencies = BTreeMap::new();
+    for canister_name in canisters_to_install {
+        let uncreated: Vec<String> = config
+            .get_config()
+            .get_canister_names_with_dependencies(Some(canister_name))?
+            .into_iter()
+            .filter(|name| name != canister_name && canister_id_store.find(name).is_none())
+            .sorted()
+            .collect();
+        uncreated_dependencies.insert(canister_name.clone(), uncreated);
+    }
+    let canisters_to_build: Vec<String> = canisters_to_install
+        .iter()
+        .filter(|canister_name| {
+            canister_id_store.find(canister_name).is_some()
+                && uncreated_dependencies[*canister_name].is_empty()
+        })
+        .cloned()
+        .collect();
+    if !canisters_to_build.is_empty() {
+        let canisters_to_load = all_project_canisters_with_ids(env, config);
+        build_canisters(
+            env,
+            &canisters_to_load,
+            &canisters_to_build,
+            config,
+            options.env_file.clone(),
+            options.jobs,
+            options.force_rebuild,
+            options.instrument,
+        )
+        .await?;
+    }
+
+    let mut canisters = vec![];
+    // The dependencies that are created, but not installed, by this deploy.
+    for canister_name in canisters_to_deploy {
+        if !canisters_to_install.contains(canister_name)
+            && canister_id_store.find(canister_name).is_none()
+        {
+            canisters.push(plan_created_canister(config, canister_name)?);
+        }
+    }
+    for canister_name in canisters_to_install {
+        let force_reinstall = matches!(
+            options.deploy_mode,
+            ForceReinstallSingleCanister(name) if name == canister_name
+        );
+        canisters.push(
+            plan_canister(
+                env,
+                config,
+                canister_name,
+                canister_id_store.find(canister_name),
+                &uncreated_dependencies[canister_name],
+                force_reinstall,
+                options.upgrade_unchanged,
+                options.no_asset_upgrade,
+                call_sender,
+            )
+            .await?,
+        );
+    }
+    print_output(
+        env,
+        &DeployPlan {
+            network: env.get_network_descriptor().name.clone(),
+            canisters,
+        },
+    )
+}
+
 #[context("Failed to collect canisters and their dependencies.")]
 fn canister_with_dependencies(
     config: &Config,
     some_canister: Option<&str>,
+    canister_tags: &CanisterTagOpt,
 ) -> DfxResult<Vec<String>> {
-    let mut canister_names = config
-        .get_config()
-        .get_canister_names_with_dependencies(some_canister)?;
+    let mut canister_names =
+        canister_tags.select_with_dependencies(config.get_config(), some_canister)?;
     canister_names.sort();
     Ok(canister_names)
 }
@@ -295,6 +447,9 @@ async fn build_canisters(
     canisters_to_build: &[String],
     config: &Config,
     env_file: Option<PathBuf>,
+    jobs: usize,
+    force_rebuild: bool,
+    instrument: bool,
 ) -> DfxResult<CanisterPool> {
     let log = env.get_logger();
     info!(log, "Building canisters...");
@@ -304,7 +459,10 @@ async fn build_canisters(
     let build_config =
         BuildConfig::from_config(config, env.get_network_descriptor().is_playground())?
             .with_canisters_to_build(canisters_to_build.into())
-            .with_env_file(env_file);
+            .with_env_file(env_file)
+            .with_jobs(jobs)
+            .with_force_rebuild(force_rebuild)
+            .with_instrument(instrument);
     canister_pool.build_or_fail(log, &build_config).await?;
     Ok(canister_pool)
 }
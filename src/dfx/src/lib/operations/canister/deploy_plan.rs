@@ -0,0 +1,377 @@
+use crate::lib::canister_info::assets::AssetsCanisterInfo;
+use crate::lib::canister_info::CanisterInfo;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::operations::canister::get_canister_status;
+use crate::lib::operations::canister::install_canister::{
+    check_candid_compatibility, check_stable_compatibility,
+};
+use crate::lib::operations::canister::settings_diff::{
+    desired_controllers_without_unlocking, desired_settings, diff_controllers, diff_settings,
+    ControllerChanges, SettingDrift,
+};
+use crate::lib::state_tree::canister_info::read_state_tree_canister_module_hash;
+use crate::util::read_module_metadata;
+use anyhow::Context;
+use candid::Principal;
+use dfx_core::config::model::dfinity::Config;
+use dfx_core::identity::CallSender;
+use fn_error_context::context;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+
+/// What `dfx deploy` would do, as printed by `dfx deploy --plan`.
+#[derive(Serialize)]
+pub struct DeployPlan {
+    pub network: String,
+    pub canisters: Vec<CanisterPlan>,
+}
+
+/// What `dfx deploy` would do to one canister.
+#[derive(Serialize)]
+pub struct CanisterPlan {
+    canister: String,
+    /// `None` if the canister would be created.
+    canister_id: Option<Principal>,
+    code: CodeAction,
+    installed_module_hash: Option<String>,
+    /// The hash of the built module.
+    /// Canisters that do not exist yet, or whose dependencies do not exist yet, are not built.
+    new_module_hash: Option<String>,
+    /// The dependencies that the deploy would create before building the canister.
+    uncreated_dependencies: Vec<String>,
+    candid_compatibility: Option<CompatibilityCheck>,
+    stable_compatibility: Option<CompatibilityCheck>,
+    /// The settings that differ from dfx.json, which the deploy would update.
+    settings: Vec<SettingDrift>,
+    controllers: Option<ControllerChanges>,
+    /// The identities in the controllers in dfx.json that cannot be resolved without unlocking them,
+    /// in which case the controllers are not compared.
+    locked_controllers: Vec<String>,
+    assets: Option<AssetsPlan>,
+    post_install: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum CodeAction {
+    /// Create the canister, without installing its code, because it is only a dependency.
+    Create,
+    /// Create the canister, then install its code.
+    CreateAndInstall,
+    /// Build and install the code after creating the dependencies of the canister.
+    InstallAfterDependencies,
+    Install,
+    Upgrade,
+    Reinstall,
+    /// The module is already installed, and would not be upgraded.
+    Unchanged,
+    /// The asset canister would not be upgraded, because of --no-asset-upgrade.
+    Skipped,
+}
+
+/// The result of a Candid or stable type compatibility check.
+#[derive(Serialize)]
+struct CompatibilityCheck {
+    /// One of `compatible`, `incompatible` or `failed`.
+    result: &'static str,
+    details: Option<String>,
+}
+
+impl From<anyhow::Result<Option<String>>> for CompatibilityCheck {
+    fn from(result: anyhow::Result<Option<String>>) -> Self {
+        match result {
+            Ok(None) => Self {
+                result: "compatible",
+                details: None,
+            },
+            Ok(Some(details)) => Self {
+                result: "incompatible",
+                details: Some(details),
+            },
+            Err(err) => Self {
+                result: "failed",
+                details: Some(format!("{err:#}")),
+            },
+        }
+    }
+}
+
+/// The asset keys that an asset sync would change.
+#[derive(Serialize)]
+struct AssetsPlan {
+    created: Vec<String>,
+    updated: Vec<String>,
+    deleted: Vec<String>,
+    properties_updated: Vec<String>,
+}
+
+impl From<ic_asset::SyncPlan> for AssetsPlan {
+    fn from(plan: ic_asset::SyncPlan) -> Self {
+        Self {
+            created: plan.created,
+            updated: plan.updated,
+            deleted: plan.deleted,
+            properties_updated: plan.properties_updated,
+        }
+    }
+}
+
+/// Plans a dependency that the deploy creates but does not install.
+pub fn plan_created_canister(config: &Config, canister_name: &str) -> DfxResult<CanisterPlan> {
+    let canister_info = CanisterInfo::load(config, canister_name, None)?;
+    let mut plan = CanisterPlan::new(&canister_info, None);
+    plan.code = CodeAction::Create;
+    plan.post_install = vec![];
+    Ok(plan)
+}
+
+/// Resolves what `dfx deploy` would do to a canister, with calls that change nothing.
+/// The canister must already be built if it exists and its dependencies exist.
+#[context("Failed to plan the deployment of canister {}.", canister_name)]
+#[allow(clippy::too_many_arguments)]
+pub async fn plan_canister(
+    env: &dyn Environment,
+    config: &Config,
+    canister_name: &str,
+    canister_id: Option<Principal>,
+    uncreated_dependencies: &[String],
+    force_reinstall: bool,
+    upgrade_unchanged: bool,
+    no_asset_upgrade: bool,
+    call_sender: &CallSender,
+) -> DfxResult<CanisterPlan> {
+    let canister_info = CanisterInfo::load(config, canister_name, canister_id)?;
+    let mut plan = CanisterPlan::new(&canister_info, canister_id);
+    plan.uncreated_dependencies = uncreated_dependencies.to_vec();
+    let Some(canister_id) = canister_id else {
+        return Ok(plan);
+    };
+    let agent = env.get_agent();
+
+    let installed_module_hash = read_state_tree_canister_module_hash(agent, canister_id).await?;
+    plan.installed_module_hash = installed_module_hash
+        .as_ref()
+        .map(|hash| format!("0x{}", hex::encode(hash)));
+    if !env.get_network_descriptor().is_playground() {
+        plan_settings(
+            env,
+            config,
+            canister_name,
+            canister_id,
+            call_sender,
+            &mut plan,
+        )
+        .await?;
+    }
+    if !uncreated_dependencies.is_empty() {
+        plan.code = CodeAction::InstallAfterDependencies;
+        return Ok(plan);
+    }
+
+    let wasm_module = dfx_core::fs::read(&canister_info.get_build_wasm_path())?;
+    let new_module_hash = Sha256::digest(wasm_module);
+    plan.code = if force_reinstall {
+        CodeAction::Reinstall
+    } else if installed_module_hash.is_none() {
+        CodeAction::Install
+    } else if !upgrade_unchanged
+        && matches!(&installed_module_hash, Some(hash) if hash[..] == new_module_hash[..])
+    {
+        CodeAction::Unchanged
+    } else {
+        CodeAction::Upgrade
+    };
+    if canister_info.is_assets() && no_asset_upgrade {
+        plan.code = CodeAction::Skipped;
+    }
+    plan.new_module_hash = Some(format!("0x{}", hex::encode(new_module_hash)));
+
+    if matches!(plan.code, CodeAction::Upgrade | CodeAction::Reinstall) {
+        if let Some(candid) = read_module_metadata(agent, canister_id, "candid:service").await {
+            plan.candid_compatibility =
+                Some(check_candid_compatibility(&canister_info, &candid).into());
+        }
+    }
+    if plan.code == CodeAction::Upgrade && canister_info.is_motoko() {
+        if let Some(stable_types) =
+            read_module_metadata(agent, canister_id, "motoko:stable-types").await
+        {
+            plan.stable_compatibility =
+                Some(check_stable_compatibility(&canister_info, env, &stable_types).into());
+        }
+    }
+
+    // Reinstalling clears the assets, so only an upgrade or no upgrade keeps the current ones to compare with.
+    if canister_info.is_assets()
+        && plan.installed_module_hash.is_some()
+        && plan.code != CodeAction::Reinstall
+    {
+        let assets_canister_info = canister_info.as_info::<AssetsCanisterInfo>()?;
+        let source_paths = assets_canister_info.get_source_paths();
+        let source_paths: Vec<&Path> = source_paths.iter().map(|p| p.as_path()).collect();
+        let canister = ic_utils::Canister::builder()
+            .with_agent(agent)
+            .with_canister_id(canister_id)
+            .build()
+            .context("Failed to build asset canister caller.")?;
+        let assets = ic_asset::plan_sync(&canister, &source_paths, false, env.get_logger())
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to compare the assets of canister {}.",
+                    canister_name
+                )
+            })?;
+        plan.assets = Some(assets.into());
+    }
+
+    Ok(plan)
+}
+
+/// Compares the settings of the canister, which `canister_status` reads without changing anything, with dfx.json.
+/// The controllers are not compared if that would require unlocking identities.
+async fn plan_settings(
+    env: &dyn Environment,
+    config: &Config,
+    canister_name: &str,
+    canister_id: Principal,
+    call_sender: &CallSender,
+    plan: &mut CanisterPlan,
+) -> DfxResult {
+    let config_interface = config.get_config();
+    let status = get_canister_status(env, canister_id, call_sender).await?;
+    let desired = desired_settings(config_interface, canister_name)?;
+    plan.settings = diff_settings(&desired, &status.settings).drift;
+    if let Some((desired, locked)) =
+        desired_controllers_without_unlocking(env, config_interface, canister_name)?
+    {
+        if locked.is_empty() {
+            plan.controllers = diff_controllers(&desired, &status.settings.controllers);
+        }
+        plan.locked_controllers = locked;
+    }
+    Ok(())
+}
+
+impl CanisterPlan {
+    fn new(canister_info: &CanisterInfo, canister_id: Option<Principal>) -> Self {
+        Self {
+            canister: canister_info.get_name().to_string(),
+            canister_id,
+            code: CodeAction::CreateAndInstall,
+            installed_module_hash: None,
+            new_module_hash: None,
+            uncreated_dependencies: vec![],
+            candid_compatibility: None,
+            stable_compatibility: None,
+            settings: vec![],
+            controllers: None,
+            locked_controllers: vec![],
+            assets: None,
+            post_install: canister_info.get_post_install().to_vec(),
+        }
+    }
+}
+
+impl Display for DeployPlan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Deploy plan for network {}:", self.network)?;
+        if self.canisters.is_empty() {
+            write!(f, "\n  No canisters to deploy.")?;
+        }
+        for canister in &self.canisters {
+            write!(f, "\n  {}", canister.canister)?;
+            if let Some(canister_id) = &canister.canister_id {
+                write!(f, " ({})", canister_id)?;
+            }
+            write!(f, "\n    code: ")?;
+            let hash = |hash: &Option<String>| hash.clone().unwrap_or_else(|| "none".to_string());
+            match canister.code {
+                CodeAction::Create => write!(f, "create the canister as a dependency")?,
+                CodeAction::CreateAndInstall => write!(f, "create the canister, then install")?,
+                CodeAction::InstallAfterDependencies => write!(
+                    f,
+                    "build and install after creating {}, installed module {}",
+                    canister.uncreated_dependencies.join(", "),
+                    hash(&canister.installed_module_hash)
+                )?,
+                CodeAction::Install => {
+                    write!(f, "install module {}", hash(&canister.new_module_hash))?
+                }
+                CodeAction::Upgrade => write!(
+                    f,
+                    "upgrade module {} -> {}",
+                    hash(&canister.installed_module_hash),
+                    hash(&canister.new_module_hash)
+                )?,
+                CodeAction::Reinstall => write!(
+                    f,
+                    "reinstall module {} -> {}, which erases the state",
+                    hash(&canister.installed_module_hash),
+                    hash(&canister.new_module_hash)
+                )?,
+                CodeAction::Unchanged => write!(
+                    f,
+                    "unchanged, module {} is already installed",
+                    hash(&canister.installed_module_hash)
+                )?,
+                CodeAction::Skipped => write!(f, "not upgraded, because of --no-asset-upgrade")?,
+            }
+            if let Some(check) = &canister.candid_compatibility {
+                write_check(f, "candid interface", check)?;
+            }
+            if let Some(check) = &canister.stable_compatibility {
+                write_check(f, "stable types", check)?;
+            }
+            for setting in &canister.settings {
+                write!(
+                    f,
+                    "\n    settings: {} {} -> {}",
+                    setting.setting, setting.current, setting.desired
+                )?;
+            }
+            if let Some(controllers) = &canister.controllers {
+                for added in &controllers.added {
+                    write!(f, "\n    controllers: add {}", added)?;
+                }
+                for removed in &controllers.removed {
+                    write!(f, "\n    controllers: remove {}", removed)?;
+                }
+            }
+            if !canister.locked_controllers.is_empty() {
+                write!(
+                    f,
+                    "\n    controllers: compared during the deploy, because identities {} are locked",
+                    canister.locked_controllers.join(", ")
+                )?;
+            }
+            if let Some(assets) = &canister.assets {
+                write!(
+                    f,
+                    "\n    assets: {} created, {} updated, {} deleted, {} with new properties",
+                    assets.created.len(),
+                    assets.updated.len(),
+                    assets.deleted.len(),
+                    assets.properties_updated.len()
+                )?;
+            }
+            for command in &canister.post_install {
+                write!(f, "\n    post-install: {}", command)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_check(f: &mut Formatter<'_>, name: &str, check: &CompatibilityCheck) -> fmt::Result {
+    write!(f, "\n    {}: {}", name, check.result)?;
+    if let Some(details) = &check.details {
+        for line in details.lines() {
+            write!(f, "\n      {}", line)?;
+        }
+    }
+    Ok(())
+}
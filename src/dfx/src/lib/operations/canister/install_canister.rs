@@ -267,7 +267,7 @@ The command line value will be used.",
     Ok(())
 }
 
-fn check_candid_compatibility(
+pub(crate) fn check_candid_compatibility(
     canister_info: &CanisterInfo,
     candid: &str,
 ) -> anyhow::Result<Option<String>> {
@@ -368,7 +368,7 @@ async fn wait_for_module_hash(
     Ok(())
 }
 
-fn check_stable_compatibility(
+pub(crate) fn check_stable_compatibility(
     canister_info: &CanisterInfo,
     env: &dyn Environment,
     stable_types: &str,
@@ -438,7 +438,7 @@ fn run_post_install_task(
     dependencies: &[Principal],
     env_file: Option<&Path>,
 ) -> DfxResult {
-    let cwd = canister.get_workspace_root();
+    let cwd = canister.get_working_dir();
     let words = shell_words::split(task)
         .with_context(|| format!("Error interpreting post-install task `{task}`"))?;
     let canonicalized = dfx_core::fs::canonicalize(&cwd.join(&words[0]))
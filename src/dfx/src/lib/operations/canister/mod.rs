@@ -1,9 +1,11 @@
 pub(crate) mod create_canister;
 pub(crate) mod deploy_canisters;
+pub(crate) mod deploy_plan;
 pub(crate) mod install_canister;
 pub mod motoko_playground;
+pub(crate) mod settings_diff;
 
-pub use create_canister::create_canister;
+pub use create_canister::{create_and_register_canister, create_canister};
 pub use install_canister::install_wallet;
 
 use crate::lib::canister_info::CanisterInfo;
@@ -26,6 +28,7 @@ use ic_utils::interfaces::management_canister::{
 };
 use ic_utils::interfaces::ManagementCanister;
 use ic_utils::Argument;
+use serde::Deserialize;
 use std::collections::HashSet;
 use std::path::PathBuf;
 
@@ -217,6 +220,135 @@ pub async fn stop_canister(
     Ok(())
 }
 
+/// A snapshot of the state of a canister, as listed by the management canister.
+#[derive(CandidType, Deserialize, Debug)]
+pub struct CanisterSnapshot {
+    pub id: Vec<u8>,
+    pub taken_at_timestamp: u64,
+    pub total_size: u64,
+}
+
+#[context("Failed to take a snapshot of canister {}.", canister_id)]
+pub async fn take_canister_snapshot(
+    env: &dyn Environment,
+    canister_id: Principal,
+    replace_snapshot: Option<Vec<u8>>,
+    call_sender: &CallSender,
+) -> DfxResult<CanisterSnapshot> {
+    if env.get_network_descriptor().is_playground() {
+        bail!("Canisters borrowed from a playground do not support snapshots.");
+    }
+
+    #[derive(CandidType)]
+    struct In {
+        canister_id: Principal,
+        replace_snapshot: Option<Vec<u8>>,
+    }
+
+    let (out,): (CanisterSnapshot,) = do_management_call(
+        env,
+        canister_id,
+        "take_canister_snapshot",
+        In {
+            canister_id,
+            replace_snapshot,
+        },
+        call_sender,
+        0,
+    )
+    .await?;
+    Ok(out)
+}
+
+#[context("Failed to list the snapshots of canister {}.", canister_id)]
+pub async fn list_canister_snapshots(
+    env: &dyn Environment,
+    canister_id: Principal,
+    call_sender: &CallSender,
+) -> DfxResult<Vec<CanisterSnapshot>> {
+    #[derive(CandidType)]
+    struct In {
+        canister_id: Principal,
+    }
+
+    let (out,): (Vec<CanisterSnapshot>,) = do_management_call(
+        env,
+        canister_id,
+        "list_canister_snapshots",
+        In { canister_id },
+        call_sender,
+        0,
+    )
+    .await?;
+    Ok(out)
+}
+
+#[context(
+    "Failed to load snapshot {} into canister {}.",
+    hex::encode(snapshot_id),
+    canister_id
+)]
+pub async fn load_canister_snapshot(
+    env: &dyn Environment,
+    canister_id: Principal,
+    snapshot_id: &[u8],
+    call_sender: &CallSender,
+) -> DfxResult {
+    #[derive(CandidType)]
+    struct In {
+        canister_id: Principal,
+        snapshot_id: Vec<u8>,
+        sender_canister_version: Option<u64>,
+    }
+
+    do_management_call(
+        env,
+        canister_id,
+        "load_canister_snapshot",
+        In {
+            canister_id,
+            snapshot_id: snapshot_id.to_vec(),
+            sender_canister_version: None,
+        },
+        call_sender,
+        0,
+    )
+    .await?;
+    Ok(())
+}
+
+#[context(
+    "Failed to delete snapshot {} of canister {}.",
+    hex::encode(snapshot_id),
+    canister_id
+)]
+pub async fn delete_canister_snapshot(
+    env: &dyn Environment,
+    canister_id: Principal,
+    snapshot_id: &[u8],
+    call_sender: &CallSender,
+) -> DfxResult {
+    #[derive(CandidType)]
+    struct In {
+        canister_id: Principal,
+        snapshot_id: Vec<u8>,
+    }
+
+    do_management_call(
+        env,
+        canister_id,
+        "delete_canister_snapshot",
+        In {
+            canister_id,
+            snapshot_id: snapshot_id.to_vec(),
+        },
+        call_sender,
+        0,
+    )
+    .await?;
+    Ok(())
+}
+
 #[context("Failed to update settings for {}.", canister_id)]
 pub async fn update_settings(
     env: &dyn Environment,
@@ -0,0 +1,460 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::{DfxError, DfxResult};
+use crate::lib::ic_attributes::{
+    get_compute_allocation, get_freezing_threshold, get_log_visibility, get_memory_allocation,
+    get_reserved_cycles_limit, get_wasm_memory_limit, CanisterSettings,
+};
+use crate::lib::operations::canister::{get_canister_status, update_settings};
+use anyhow::{bail, Context};
+use candid::{Nat, Principal};
+use dfx_core::cli::ask_for_consent;
+use dfx_core::config::model::dfinity::ConfigInterface;
+use dfx_core::error::cli::UserConsent;
+use dfx_core::error::identity::instantiate_identity_from_name::InstantiateIdentityFromNameError::GetIdentityPrincipalFailed;
+use dfx_core::identity::CallSender;
+use fn_error_context::context;
+use ic_agent::identity::Identity;
+use ic_utils::interfaces::management_canister::{DefiniteCanisterSettings, LogVisibility};
+use serde::Serialize;
+use slog::{info, warn};
+use std::collections::BTreeSet;
+
+/// A setting of a canister whose value on the network differs from dfx.json.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct SettingDrift {
+    pub setting: &'static str,
+    /// The value on the network.
+    pub current: String,
+    /// The value in dfx.json.
+    pub desired: String,
+}
+
+/// The controllers to add to and remove from a canister to match dfx.json.
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct ControllerChanges {
+    pub added: Vec<Principal>,
+    pub removed: Vec<Principal>,
+}
+
+/// The settings of a canister that differ from dfx.json.
+#[derive(Debug)]
+pub struct SettingsDiff {
+    pub drift: Vec<SettingDrift>,
+    pub controllers: Option<ControllerChanges>,
+    /// The update that reconciles the canister with dfx.json.
+    pub update: CanisterSettings,
+}
+
+impl SettingsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.drift.is_empty() && self.controllers.is_none()
+    }
+}
+
+/// The settings that dfx.json specifies for a canister.
+/// Settings that dfx.json leaves out are `None`.
+pub fn desired_settings(
+    config_interface: &ConfigInterface,
+    canister_name: &str,
+) -> DfxResult<CanisterSettings> {
+    let config_interface = Some(config_interface);
+    let canister_name = Some(canister_name);
+    Ok(CanisterSettings {
+        controllers: None,
+        compute_allocation: get_compute_allocation(None, config_interface, canister_name)?,
+        memory_allocation: get_memory_allocation(None, config_interface, canister_name)?,
+        freezing_threshold: get_freezing_threshold(None, config_interface, canister_name)?,
+        reserved_cycles_limit: get_reserved_cycles_limit(None, config_interface, canister_name)?,
+        wasm_memory_limit: get_wasm_memory_limit(None, config_interface, canister_name)?,
+        log_visibility: get_log_visibility(None, config_interface, canister_name)?,
+    })
+}
+
+/// The principals of the controllers that dfx.json lists for a canister.
+/// Each controller is either a principal or the name of an identity.
+#[context(
+    "Failed to get the controllers of canister {} in dfx.json.",
+    canister_name
+)]
+pub fn desired_controllers(
+    env: &dyn Environment,
+    config_interface: &ConfigInterface,
+    canister_name: &str,
+) -> DfxResult<Option<Vec<Principal>>> {
+    let Some(controllers) = config_interface.get_controllers(canister_name)? else {
+        return Ok(None);
+    };
+    let mut principals: Vec<Principal> = vec![];
+    for controller in &controllers {
+        let principal = controller_to_principal(env, controller)?;
+        if !principals.contains(&principal) {
+            principals.push(principal);
+        }
+    }
+    Ok(Some(principals))
+}
+
+/// The principals of the controllers that dfx.json lists for a canister, resolved without unlocking any identity.
+/// Also returns the names of the identities that cannot be resolved this way,
+/// because their keys are encrypted, in the keyring or in an HSM.
+#[context(
+    "Failed to get the controllers of canister {} in dfx.json.",
+    canister_name
+)]
+pub fn desired_controllers_without_unlocking(
+    env: &dyn Environment,
+    config_interface: &ConfigInterface,
+    canister_name: &str,
+) -> DfxResult<Option<(Vec<Principal>, Vec<String>)>> {
+    let Some(controllers) = config_interface.get_controllers(canister_name)? else {
+        return Ok(None);
+    };
+    let selected_identity = env
+        .get_selected_identity()
+        .zip(env.get_selected_identity_principal());
+    let mut unencrypted_principals = None;
+    let mut principals: Vec<Principal> = vec![];
+    let mut locked = vec![];
+    for controller in &controllers {
+        let principal = match (Principal::from_text(controller), selected_identity) {
+            (Ok(principal), _) => principal,
+            (_, Some((name, principal))) if name == controller => principal,
+            _ => {
+                if unencrypted_principals.is_none() {
+                    unencrypted_principals = Some(
+                        env.new_identity_manager()?
+                            .get_unencrypted_principal_map(env.get_logger()),
+                    );
+                }
+                match unencrypted_principals
+                    .as_ref()
+                    .and_then(|map| map.get(controller))
+                {
+                    Some(principal) => Principal::from_text(principal)?,
+                    None => {
+                        locked.push(controller.clone());
+                        continue;
+                    }
+                }
+            }
+        };
+        if !principals.contains(&principal) {
+            principals.push(principal);
+        }
+    }
+    Ok(Some((principals, locked)))
+}
+
+#[context("Failed to convert controller '{}' to a principal", controller)]
+fn controller_to_principal(env: &dyn Environment, controller: &str) -> DfxResult<Principal> {
+    if let Ok(principal) = Principal::from_text(controller) {
+        return Ok(principal);
+    }
+    if env.get_selected_identity().map(String::as_str) == Some(controller) {
+        if let Some(principal) = env.get_selected_identity_principal() {
+            return Ok(principal);
+        }
+    }
+    env.new_identity_manager()?
+        .instantiate_identity_from_name(controller, env.get_logger())
+        .and_then(|identity| identity.sender().map_err(GetIdentityPrincipalFailed))
+        .map_err(DfxError::new)
+}
+
+/// Compares the settings that dfx.json specifies with the settings of the canister on the network.
+pub fn diff_settings(
+    desired: &CanisterSettings,
+    current: &DefiniteCanisterSettings,
+) -> SettingsDiff {
+    let mut drift = vec![];
+    let mut update = CanisterSettings::default();
+    let mut compare = |setting, current: Option<&Nat>, desired: Nat| {
+        if current != Some(&desired) {
+            drift.push(SettingDrift {
+                setting,
+                current: current.map_or_else(|| "not set".to_string(), |n| n.0.to_string()),
+                desired: desired.0.to_string(),
+            });
+            true
+        } else {
+            false
+        }
+    };
+
+    if let Some(compute_allocation) = desired.compute_allocation {
+        let value = Nat::from(u8::from(compute_allocation));
+        if compare(
+            "compute_allocation",
+            Some(&current.compute_allocation),
+            value,
+        ) {
+            update.compute_allocation = Some(compute_allocation);
+        }
+    }
+    if let Some(memory_allocation) = desired.memory_allocation {
+        let value = Nat::from(u64::from(memory_allocation));
+        if compare("memory_allocation", Some(&current.memory_allocation), value) {
+            update.memory_allocation = Some(memory_allocation);
+        }
+    }
+    if let Some(freezing_threshold) = desired.freezing_threshold {
+        let value = Nat::from(u64::from(freezing_threshold));
+        if compare(
+            "freezing_threshold",
+            Some(&current.freezing_threshold),
+            value,
+        ) {
+            update.freezing_threshold = Some(freezing_threshold);
+        }
+    }
+    if let Some(reserved_cycles_limit) = desired.reserved_cycles_limit {
+        let value = Nat::from(u128::from(reserved_cycles_limit));
+        if compare(
+            "reserved_cycles_limit",
+            current.reserved_cycles_limit.as_ref(),
+            value,
+        ) {
+            update.reserved_cycles_limit = Some(reserved_cycles_limit);
+        }
+    }
+    if let Some(wasm_memory_limit) = desired.wasm_memory_limit {
+        let value = Nat::from(u64::from(wasm_memory_limit));
+        if compare(
+            "wasm_memory_limit",
+            current.wasm_memory_limit.as_ref(),
+            value,
+        ) {
+            update.wasm_memory_limit = Some(wasm_memory_limit);
+        }
+    }
+    if let Some(log_visibility) = desired.log_visibility {
+        let desired_name = log_visibility_name(&log_visibility);
+        let current_name = log_visibility_name(&current.log_visibility);
+        if desired_name != current_name {
+            drift.push(SettingDrift {
+                setting: "log_visibility",
+                current: current_name.to_string(),
+                desired: desired_name.to_string(),
+            });
+            update.log_visibility = Some(log_visibility);
+        }
+    }
+
+    let mut controllers = None;
+    if let Some(desired_controllers) = &desired.controllers {
+        controllers = diff_controllers(desired_controllers, &current.controllers);
+        if controllers.is_some() {
+            update.controllers = Some(desired_controllers.clone());
+        }
+    }
+
+    SettingsDiff {
+        drift,
+        controllers,
+        update,
+    }
+}
+
+/// The controllers to add and remove so that a canister has the desired controllers, or `None` if it already has them.
+pub fn diff_controllers(desired: &[Principal], current: &[Principal]) -> Option<ControllerChanges> {
+    let current_set: BTreeSet<_> = current.iter().collect();
+    let desired_set: BTreeSet<_> = desired.iter().collect();
+    let changes = ControllerChanges {
+        added: desired_set.difference(&current_set).map(|p| **p).collect(),
+        removed: current_set.difference(&desired_set).map(|p| **p).collect(),
+    };
+    (changes != ControllerChanges::default()).then_some(changes)
+}
+
+fn log_visibility_name(log_visibility: &LogVisibility) -> &'static str {
+    match log_visibility {
+        LogVisibility::Controllers => "controllers",
+        LogVisibility::Public => "public",
+    }
+}
+
+/// Compares the settings that dfx.json specifies for a canister with its settings on the network.
+#[context(
+    "Failed to compare the settings of canister {} with dfx.json.",
+    canister_name
+)]
+pub async fn get_settings_diff(
+    env: &dyn Environment,
+    canister_name: &str,
+    canister_id: Principal,
+    call_sender: &CallSender,
+) -> DfxResult<SettingsDiff> {
+    let config = env.get_config_or_anyhow()?;
+    let mut desired = desired_settings(config.get_config(), canister_name)?;
+    desired.controllers = desired_controllers(env, config.get_config(), canister_name)?;
+    let status = get_canister_status(env, canister_id, call_sender).await?;
+    Ok(diff_settings(&desired, &status.settings))
+}
+
+/// Updates the settings of canisters that differ from dfx.json.
+/// The differences are shown first, and applied only with the consent of the user.
+/// Controllers that would leave the caller unable to manage a canister are refused.
+#[context("Failed to reconcile the settings of canisters with dfx.json.")]
+pub async fn reconcile_settings(
+    env: &dyn Environment,
+    canisters: &[(String, Principal)],
+    call_sender: &CallSender,
+    skip_consent: bool,
+) -> DfxResult {
+    let log = env.get_logger();
+    let mut diffs = vec![];
+    for (canister_name, canister_id) in canisters {
+        let diff = get_settings_diff(env, canister_name, *canister_id, call_sender).await?;
+        if !diff.is_empty() {
+            diffs.push((canister_name, *canister_id, diff));
+        }
+    }
+    if diffs.is_empty() {
+        return Ok(());
+    }
+
+    info!(log, "The settings of some canisters differ from dfx.json:");
+    for (canister_name, _, diff) in &diffs {
+        for drift in &diff.drift {
+            info!(
+                log,
+                "  {} {}: {} -> {}", canister_name, drift.setting, drift.current, drift.desired
+            );
+        }
+        if let Some(controllers) = &diff.controllers {
+            for added in &controllers.added {
+                info!(log, "  {} controllers: add {}", canister_name, added);
+            }
+            for removed in &controllers.removed {
+                info!(log, "  {} controllers: remove {}", canister_name, removed);
+            }
+        }
+    }
+
+    let caller = match call_sender {
+        CallSender::SelectedId => env
+            .get_selected_identity_principal()
+            .context("Selected identity is not instantiated")?,
+        CallSender::Wallet(wallet) => *wallet,
+    };
+    for (canister_name, _, diff) in &diffs {
+        if let Some(controllers) = &diff.update.controllers {
+            if !controllers.contains(&caller) {
+                bail!(
+                    "The controllers of canister {} in dfx.json do not include {}, which deploys it. Updating them would lock it out of the canister. Add it to the controllers in dfx.json, or deploy as one of them.",
+                    canister_name,
+                    caller
+                );
+            }
+        }
+    }
+
+    if !skip_consent {
+        match ask_for_consent("This updates the settings of the canisters to match dfx.json.") {
+            Ok(()) => {}
+            Err(UserConsent::Declined) => {
+                warn!(
+                    log,
+                    "Kept the current settings. Run `dfx canister settings diff --all` to list the differences."
+                );
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+    for (canister_name, canister_id, diff) in diffs {
+        info!(
+            log,
+            "Updating the settings of canister {}...", canister_name
+        );
+        update_settings(env, canister_id, diff.update, call_sender).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_utils::interfaces::management_canister::attributes::FreezingThreshold;
+    use ic_utils::interfaces::management_canister::builders::WasmMemoryLimit;
+
+    fn current_settings() -> DefiniteCanisterSettings {
+        DefiniteCanisterSettings {
+            controllers: vec![],
+            compute_allocation: Nat::from(0u8),
+            memory_allocation: Nat::from(0u8),
+            freezing_threshold: Nat::from(2_592_000u64),
+            reserved_cycles_limit: Some(Nat::from(5_000_000_000_000u128)),
+            wasm_memory_limit: None,
+            log_visibility: LogVisibility::Controllers,
+        }
+    }
+
+    #[test]
+    fn settings_not_in_dfx_json_are_not_drift() {
+        let diff = diff_settings(&CanisterSettings::default(), &current_settings());
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn only_changed_settings_are_updated() {
+        let desired = CanisterSettings {
+            freezing_threshold: Some(FreezingThreshold::try_from(1234u64).unwrap()),
+            log_visibility: Some(LogVisibility::Controllers),
+            ..Default::default()
+        };
+        let diff = diff_settings(&desired, &current_settings());
+        assert_eq!(
+            diff.drift,
+            vec![SettingDrift {
+                setting: "freezing_threshold",
+                current: "2592000".to_string(),
+                desired: "1234".to_string(),
+            }]
+        );
+        assert!(diff.update.freezing_threshold.is_some());
+        assert!(diff.update.log_visibility.is_none());
+    }
+
+    #[test]
+    fn controllers_are_added_and_removed() {
+        let alice = Principal::from_slice(&[1]);
+        let bob = Principal::from_slice(&[2]);
+        let carol = Principal::from_slice(&[3]);
+        let current = DefiniteCanisterSettings {
+            controllers: vec![alice, bob],
+            ..current_settings()
+        };
+
+        let desired = CanisterSettings {
+            controllers: Some(vec![bob, alice]),
+            ..Default::default()
+        };
+        assert!(diff_settings(&desired, &current).is_empty());
+
+        let desired = CanisterSettings {
+            controllers: Some(vec![alice, carol]),
+            ..Default::default()
+        };
+        let diff = diff_settings(&desired, &current);
+        assert_eq!(
+            diff.controllers,
+            Some(ControllerChanges {
+                added: vec![carol],
+                removed: vec![bob],
+            })
+        );
+        assert_eq!(diff.update.controllers, Some(vec![alice, carol]));
+    }
+
+    #[test]
+    fn unset_limits_are_drift() {
+        let desired = CanisterSettings {
+            wasm_memory_limit: Some(WasmMemoryLimit::try_from(1024u64).unwrap()),
+            ..Default::default()
+        };
+        let diff = diff_settings(&desired, &current_settings());
+        assert_eq!(diff.drift[0].current, "not set");
+        assert_eq!(diff.drift[0].desired, "1024");
+    }
+}
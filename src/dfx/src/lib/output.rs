@@ -0,0 +1,56 @@
+//! Printing the results of commands, as text or, with `dfx --json`, as JSON.
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// The format in which commands print their results on stdout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Text meant to be read by people. It can change between releases.
+    #[default]
+    Text,
+    /// JSON with a documented schema, meant to be parsed by scripts.
+    Json,
+}
+
+/// Prints the result of a command: with its `Display` implementation, or as JSON.
+pub fn print_output<T: Serialize + Display>(env: &dyn Environment, output: &T) -> DfxResult {
+    print_output_with(env, output, |output| {
+        println!("{output}");
+        Ok(())
+    })
+}
+
+/// Prints the result of a command: with `print_text`, or as JSON.
+/// For results whose text output is more than a `Display` implementation, like output on stderr.
+pub fn print_output_with<T: Serialize>(
+    env: &dyn Environment,
+    output: &T,
+    print_text: impl FnOnce(&T) -> DfxResult,
+) -> DfxResult {
+    match env.get_output_format() {
+        OutputFormat::Text => print_text(output),
+        OutputFormat::Json => print_json(output),
+    }
+}
+
+/// Prints one of a stream of results of a command: with `print_text`, or as JSON on a single line.
+pub fn print_output_line_with<T: Serialize>(
+    env: &dyn Environment,
+    output: &T,
+    print_text: impl FnOnce(&T) -> DfxResult,
+) -> DfxResult {
+    match env.get_output_format() {
+        OutputFormat::Text => print_text(output),
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string(output)?);
+            Ok(())
+        }
+    }
+}
+
+fn print_json<T: Serialize + ?Sized>(output: &T) -> DfxResult {
+    println!("{}", serde_json::to_string_pretty(output)?);
+    Ok(())
+}
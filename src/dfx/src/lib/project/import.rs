@@ -1,6 +1,7 @@
 use crate::lib::error::ProjectError;
 use dfx_core::config::model::canister_id_store;
 use dfx_core::config::model::canister_id_store::CanisterIds;
+use dfx_core::config::model::canister_includes::{expand_include_pattern, is_glob_pattern};
 use dfx_core::config::model::dfinity::Config;
 use reqwest::{Client, StatusCode};
 use serde::Deserialize;
@@ -18,7 +19,17 @@ struct DfxJsonCanister {
 
 #[derive(Clone, Debug, Deserialize)]
 struct DfxJsonProject {
+    #[serde(default)]
     pub canisters: BTreeMap<String, DfxJsonCanister>,
+    #[serde(default)]
+    pub include: Vec<String>,
+}
+
+/// A canister definition, along with the location of the file that defines it.
+struct DefinedCanister {
+    name: String,
+    canister: DfxJsonCanister,
+    defined_in: Url,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -56,7 +67,7 @@ pub async fn import_canister_definitions(
     };
     info!(logger, "Importing {} from {}", what, their_dfx_json_url);
 
-    let their_project = loader.load_project_definition(&their_dfx_json_url).await?;
+    let their_canisters = loader.load_project_canisters(&their_dfx_json_url).await?;
     let their_canister_ids = loader
         .load_canister_ids(&their_canister_ids_json_url)
         .await?;
@@ -67,7 +78,12 @@ pub async fn import_canister_definitions(
 
     let config_canisters_object = get_canisters_json_object(config)?;
 
-    for (their_canister_name, their_canister) in their_project.canisters {
+    for DefinedCanister {
+        name: their_canister_name,
+        canister: their_canister,
+        defined_in,
+    } in their_canisters
+    {
         if matches!(import_only_canister_name, Some(ref n) if *n != their_canister_name) {
             continue;
         }
@@ -84,7 +100,7 @@ pub async fn import_canister_definitions(
             import_candid_definition(
                 logger,
                 &mut loader,
-                &their_dfx_json_url,
+                &defined_in,
                 &our_project_root,
                 their_relative_candid,
                 &our_canister_name,
@@ -112,18 +128,18 @@ pub async fn import_canister_definitions(
 async fn import_candid_definition(
     logger: &Logger,
     loader: &mut Loader,
-    their_dfx_json_url: &Url,
+    their_definition_url: &Url,
     our_project_root: &Path,
     their_relative_candid: &str,
     our_canister_name: &str,
     our_canister: &mut Map<String, Value>,
 ) -> Result<(), ProjectError> {
     let our_relative_candid_path = format!("candid/{}.did", our_canister_name);
-    let their_candid_url = their_dfx_json_url
+    let their_candid_url = their_definition_url
         .join(their_relative_candid)
         .map_err(|e| {
             ProjectError::InvalidUrl(
-                their_dfx_json_url.clone(),
+                their_definition_url.clone(),
                 their_relative_candid.to_string(),
                 e,
             )
@@ -253,6 +269,62 @@ impl Loader {
         Ok(project)
     }
 
+    /// Loads the canisters defined in a dfx.json and in the files it includes.
+    async fn load_project_canisters(
+        &mut self,
+        url: &Url,
+    ) -> Result<Vec<DefinedCanister>, ProjectError> {
+        let project = self.load_project_definition(url).await?;
+        let mut definitions = vec![(url.clone(), project.canisters)];
+        for pattern in &project.include {
+            for included_url in Self::include_pattern_urls(url, pattern)? {
+                let included = self.load_project_definition(&included_url).await?;
+                definitions.push((included_url, included.canisters));
+            }
+        }
+
+        let mut canisters: Vec<DefinedCanister> = vec![];
+        for (defined_in, defined) in definitions {
+            for (name, canister) in defined {
+                if canisters.iter().any(|c| c.name == name) {
+                    return Err(ProjectError::DuplicateCanisterDefinition(name, url.clone()));
+                }
+                canisters.push(DefinedCanister {
+                    name,
+                    canister,
+                    defined_in: defined_in.clone(),
+                });
+            }
+        }
+        Ok(canisters)
+    }
+
+    fn include_pattern_urls(dfx_json_url: &Url, pattern: &str) -> Result<Vec<Url>, ProjectError> {
+        if dfx_json_url.scheme() == "file" {
+            let dfx_json_path = dfx_json_url
+                .to_file_path()
+                .map_err(|_| ProjectError::ConvertingUrlToPathFailed(dfx_json_url.clone()))?;
+            let project_root = dfx_json_path.parent().unwrap();
+            expand_include_pattern(project_root, pattern)?
+                .into_iter()
+                .map(|path| {
+                    Url::from_file_path(&path)
+                        .map_err(|_| ProjectError::ConvertingPathToUrlFailed(path.clone()))
+                })
+                .collect()
+        } else if is_glob_pattern(pattern) {
+            Err(ProjectError::CannotExpandRemoteIncludePattern(
+                dfx_json_url.clone(),
+                pattern.to_string(),
+            ))
+        } else {
+            let included_url = dfx_json_url.join(pattern).map_err(|e| {
+                ProjectError::InvalidUrl(dfx_json_url.clone(), pattern.to_string(), e)
+            })?;
+            Ok(vec![included_url])
+        }
+    }
+
     async fn load_canister_ids(
         &mut self,
         url: &Url,
@@ -275,7 +347,10 @@ impl Loader {
         url: &Url,
     ) -> Result<Option<Vec<u8>>, ProjectError> {
         if url.scheme() == "file" {
-            Self::read_optional_file_contents(&PathBuf::from(url.path()))
+            let path = url
+                .to_file_path()
+                .map_err(|_| ProjectError::ConvertingUrlToPathFailed(url.clone()))?;
+            Self::read_optional_file_contents(&path)
         } else {
             self.get_optional_url_body(url).await
         }
@@ -0,0 +1,228 @@
+//! Breaking down the size of a Wasm module, for `dfx build --size-report`.
+use std::fmt::Write;
+use walrus::{ActiveDataLocation, DataKind, FunctionKind, IdsToIndices, Module};
+
+/// The number of functions and data segments listed in a size report.
+const LARGEST_COUNT: usize = 10;
+
+/// The sizes of the parts of a Wasm module.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WasmSizes {
+    /// The code, data and custom sections, and the other sections together.
+    pub sections: Vec<(String, usize)>,
+    /// The custom sections, by name. Canister metadata is stored in custom sections named `icp:public <name>` or `icp:private <name>`.
+    pub custom_sections: Vec<(String, usize)>,
+    /// The size of the body of each function defined in the module.
+    pub functions: Vec<FunctionSize>,
+    /// The size of the contents of each data segment.
+    pub data_segments: Vec<DataSegmentSize>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct FunctionSize {
+    pub index: u32,
+    /// The name from the `name` custom section, if the module has one.
+    pub name: Option<String>,
+    pub size: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DataSegmentSize {
+    pub index: u32,
+    /// The constant memory offset of an active segment.
+    pub offset: Option<i64>,
+    pub size: usize,
+}
+
+/// Measures the sections, functions and data segments of a Wasm module that was parsed from `module_size` bytes.
+/// The module must not have been modified since, because function sizes are taken from the parsed binary.
+pub fn measure(module: &Module, module_size: usize) -> WasmSizes {
+    let functions: Vec<FunctionSize> = module
+        .funcs
+        .iter()
+        .filter_map(|function| match &function.kind {
+            FunctionKind::Local(local) => local.original_range.as_ref().map(|range| FunctionSize {
+                index: function.id().index() as u32,
+                name: function.name.clone(),
+                size: range.end - range.start,
+            }),
+            _ => None,
+        })
+        .collect();
+    let data_segments: Vec<DataSegmentSize> = module
+        .data
+        .iter()
+        .enumerate()
+        .map(|(index, data)| DataSegmentSize {
+            index: index as u32,
+            offset: match &data.kind {
+                DataKind::Active(active) => match active.location {
+                    ActiveDataLocation::Absolute(offset) => Some(i64::from(offset)),
+                    ActiveDataLocation::Relative(_) => None,
+                },
+                DataKind::Passive => None,
+            },
+            size: data.value.len(),
+        })
+        .collect();
+    // the `name` and `producers` sections are parsed into the module, and count as other sections
+    let indices = IdsToIndices::default();
+    let custom_sections: Vec<(String, usize)> = module
+        .customs
+        .iter()
+        .map(|(_, section)| (section.name().to_string(), section.data(&indices).len()))
+        .collect();
+
+    let code = functions.iter().map(|f| f.size).sum();
+    let data = data_segments.iter().map(|d| d.size).sum();
+    let custom = custom_sections.iter().map(|(_, size)| size).sum();
+    let other = module_size.saturating_sub(code + data + custom);
+    let sections = [
+        ("code", code),
+        ("data", data),
+        ("custom", custom),
+        ("other", other),
+    ]
+    .into_iter()
+    .filter(|(_, size)| *size > 0)
+    .map(|(name, size)| (name.to_string(), size))
+    .collect();
+    WasmSizes {
+        sections,
+        custom_sections,
+        functions,
+        data_segments,
+    }
+}
+
+/// Formats a size report, from the sizes of the module after each post-processing step and the breakdown of the final module.
+pub fn format_report(steps: &[(&str, usize)], sizes: &WasmSizes) -> String {
+    let by_size = |mut rows: Vec<(String, usize)>| {
+        rows.sort_by(|(_, a), (_, b)| b.cmp(a));
+        rows
+    };
+    let largest = |rows| {
+        let mut rows = by_size(rows);
+        rows.truncate(LARGEST_COUNT);
+        rows
+    };
+    let functions = sizes
+        .functions
+        .iter()
+        .map(|f| {
+            let name = f
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("func[{}]", f.index));
+            (name, f.size)
+        })
+        .collect();
+    let data_segments = sizes
+        .data_segments
+        .iter()
+        .map(|d| {
+            let name = match d.offset {
+                Some(offset) => format!("data[{}] at offset {}", d.index, offset),
+                None => format!("data[{}]", d.index),
+            };
+            (name, d.size)
+        })
+        .collect();
+    let tables = [
+        (
+            "Post-processing",
+            steps
+                .iter()
+                .map(|(step, size)| (step.to_string(), *size))
+                .collect(),
+        ),
+        ("Sections", by_size(sizes.sections.clone())),
+        ("Custom sections", by_size(sizes.custom_sections.clone())),
+        ("Largest functions", largest(functions)),
+        ("Largest data segments", largest(data_segments)),
+    ];
+
+    let width = tables
+        .iter()
+        .flat_map(|(_, rows)| rows.iter().map(|(name, _)| name.len()))
+        .max()
+        .unwrap_or(0);
+    let mut report = String::new();
+    for (title, rows) in tables.iter().filter(|(_, rows)| !rows.is_empty()) {
+        let _ = writeln!(report, "{title}:");
+        for (name, size) in rows {
+            let _ = writeln!(report, "  {name:<width$} {size:>10} bytes");
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walrus::{ActiveData, FunctionBuilder, RawCustomSection};
+
+    #[test]
+    fn measures_wasm_module() {
+        let mut module = Module::default();
+        let memory = module.memories.add_local(false, 1, None);
+        let mut small = FunctionBuilder::new(&mut module.types, &[], &[]);
+        small.func_body();
+        small.finish(vec![], &mut module.funcs);
+        let mut big = FunctionBuilder::new(&mut module.types, &[], &[]);
+        big.name("big".to_string()).func_body().i32_const(1).drop();
+        big.finish(vec![], &mut module.funcs);
+        for (offset, value) in [(1024, vec![1, 2, 3]), (2048, vec![4])] {
+            module.data.add(
+                DataKind::Active(ActiveData {
+                    memory,
+                    location: ActiveDataLocation::Absolute(offset),
+                }),
+                value,
+            );
+        }
+        module.customs.add(RawCustomSection {
+            name: "icp:public candid:service".to_string(),
+            data: b"service : {}".to_vec(),
+        });
+        let bytes = module.emit_wasm();
+
+        let sizes = measure(&Module::from_buffer(&bytes).unwrap(), bytes.len());
+        let mut function_sizes: Vec<_> = sizes
+            .functions
+            .iter()
+            .map(|f| (f.name.as_deref(), f.size))
+            .collect();
+        function_sizes.sort();
+        // the sizes include the size prefix of the body
+        assert_eq!(function_sizes, [(None, 3), (Some("big"), 6)]);
+        assert_eq!(
+            sizes.data_segments,
+            [
+                DataSegmentSize {
+                    index: 0,
+                    offset: Some(1024),
+                    size: 3
+                },
+                DataSegmentSize {
+                    index: 1,
+                    offset: Some(2048),
+                    size: 1
+                },
+            ]
+        );
+        assert_eq!(
+            sizes.custom_sections,
+            [("icp:public candid:service".to_string(), 12)]
+        );
+        assert_eq!(
+            sizes.sections.iter().map(|(_, size)| size).sum::<usize>(),
+            bytes.len()
+        );
+
+        let report = format_report(&[("Build output", 100)], &sizes);
+        assert!(report.contains(
+            "Largest functions:\n  big                                6 bytes\n  func[1]                            3 bytes\n"
+        ));
+    }
+}
@@ -4,7 +4,8 @@ use crate::lib::diagnosis::{diagnose, Diagnosis};
 use crate::lib::environment::{Environment, EnvironmentImpl};
 use crate::lib::error::DfxResult;
 use crate::lib::logger::{create_root_logger, LoggingMode};
-use anyhow::Error;
+use crate::lib::output::OutputFormat;
+use anyhow::{bail, Error};
 use clap::{ArgAction, CommandFactory, Parser};
 use dfx_core::extension::manager::ExtensionManager;
 use std::collections::HashMap;
@@ -37,6 +38,10 @@ pub struct CliOpts {
     #[arg(long, global = true)]
     logfile: Option<String>,
 
+    /// Prints the results of commands as JSON instead of text. The JSON schema of each command is stable across releases.
+    #[arg(long, global = true)]
+    json: bool,
+
     /// The user identity to run this command as. It contains your principal as well as some things DFX associates with it like the wallet.
     #[arg(long, env = "DFX_IDENTITY", global = true)]
     identity: Option<String>,
@@ -136,6 +141,10 @@ fn inner_main() -> DfxResult {
 
     let cli_opts = CliOpts::parse_from(args);
 
+    if cli_opts.json && !cli_opts.command.supports_json() {
+        bail!("This command does not support --json. The commands that do are listed in the reference of the dfx command.");
+    }
+
     if matches!(cli_opts.command, commands::DfxCommand::Schema(_)) {
         return commands::exec_without_env(cli_opts.command);
     }
@@ -143,11 +152,17 @@ fn inner_main() -> DfxResult {
     let (verbose_level, log) = setup_logging(&cli_opts);
     let identity = cli_opts.identity;
     let effective_canister_id = cli_opts.provisional_create_canister_effective_canister_id;
+    let output_format = if cli_opts.json {
+        OutputFormat::Json
+    } else {
+        OutputFormat::Text
+    };
 
     let env = EnvironmentImpl::new(em)?
         .with_logger(log)
         .with_identity_override(identity)
         .with_verbose_level(verbose_level)
+        .with_output_format(output_format)
         .with_effective_canister_id(effective_canister_id);
 
     slog::trace!(
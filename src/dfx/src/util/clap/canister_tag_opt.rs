@@ -0,0 +1,68 @@
+use crate::lib::error::DfxResult;
+use anyhow::bail;
+use clap::Args;
+use dfx_core::config::model::dfinity::ConfigInterface;
+use std::collections::BTreeSet;
+
+/// Selects canisters by the `tags` defined for them in dfx.json.
+#[derive(Args, Clone, Debug, Default)]
+pub struct CanisterTagOpt {
+    /// Selects the canisters that have this tag in dfx.json.
+    /// Can be specified more than once.
+    #[arg(long, value_name = "TAG")]
+    tag: Vec<String>,
+
+    /// Leaves out the canisters that have this tag in dfx.json.
+    /// Fails if a selected canister depends on one of them.
+    /// Can be specified more than once.
+    #[arg(long, value_name = "TAG")]
+    exclude: Vec<String>,
+}
+
+impl CanisterTagOpt {
+    /// Whether --tag or --exclude was given.
+    pub fn is_set(&self) -> bool {
+        !self.tag.is_empty() || !self.exclude.is_empty()
+    }
+
+    /// Fails if --tag or --exclude was combined with a single canister.
+    pub fn ensure_no_canister(&self, canister: Option<&str>) -> DfxResult {
+        if self.is_set() && canister.is_some() {
+            bail!("--tag and --exclude cannot be used together with a canister name.");
+        }
+        Ok(())
+    }
+
+    /// Returns the names of the selected canisters, without their dependencies.
+    /// Without --tag, all canisters that are not excluded are selected.
+    pub fn select(&self, config: &ConfigInterface) -> DfxResult<Vec<String>> {
+        Ok(config.get_canister_names_with_tags(&self.tag, &self.exclude)?)
+    }
+
+    /// Returns the names of the selected canisters and of all canisters they depend on in dfx.json.
+    /// Without --tag or --exclude, selects `canister`, or all canisters if it is `None`.
+    /// Fails if a selected canister depends on a canister that was left out with --exclude.
+    pub fn select_with_dependencies(
+        &self,
+        config: &ConfigInterface,
+        canister: Option<&str>,
+    ) -> DfxResult<Vec<String>> {
+        if !self.is_set() {
+            return Ok(config.get_canister_names_with_dependencies(canister)?);
+        }
+        let mut names = BTreeSet::new();
+        for selected in self.select(config)? {
+            for dependency in config.get_canister_names_with_dependencies(Some(&selected))? {
+                if config.canister_has_any_tag(&dependency, &self.exclude) {
+                    bail!(
+                        "Canister '{}' depends on canister '{}', which is left out with --exclude.",
+                        selected,
+                        dependency
+                    );
+                }
+                names.insert(dependency);
+            }
+        }
+        Ok(names.into_iter().collect())
+    }
+}
@@ -188,6 +188,16 @@ pub fn icrc_subaccount_parser(subaccount: &str) -> Result<Subaccount, String> {
     Err("Failed to parse subaccount. Expected 32 bytes of hex-encoded data.".to_string())
 }
 
+pub fn snapshot_id_parser(snapshot_id: &str) -> Result<String, String> {
+    if snapshot_id.is_empty() || snapshot_id.len() % 2 != 0 {
+        Err("Snapshot id must consist of an even number of hex digits".to_string())
+    } else if snapshot_id.contains(|c: char| !c.is_ascii_hexdigit()) {
+        Err("Snapshot id must contain only hex digits".to_string())
+    } else {
+        Ok(snapshot_id.to_string())
+    }
+}
+
 pub fn hsm_key_id_parser(key_id: &str) -> Result<String, String> {
     if key_id.len() % 2 != 0 {
         Err("Key id must consist of an even number of hex digits".to_string())
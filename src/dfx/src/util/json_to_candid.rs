@@ -0,0 +1,259 @@
+//! Converting JSON values to Candid values, guided by the Candid types of a method.
+use crate::lib::error::DfxResult;
+use anyhow::{bail, Context};
+use candid::types::value::{IDLField, IDLValue, VariantValue};
+use candid::types::{Label, Type, TypeEnv, TypeInner};
+use candid::{IDLArgs, Int, Nat, Principal};
+use serde_json::Value as JsonValue;
+use std::str::FromStr;
+
+/// Converts JSON values, one per argument, to Candid arguments of the given types.
+pub fn json_to_idl_args(env: &TypeEnv, types: &[Type], values: &[JsonValue]) -> DfxResult<IDLArgs> {
+    if types.len() != values.len() {
+        bail!(
+            "Expected {} arguments, but found {}.",
+            types.len(),
+            values.len()
+        );
+    }
+    let args = types
+        .iter()
+        .zip(values)
+        .enumerate()
+        .map(|(i, (ty, value))| {
+            json_to_idl(env, ty, value).with_context(|| format!("Invalid argument {}.", i + 1))
+        })
+        .collect::<DfxResult<Vec<_>>>()?;
+    Ok(IDLArgs::new(&args))
+}
+
+/// Converts a JSON value to a Candid value of the given type.
+///
+/// Numbers can be given as JSON numbers or as strings, for values that do not fit the precision of JSON numbers.
+/// Records are JSON objects, or arrays for tuples. Variants are objects with a single field,
+/// or strings for the cases without a value. Blobs are arrays of bytes, or strings of hex digits.
+/// Optional values are `null` or the value itself, or `[]` and `[value]` like in the output of idl2json.
+pub fn json_to_idl(env: &TypeEnv, ty: &Type, value: &JsonValue) -> DfxResult<IDLValue> {
+    let ty = env.trace_type(ty)?;
+    let idl_value = match (ty.as_ref(), value) {
+        (TypeInner::Reserved, _) => IDLValue::Reserved,
+        (TypeInner::Null, JsonValue::Null) => IDLValue::Null,
+        (TypeInner::Bool, JsonValue::Bool(b)) => IDLValue::Bool(*b),
+        (TypeInner::Text, JsonValue::String(s)) => IDLValue::Text(s.clone()),
+        (TypeInner::Principal, JsonValue::String(s)) => IDLValue::Principal(
+            Principal::from_text(s).with_context(|| format!("Invalid principal '{}'.", s))?,
+        ),
+        (_, JsonValue::Number(n)) if is_number(&ty) => number(&ty, &n.to_string())?,
+        (_, JsonValue::String(s)) if is_number(&ty) => number(&ty, s)?,
+        (TypeInner::Opt(_), JsonValue::Null) => IDLValue::None,
+        (TypeInner::Opt(_), JsonValue::Array(items)) if items.is_empty() => IDLValue::None,
+        (TypeInner::Opt(inner), JsonValue::Array(items)) if items.len() == 1 => {
+            // for optional arrays, `[value]` can also be the array itself
+            let value =
+                json_to_idl(env, inner, &items[0]).or_else(|_| json_to_idl(env, inner, value))?;
+            IDLValue::Opt(Box::new(value))
+        }
+        (TypeInner::Opt(inner), value) => IDLValue::Opt(Box::new(json_to_idl(env, inner, value)?)),
+        (TypeInner::Vec(inner), JsonValue::String(s))
+            if env.trace_type(inner)?.as_ref() == &TypeInner::Nat8 =>
+        {
+            IDLValue::Blob(hex::decode(s).with_context(|| format!("Invalid blob '{}'.", s))?)
+        }
+        (TypeInner::Vec(inner), JsonValue::Array(items)) => IDLValue::Vec(
+            items
+                .iter()
+                .map(|item| json_to_idl(env, inner, item))
+                .collect::<DfxResult<_>>()?,
+        ),
+        (TypeInner::Record(fields), JsonValue::Array(items)) if fields.len() == items.len() => {
+            IDLValue::Record(
+                fields
+                    .iter()
+                    .zip(items)
+                    .map(|(field, item)| {
+                        Ok(IDLField {
+                            id: (*field.id).clone(),
+                            val: json_to_idl(env, &field.ty, item)?,
+                        })
+                    })
+                    .collect::<DfxResult<_>>()?,
+            )
+        }
+        (TypeInner::Record(fields), JsonValue::Object(map)) => IDLValue::Record(
+            fields
+                .iter()
+                .map(|field| {
+                    let name = label_name(&field.id);
+                    let val = match map.get(&name) {
+                        Some(value) => json_to_idl(env, &field.ty, value)
+                            .with_context(|| format!("Invalid field '{}'.", name))?,
+                        None => match env.trace_type(&field.ty)?.as_ref() {
+                            TypeInner::Opt(_) => IDLValue::None,
+                            TypeInner::Null => IDLValue::Null,
+                            TypeInner::Reserved => IDLValue::Reserved,
+                            _ => bail!("Missing field '{}'.", name),
+                        },
+                    };
+                    Ok(IDLField {
+                        id: (*field.id).clone(),
+                        val,
+                    })
+                })
+                .collect::<DfxResult<_>>()?,
+        ),
+        (TypeInner::Variant(fields), JsonValue::String(s)) => {
+            variant(env, fields, s, &JsonValue::Null)?
+        }
+        (TypeInner::Variant(fields), JsonValue::Object(map)) if map.len() == 1 => {
+            let (name, value) = map.iter().next().unwrap();
+            variant(env, fields, name, value)?
+        }
+        _ => bail!("Expected a value of type {}, but found {}.", ty, value),
+    };
+    Ok(idl_value)
+}
+
+fn variant(
+    env: &TypeEnv,
+    fields: &[candid::types::Field],
+    name: &str,
+    value: &JsonValue,
+) -> DfxResult<IDLValue> {
+    let (index, field) = fields
+        .iter()
+        .enumerate()
+        .find(|(_, field)| label_name(&field.id) == name)
+        .with_context(|| format!("Unknown variant case '{}'.", name))?;
+    let val = json_to_idl(env, &field.ty, value)?;
+    Ok(IDLValue::Variant(VariantValue(
+        Box::new(IDLField {
+            id: (*field.id).clone(),
+            val,
+        }),
+        index as u64,
+    )))
+}
+
+/// The name of a field in JSON: its name, or its number for tuple fields and fields with numeric labels.
+fn label_name(label: &Label) -> String {
+    match label {
+        Label::Named(name) => name.clone(),
+        Label::Id(id) | Label::Unnamed(id) => id.to_string(),
+    }
+}
+
+fn is_number(ty: &Type) -> bool {
+    matches!(
+        ty.as_ref(),
+        TypeInner::Nat
+            | TypeInner::Nat8
+            | TypeInner::Nat16
+            | TypeInner::Nat32
+            | TypeInner::Nat64
+            | TypeInner::Int
+            | TypeInner::Int8
+            | TypeInner::Int16
+            | TypeInner::Int32
+            | TypeInner::Int64
+            | TypeInner::Float32
+            | TypeInner::Float64
+    )
+}
+
+fn number(ty: &Type, s: &str) -> DfxResult<IDLValue> {
+    let invalid = || format!("Invalid {} '{}'.", ty, s);
+    let value = match ty.as_ref() {
+        TypeInner::Nat => IDLValue::Nat(Nat::from_str(s).ok().with_context(invalid)?),
+        TypeInner::Nat8 => IDLValue::Nat8(s.parse().with_context(invalid)?),
+        TypeInner::Nat16 => IDLValue::Nat16(s.parse().with_context(invalid)?),
+        TypeInner::Nat32 => IDLValue::Nat32(s.parse().with_context(invalid)?),
+        TypeInner::Nat64 => IDLValue::Nat64(s.parse().with_context(invalid)?),
+        TypeInner::Int => IDLValue::Int(Int::from_str(s).ok().with_context(invalid)?),
+        TypeInner::Int8 => IDLValue::Int8(s.parse().with_context(invalid)?),
+        TypeInner::Int16 => IDLValue::Int16(s.parse().with_context(invalid)?),
+        TypeInner::Int32 => IDLValue::Int32(s.parse().with_context(invalid)?),
+        TypeInner::Int64 => IDLValue::Int64(s.parse().with_context(invalid)?),
+        TypeInner::Float32 => IDLValue::Float32(s.parse().with_context(invalid)?),
+        TypeInner::Float64 => IDLValue::Float64(s.parse().with_context(invalid)?),
+        _ => unreachable!(),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid_parser::utils::CandidSource;
+    use serde_json::json;
+
+    fn method_args(did: &str, method: &str) -> (TypeEnv, Vec<Type>) {
+        let (env, actor) = CandidSource::Text(did).load().unwrap();
+        let func = env.get_method(&actor.unwrap(), method).unwrap().clone();
+        (env, func.args)
+    }
+
+    #[test]
+    fn converts_json_to_candid_arguments() {
+        let did = r#"
+            type Color = variant { red; rgb : record { nat8; nat8; nat8 } };
+            service : {
+                paint : (record { name : text; size : opt nat; color : Color }, principal, blob) -> ();
+            }
+        "#;
+        let (env, types) = method_args(did, "paint");
+        let args = json_to_idl_args(
+            &env,
+            &types,
+            &[
+                json!({ "name": "wall", "color": { "rgb": [255, 0, 0] } }),
+                json!("aaaaa-aa"),
+                json!("c0ffee"),
+            ],
+        )
+        .unwrap();
+        let expected = candid_parser::parse_idl_args(
+            r#"(record { name = "wall"; size = null; color = variant { rgb = record { 255; 0; 0 } } }, principal "aaaaa-aa", blob "\c0\ff\ee")"#,
+        )
+        .unwrap();
+        assert_eq!(
+            args.to_bytes_with_types(&env, &types).unwrap(),
+            expected.to_bytes_with_types(&env, &types).unwrap()
+        );
+    }
+
+    #[test]
+    fn converts_the_output_of_idl2json_back() {
+        let did = r#"
+            type Color = variant { red; rgb : record { nat8; nat8; nat8 } };
+            service : {
+                paint : (record { name : text; size : opt nat; label : opt text; tags : opt vec text; color : opt Color }) -> ();
+            }
+        "#;
+        let (env, types) = method_args(did, "paint");
+        let original = candid_parser::parse_idl_args(
+            r#"(record { name = "wall"; size = opt 42; label = null; tags = opt vec { "a"; "b" }; color = opt variant { rgb = record { 255; 0; 0 } } })"#,
+        )
+        .unwrap()
+        .annotate_types(true, &env, &types)
+        .unwrap();
+        let json = idl2json::idl2json(&original.args[0], &idl2json::Idl2JsonOptions::default());
+        assert_eq!(json["size"].as_array().map(Vec::len), Some(1));
+        assert_eq!(json["label"], json!([]));
+
+        let args = json_to_idl_args(&env, &types, &[json]).unwrap();
+        assert_eq!(
+            args.to_bytes_with_types(&env, &types).unwrap(),
+            original.to_bytes_with_types(&env, &types).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_json_of_the_wrong_type() {
+        let did = "service : { set : (nat8, variant { a; b }) -> () }";
+        let (env, types) = method_args(did, "set");
+        assert!(json_to_idl_args(&env, &types, &[json!(256), json!("a")]).is_err());
+        assert!(json_to_idl_args(&env, &types, &[json!(1), json!("c")]).is_err());
+        assert!(json_to_idl_args(&env, &types, &[json!(1)]).is_err());
+        assert!(json_to_idl_args(&env, &types, &[json!("1"), json!("b")]).is_ok());
+    }
+}
@@ -9,6 +9,7 @@ use candid::types::{value::IDLValue, Function, Type, TypeEnv, TypeInner};
 use candid::{Decode, Encode, IDLArgs, Principal};
 use candid_parser::error::pretty_wrap;
 use candid_parser::utils::CandidSource;
+use dfx_core::fs::composite::ensure_dir_exists;
 use dfx_core::fs::create_dir_all;
 use fn_error_context::context;
 use idl2json::{idl2json, Idl2JsonOptions};
@@ -17,7 +18,7 @@ use reqwest::{Client, StatusCode, Url};
 use rust_decimal::Decimal;
 use socket2::{Domain, Socket};
 use std::collections::BTreeMap;
-use std::io::{stderr, stdin, stdout, IsTerminal, Read};
+use std::io::{stderr, stdin, stdout, IsTerminal, Read, Write};
 use std::net::{IpAddr, SocketAddr, TcpListener};
 use std::path::Path;
 use std::time::Duration;
@@ -25,6 +26,7 @@ use std::time::Duration;
 pub mod assets;
 pub mod clap;
 pub mod currency_conversion;
+pub mod json_to_candid;
 pub mod stderr_wrapper;
 pub mod url;
 
@@ -407,6 +409,19 @@ pub async fn download_file_to_path(from: &Url, to: &Path) -> DfxResult {
     Ok(())
 }
 
+#[context("Failed to write to a tempfile then rename it to {}", path.display())]
+pub fn write_to_tempfile_then_rename(content: &[u8], path: &Path) -> DfxResult {
+    assert!(path.is_absolute());
+    let dir = dfx_core::fs::parent(path)?;
+    ensure_dir_exists(&dir)?;
+    let mut f = tempfile::NamedTempFile::new_in(&dir)
+        .with_context(|| format!("Failed to create a NamedTempFile in {dir:?}"))?;
+    f.write_all(content)
+        .with_context(|| format!("Failed to write the NamedTempFile at {:?}", f.path()))?;
+    dfx_core::fs::rename(f.path(), path)?;
+    Ok(())
+}
+
 #[context("Failed to download from url: {}.", from)]
 pub async fn download_file(from: &Url) -> DfxResult<Vec<u8>> {
     let client = reqwest::Client::builder()